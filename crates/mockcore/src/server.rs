@@ -20,6 +20,16 @@ impl Server {
     self.state.lock().unwrap()
   }
 
+  fn private_keys_enabled(&self) -> bool {
+    let state = self.state();
+
+    state
+      .loaded_wallets
+      .first()
+      .map(|wallet_name| !state.watch_only_wallets.contains(wallet_name))
+      .unwrap_or(true)
+  }
+
   fn not_found() -> jsonrpc_core::Error {
     jsonrpc_core::Error::new(jsonrpc_core::types::error::ErrorCode::ServerError(-8))
   }
@@ -286,7 +296,9 @@ impl Api for Server {
   }
 
   fn get_wallet_info(&self) -> Result<GetWalletInfoResult, jsonrpc_core::Error> {
-    if let Some(wallet_name) = self.state().loaded_wallets.first().cloned() {
+    let wallet_name = self.state().loaded_wallets.first().cloned();
+
+    if let Some(wallet_name) = wallet_name {
       Ok(GetWalletInfoResult {
         avoid_reuse: None,
         balance: Amount::from_sat(0),
@@ -296,7 +308,7 @@ impl Api for Server {
         keypool_size: 0,
         keypool_size_hd_internal: 0,
         pay_tx_fee: Amount::from_sat(0),
-        private_keys_enabled: true,
+        private_keys_enabled: self.private_keys_enabled(),
         scanning: None,
         tx_count: 0,
         unconfirmed_balance: Amount::from_sat(0),
@@ -346,12 +358,17 @@ impl Api for Server {
   fn create_wallet(
     &self,
     name: String,
-    _disable_private_keys: Option<bool>,
+    disable_private_keys: Option<bool>,
     _blank: Option<bool>,
     _passphrase: Option<String>,
     _avoid_reuse: Option<bool>,
   ) -> Result<LoadWalletResult, jsonrpc_core::Error> {
     self.state().wallets.insert(name.clone());
+
+    if disable_private_keys == Some(true) {
+      self.state().watch_only_wallets.insert(name.clone());
+    }
+
     Ok(LoadWalletResult {
       name,
       warning: None,
@@ -488,16 +505,21 @@ impl Api for Server {
     assert_eq!(sighash_type, None, "sighash_type param not supported");
 
     let mut transaction: Transaction = deserialize(&hex::decode(tx).unwrap()).unwrap();
-    for input in &mut transaction.input {
-      if input.witness.is_empty() {
-        input.witness = Witness::from_slice(&[&[0; 64]]);
+
+    let complete = self.private_keys_enabled();
+
+    if complete {
+      for input in &mut transaction.input {
+        if input.witness.is_empty() {
+          input.witness = Witness::from_slice(&[&[0; 64]]);
+        }
       }
     }
 
     Ok(
       serde_json::to_value(SignRawTransactionResult {
         hex: serialize(&transaction),
-        complete: true,
+        complete,
         errors: None,
       })
       .unwrap(),
@@ -530,6 +552,14 @@ impl Api for Server {
       }
     }
 
+    state.mempool.retain(|mempool_tx| {
+      !mempool_tx.input.iter().any(|mempool_tx_in| {
+        tx.input
+          .iter()
+          .any(|tx_in| tx_in.previous_output == mempool_tx_in.previous_output)
+      })
+    });
+
     state.mempool.push(tx.clone());
 
     Ok(tx.txid().to_string())
@@ -671,8 +701,13 @@ impl Api for Server {
 
     let blockhash = tx_height.map(|tx_height| state.hashes[usize::try_from(*tx_height).unwrap()]);
 
+    let transaction = state
+      .transactions
+      .get(&txid)
+      .or_else(|| state.mempool.iter().find(|tx| tx.txid() == txid));
+
     if verbose.unwrap_or(false) {
-      match state.transactions.get(&txid) {
+      match transaction {
         Some(transaction) => Ok(
           serde_json::to_value(GetRawTransactionResult {
             in_active_chain: Some(true),
@@ -711,13 +746,92 @@ impl Api for Server {
         None => Err(Self::not_found()),
       }
     } else {
-      match state.transactions.get(&txid) {
+      match transaction {
         Some(tx) => Ok(Value::String(hex::encode(serialize(tx)))),
         None => Err(Self::not_found()),
       }
     }
   }
 
+  fn get_mempool_entry(&self, txid: Txid) -> Result<GetMempoolEntryResult, jsonrpc_core::Error> {
+    let state = self.state();
+
+    let Some(tx) = state.mempool.iter().find(|tx| tx.txid() == txid) else {
+      return Err(Self::not_found());
+    };
+
+    let fee = |tx: &Transaction| {
+      tx.input
+        .iter()
+        .map(|tx_in| {
+          state
+            .transactions
+            .get(&tx_in.previous_output.txid)
+            .or_else(|| {
+              state
+                .mempool
+                .iter()
+                .find(|mempool_tx| mempool_tx.txid() == tx_in.previous_output.txid)
+            })
+            .unwrap()
+            .output[usize::try_from(tx_in.previous_output.vout).unwrap()]
+          .value
+        })
+        .sum::<u64>()
+        - tx.output.iter().map(|tx_out| tx_out.value).sum::<u64>()
+    };
+
+    let depends = state
+      .mempool
+      .iter()
+      .filter(|mempool_tx| {
+        tx.input
+          .iter()
+          .any(|tx_in| tx_in.previous_output.txid == mempool_tx.txid())
+      })
+      .collect::<Vec<&Transaction>>();
+
+    let spent_by = state
+      .mempool
+      .iter()
+      .filter(|mempool_tx| {
+        mempool_tx
+          .input
+          .iter()
+          .any(|tx_in| tx_in.previous_output.txid == txid)
+      })
+      .map(|mempool_tx| mempool_tx.txid())
+      .collect::<Vec<Txid>>();
+
+    let vsize = u64::try_from(tx.vsize()).unwrap();
+
+    Ok(GetMempoolEntryResult {
+      vsize,
+      weight: Some(tx.weight().to_wu()),
+      time: 0,
+      height: u64::try_from(state.hashes.len() - 1).unwrap(),
+      descendant_count: u64::try_from(spent_by.len()).unwrap() + 1,
+      descendant_size: vsize,
+      ancestor_count: u64::try_from(depends.len()).unwrap() + 1,
+      ancestor_size: vsize
+        + depends
+          .iter()
+          .map(|tx| u64::try_from(tx.vsize()).unwrap())
+          .sum::<u64>(),
+      wtxid: Txid::from_raw_hash(tx.wtxid().to_raw_hash()),
+      fees: GetMempoolEntryResultFees {
+        base: Amount::from_sat(fee(tx)),
+        modified: Amount::from_sat(fee(tx)),
+        ancestor: Amount::from_sat(fee(tx) + depends.iter().map(|tx| fee(tx)).sum::<u64>()),
+        descendant: Amount::from_sat(fee(tx)),
+      },
+      depends: depends.iter().map(|tx| tx.txid()).collect(),
+      spent_by,
+      bip125_replaceable: tx.is_explicitly_rbf(),
+      unbroadcast: None,
+    })
+  }
+
   fn list_unspent(
     &self,
     minconf: Option<usize>,
@@ -726,7 +840,10 @@ impl Api for Server {
     include_unsafe: Option<bool>,
     query_options: Option<String>,
   ) -> Result<Vec<ListUnspentResultEntry>, jsonrpc_core::Error> {
-    assert_eq!(minconf, None, "minconf param not supported");
+    assert!(
+      matches!(minconf, None | Some(0)),
+      "minconf param not supported"
+    );
     assert_eq!(maxconf, None, "maxconf param not supported");
     assert_eq!(address, None, "address param not supported");
     assert_eq!(include_unsafe, None, "include_unsafe param not supported");
@@ -734,10 +851,18 @@ impl Api for Server {
 
     let state = self.state();
 
+    let spent_in_mempool = |outpoint: &OutPoint| {
+      state.mempool.iter().any(|tx| {
+        tx.input
+          .iter()
+          .any(|tx_in| tx_in.previous_output == *outpoint)
+      })
+    };
+
     let mut unspent = Vec::new();
 
     for (outpoint, &amount) in &state.utxos {
-      if state.locked.contains(outpoint) {
+      if state.locked.contains(outpoint) || spent_in_mempool(outpoint) {
         continue;
       }
 
@@ -770,6 +895,45 @@ impl Api for Server {
       });
     }
 
+    if minconf == Some(0) {
+      for tx in &state.mempool {
+        for (vout, tx_out) in tx.output.iter().enumerate() {
+          let outpoint = OutPoint {
+            txid: tx.txid(),
+            vout: vout.try_into().unwrap(),
+          };
+
+          if state.locked.contains(&outpoint) || spent_in_mempool(&outpoint) {
+            continue;
+          }
+
+          let Ok(address) = Address::from_script(&tx_out.script_pubkey, state.network) else {
+            continue;
+          };
+
+          if !state.is_wallet_address(&address) {
+            continue;
+          }
+
+          unspent.push(ListUnspentResultEntry {
+            txid: outpoint.txid,
+            vout: outpoint.vout,
+            address: None,
+            label: None,
+            redeem_script: None,
+            witness_script: None,
+            script_pub_key: tx_out.script_pubkey.clone(),
+            amount: Amount::from_sat(tx_out.value),
+            confirmations: 0,
+            spendable: true,
+            solvable: true,
+            descriptor: None,
+            safe: true,
+          });
+        }
+      }
+    }
+
     Ok(unspent)
   }
 
@@ -878,8 +1042,6 @@ impl Api for Server {
     unlock: bool,
     outputs: Vec<JsonOutPoint>,
   ) -> Result<bool, jsonrpc_core::Error> {
-    assert!(!unlock);
-
     let mut state = self.state();
 
     if state.fail_lock_unspent {
@@ -891,7 +1053,12 @@ impl Api for Server {
         vout: output.vout,
         txid: output.txid,
       };
-      assert!(state.locked.insert(output));
+
+      if unlock {
+        assert!(state.locked.remove(&output));
+      } else {
+        assert!(state.locked.insert(output));
+      }
     }
 
     Ok(true)
@@ -970,21 +1137,18 @@ impl Api for Server {
     .unwrap();
 
     for (i, txin) in psbt.unsigned_tx.input.iter().enumerate() {
-      psbt.inputs[i].witness_utxo = Some(
-        self
-          .state()
-          .transactions
-          .get(&txin.previous_output.txid)
-          .unwrap()
-          .output[txin.previous_output.vout as usize]
-          .clone(),
-      );
+      if let Some(transaction) = self.state().transactions.get(&txin.previous_output.txid) {
+        psbt.inputs[i].witness_utxo =
+          Some(transaction.output[txin.previous_output.vout as usize].clone());
+      }
     }
 
     if let Some(sign) = sign {
-      if sign {
+      if sign && self.private_keys_enabled() {
         for input in psbt.inputs.iter_mut() {
-          input.final_script_witness = Some(Witness::from_slice(&[&[0; 64]]));
+          if input.final_script_witness.is_none() {
+            input.final_script_witness = Some(Witness::from_slice(&[&[0; 64]]));
+          }
         }
       }
     }
@@ -1000,17 +1164,20 @@ impl Api for Server {
     psbt: String,
     _extract: Option<bool>,
   ) -> Result<FinalizePsbtResult, jsonrpc_core::Error> {
-    let mut transaction = Psbt::deserialize(
+    let psbt = Psbt::deserialize(
       &base64::engine::general_purpose::STANDARD
         .decode(psbt)
         .unwrap(),
     )
-    .unwrap()
-    .unsigned_tx;
+    .unwrap();
+
+    let mut transaction = psbt.unsigned_tx;
 
-    for input in &mut transaction.input {
-      if input.witness.is_empty() {
-        input.witness = Witness::from_slice(&[&[0; 64]]);
+    for (txin, input) in transaction.input.iter_mut().zip(psbt.inputs) {
+      if let Some(witness) = input.final_script_witness {
+        txin.witness = witness;
+      } else if txin.witness.is_empty() {
+        txin.witness = Witness::from_slice(&[&[0; 64]]);
       }
     }
 
@@ -25,6 +25,7 @@ pub struct State {
   pub receive_addresses: Vec<Address>,
   pub change_addresses: Vec<Address>,
   pub wallets: BTreeSet<String>,
+  pub watch_only_wallets: BTreeSet<String>,
 }
 
 impl State {
@@ -54,6 +55,7 @@ impl State {
       utxos: BTreeMap::new(),
       version,
       wallets: BTreeSet::new(),
+      watch_only_wallets: BTreeSet::new(),
     }
   }
 
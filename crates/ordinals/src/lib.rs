@@ -56,6 +56,8 @@ mod rune_id;
 mod runestone;
 mod sat;
 mod sat_point;
+#[cfg(feature = "schemars")]
+mod schema;
 mod spaced_rune;
 mod terms;
 pub mod varint;
@@ -0,0 +1,104 @@
+//! JSON schemas for types which are serialized as strings.
+
+use {
+  super::*,
+  schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+  },
+};
+
+fn string(description: &str, pattern: Option<&str>, values: Vec<String>) -> Schema {
+  SchemaObject {
+    instance_type: Some(InstanceType::String.into()),
+    enum_values: (!values.is_empty()).then(|| values.into_iter().map(Into::into).collect()),
+    metadata: Some(Box::new(Metadata {
+      description: Some(description.into()),
+      ..Default::default()
+    })),
+    string: pattern.map(|pattern| {
+      Box::new(StringValidation {
+        pattern: Some(pattern.into()),
+        ..Default::default()
+      })
+    }),
+    ..Default::default()
+  }
+  .into()
+}
+
+macro_rules! string_schema {
+  ($type:ty, $description:literal, $pattern:expr, $values:expr) => {
+    impl JsonSchema for $type {
+      fn schema_name() -> String {
+        stringify!($type).into()
+      }
+
+      fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string($description, $pattern, $values)
+      }
+    }
+  };
+}
+
+string_schema!(
+  Charm,
+  "Inscription charm",
+  None,
+  Charm::ALL.iter().map(Charm::to_string).collect()
+);
+
+string_schema!(
+  Rarity,
+  "Sat rarity",
+  None,
+  [
+    Rarity::Common,
+    Rarity::Uncommon,
+    Rarity::Rare,
+    Rarity::Epic,
+    Rarity::Legendary,
+    Rarity::Mythic,
+  ]
+  .iter()
+  .map(Rarity::to_string)
+  .collect()
+);
+
+string_schema!(Rune, "Rune name", Some("^[A-Z]+$"), Vec::new());
+
+string_schema!(
+  RuneId,
+  "Rune ID, the block height and transaction index of the etching",
+  Some("^[0-9]+:[0-9]+$"),
+  Vec::new()
+);
+
+string_schema!(
+  SatPoint,
+  "Sat location, an outpoint and offset into that output",
+  Some("^[0-9a-f]{64}:[0-9]+:[0-9]+$"),
+  Vec::new()
+);
+
+string_schema!(
+  SpacedRune,
+  "Rune name with optional `•` spacers",
+  Some("^[A-Z](•?[A-Z])*$"),
+  Vec::new()
+);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn charm_schema_lists_all_charms() {
+    let schema = serde_json::to_value(schemars::schema_for!(Charm)).unwrap();
+
+    assert_eq!(schema["type"], "string");
+    assert_eq!(schema["enum"].as_array().unwrap().len(), Charm::ALL.len());
+    assert_eq!(schema["enum"][0], "coin");
+  }
+}
@@ -8,13 +8,26 @@ pub use crate::templates::{
   TransactionHtml as Transaction,
 };
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) mod schema;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AddressInfo {
+  pub inscriptions: Vec<InscriptionId>,
+  #[schemars(with = "Vec<schema::OutPoint>")]
+  pub outputs: Vec<OutPoint>,
+  pub runes_balances: Vec<(SpacedRune, Pile)>,
+  pub sat_balance: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Block {
   pub best_height: u32,
+  #[schemars(with = "schema::BlockHash")]
   pub hash: BlockHash,
   pub height: u32,
   pub inscriptions: Vec<InscriptionId>,
   pub runes: Vec<SpacedRune>,
+  #[schemars(with = "schema::BlockHash")]
   pub target: BlockHash,
 }
 
@@ -37,15 +50,17 @@ impl Block {
   }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BlockInfo {
   pub average_fee: u64,
   pub average_fee_rate: u64,
   pub bits: u32,
   #[serde(with = "SerHex::<Strict>")]
+  #[schemars(with = "schema::Hash")]
   pub chainwork: [u8; 32],
   pub confirmations: i32,
   pub difficulty: f64,
+  #[schemars(with = "schema::BlockHash")]
   pub hash: BlockHash,
   pub height: u32,
   pub max_fee: u64,
@@ -53,13 +68,17 @@ pub struct BlockInfo {
   pub max_tx_size: u32,
   pub median_fee: u64,
   pub median_time: Option<u64>,
+  #[schemars(with = "schema::TxMerkleNode")]
   pub merkle_root: TxMerkleNode,
   pub min_fee: u64,
   pub min_fee_rate: u64,
+  #[schemars(with = "Option<schema::BlockHash>")]
   pub next_block: Option<BlockHash>,
   pub nonce: u32,
+  #[schemars(with = "Option<schema::BlockHash>")]
   pub previous_block: Option<BlockHash>,
   pub subsidy: u64,
+  #[schemars(with = "schema::BlockHash")]
   pub target: BlockHash,
   pub timestamp: u64,
   pub total_fee: u64,
@@ -69,14 +88,14 @@ pub struct BlockInfo {
   pub version: u32,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Children {
   pub ids: Vec<InscriptionId>,
   pub more: bool,
   pub page: usize,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Inscription {
   pub address: Option<String>,
   pub charms: Vec<Charm>,
@@ -98,7 +117,7 @@ pub struct Inscription {
   pub value: Option<u64>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct InscriptionRecursive {
   pub charms: Vec<Charm>,
   pub content_type: Option<String>,
@@ -107,6 +126,7 @@ pub struct InscriptionRecursive {
   pub height: u32,
   pub id: InscriptionId,
   pub number: i32,
+  #[schemars(with = "schema::OutPoint")]
   pub output: OutPoint,
   pub sat: Option<ordinals::Sat>,
   pub satpoint: SatPoint,
@@ -114,15 +134,16 @@ pub struct InscriptionRecursive {
   pub value: Option<u64>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Inscriptions {
   pub ids: Vec<InscriptionId>,
   pub more: bool,
   pub page_index: u32,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Output {
+  #[schemars(with = "Option<schema::Address>")]
   pub address: Option<Address<NetworkUnchecked>>,
   pub indexed: bool,
   pub inscriptions: Vec<InscriptionId>,
@@ -162,7 +183,47 @@ impl Output {
   }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OutputRecursive {
+  #[schemars(with = "Option<schema::Address>")]
+  pub address: Option<Address<NetworkUnchecked>>,
+  pub height: u32,
+  pub inscriptions: Vec<InscriptionId>,
+  pub runes: Vec<(SpacedRune, Pile)>,
+  pub sat_ranges: Option<Vec<(u64, u64)>>,
+  pub script_pubkey: String,
+  pub value: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RuneBalancesRecursive {
+  #[schemars(with = "Vec<(schema::OutPoint, Pile)>")]
+  pub balances: Vec<(OutPoint, Pile)>,
+  pub more: bool,
+  pub page: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RuneRecursive {
+  pub block: u64,
+  pub burned: u128,
+  pub divisibility: u8,
+  #[schemars(with = "schema::Txid")]
+  pub etching: Txid,
+  pub id: RuneId,
+  pub mintable: bool,
+  pub mints: u128,
+  pub number: u64,
+  pub premine: u128,
+  pub spaced_rune: SpacedRune,
+  pub supply: u128,
+  pub symbol: Option<char>,
+  pub terms: Option<Terms>,
+  pub timestamp: u64,
+  pub turbo: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Sat {
   pub block: u32,
   pub charms: Vec<Charm>,
@@ -181,14 +242,33 @@ pub struct Sat {
   pub timestamp: i64,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SatRecursive {
+  pub block: u32,
+  pub charms: Vec<Charm>,
+  pub inscriptions: Vec<InscriptionRecursive>,
+  pub name: String,
+  pub number: u64,
+  pub rarity: Rarity,
+  pub satpoint: Option<SatPoint>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct SatInscription {
   pub id: Option<InscriptionId>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct SatInscriptions {
   pub ids: Vec<InscriptionId>,
   pub more: bool,
   pub page: u64,
 }
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionRecursive {
+  pub height: u32,
+  pub hex: String,
+  #[schemars(with = "schema::Transaction")]
+  pub transaction: bitcoin::Transaction,
+}
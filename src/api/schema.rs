@@ -0,0 +1,117 @@
+//! JSON schemas for types whose schemas can't be derived, either because they
+//! are defined in other crates, or because they are serialized as strings.
+//! Fields of those types are annotated with `#[schemars(with = "…")]` using
+//! the stand-ins defined here.
+
+use {
+  super::*,
+  schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+  },
+};
+
+fn schema(instance_type: InstanceType, description: &str, pattern: Option<&str>) -> Schema {
+  SchemaObject {
+    instance_type: Some(instance_type.into()),
+    metadata: Some(Box::new(Metadata {
+      description: Some(description.into()),
+      ..default()
+    })),
+    string: pattern.map(|pattern| {
+      Box::new(StringValidation {
+        pattern: Some(pattern.into()),
+        ..default()
+      })
+    }),
+    ..default()
+  }
+  .into()
+}
+
+macro_rules! schema {
+  ($type:ident, $name:literal, $instance_type:ident, $description:literal, $pattern:expr) => {
+    impl JsonSchema for $type {
+      fn schema_name() -> String {
+        $name.into()
+      }
+
+      fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        schema(InstanceType::$instance_type, $description, $pattern)
+      }
+    }
+  };
+}
+
+pub(crate) struct Address;
+
+schema!(Address, "Address", String, "Bitcoin address", None);
+
+pub(crate) struct BlockHash;
+
+schema!(
+  BlockHash,
+  "BlockHash",
+  String,
+  "Block hash, hex-encoded",
+  Some("^[0-9a-f]{64}$")
+);
+
+pub(crate) struct Hash;
+
+schema!(
+  Hash,
+  "Hash",
+  String,
+  "256-bit hash, hex-encoded",
+  Some("^[0-9a-f]{64}$")
+);
+
+pub(crate) struct OutPoint;
+
+schema!(
+  OutPoint,
+  "OutPoint",
+  String,
+  "Transaction ID and output index",
+  Some("^[0-9a-f]{64}:[0-9]+$")
+);
+
+pub(crate) struct Transaction;
+
+schema!(
+  Transaction,
+  "BitcoinTransaction",
+  Object,
+  "Bitcoin transaction with `version`, `lock_time`, `input`, and `output` fields",
+  None
+);
+
+pub(crate) struct TxMerkleNode;
+
+schema!(
+  TxMerkleNode,
+  "TxMerkleNode",
+  String,
+  "Transaction merkle root, hex-encoded",
+  Some("^[0-9a-f]{64}$")
+);
+
+pub(crate) struct Txid;
+
+schema!(
+  Txid,
+  "Txid",
+  String,
+  "Transaction ID, hex-encoded",
+  Some("^[0-9a-f]{64}$")
+);
+
+schema!(
+  InscriptionId,
+  "InscriptionId",
+  String,
+  "Inscription ID, the ID of the reveal transaction and the index of the inscription within it",
+  Some("^[0-9a-f]{64}i[0-9]+$")
+);
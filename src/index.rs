@@ -5,14 +5,19 @@ use {
       OutPointValue, RuneEntryValue, RuneIdValue, SatPointValue, SatRange, TxidValue,
     },
     event::Event,
+    inscription_filter::{InscriptionFilter, Order},
     lot::Lot,
+    metrics::Metrics,
     reorg::Reorg,
+    snapshot::{Manifest, Snapshot},
+    undo::UndoLog,
     updater::Updater,
+    verify::{Verification, Verifier},
   },
   super::*,
   crate::{
     runes::MintError,
-    subcommand::{find::FindRangeOutput, server::query},
+    subcommand::{find::FindRangeOutput, index::export::InscriptionRecord, server::query},
     templates::StatusHtml,
   },
   bitcoin::block::Header,
@@ -25,12 +30,13 @@ use {
   log::log_enabled,
   redb::{
     Database, DatabaseError, MultimapTable, MultimapTableDefinition, MultimapTableHandle,
-    ReadOnlyTable, ReadableMultimapTable, ReadableTable, ReadableTableMetadata, RepairSession,
-    StorageError, Table, TableDefinition, TableHandle, TableStats, WriteTransaction,
+    MultimapValue, ReadOnlyTable, ReadableMultimapTable, ReadableTable, ReadableTableMetadata,
+    RepairSession, StorageError, Table, TableDefinition, TableHandle, TableStats, WriteTransaction,
   },
   std::{
     collections::HashMap,
     io::{BufWriter, Write},
+    ops::RangeInclusive,
     sync::Once,
   },
 };
@@ -40,31 +46,126 @@ pub use self::entry::RuneEntry;
 pub(crate) mod entry;
 pub mod event;
 mod fetcher;
+pub(crate) mod inscription_filter;
 mod lot;
+pub(crate) mod metrics;
 mod reorg;
 mod rtx;
+pub(crate) mod search;
+pub mod snapshot;
+mod undo;
 mod updater;
+mod verify;
 
 #[cfg(test)]
 pub(crate) mod testing;
 
-const SCHEMA_VERSION: u64 = 25;
+const SCHEMA_VERSION: u64 = 31;
+
+const MIGRATIONS: &[Migration] = &[
+  Migration {
+    description: "add address index tables",
+    migrate: |wtx| {
+      wtx.open_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)?;
+      wtx.open_table(OUTPOINT_TO_TXOUT)?;
+      Index::set_statistic(
+        &mut wtx.open_table(STATISTIC_TO_COUNT)?,
+        Statistic::IndexAddresses,
+        0,
+      )
+    },
+    version: 26,
+  },
+  Migration {
+    description: "add undo log table",
+    migrate: |wtx| {
+      wtx.open_table(HEIGHT_TO_UNDO_LOG)?;
+      Ok(())
+    },
+    version: 27,
+  },
+  Migration {
+    description: "add search index table",
+    migrate: |wtx| {
+      wtx.open_multimap_table(SEARCH_TERM_TO_SEQUENCE_NUMBER)?;
+      Index::set_statistic(
+        &mut wtx.open_table(STATISTIC_TO_COUNT)?,
+        Statistic::IndexSearch,
+        0,
+      )
+    },
+    version: 28,
+  },
+  Migration {
+    description: "add inscription filter index tables",
+    migrate: |wtx| {
+      wtx.open_multimap_table(CHARM_TO_SEQUENCE_NUMBER)?;
+      wtx.open_multimap_table(CONTENT_TYPE_TO_SEQUENCE_NUMBER)?;
+      wtx.open_multimap_table(METAPROTOCOL_TO_SEQUENCE_NUMBER)?;
+      Index::set_statistic(
+        &mut wtx.open_table(STATISTIC_TO_COUNT)?,
+        Statistic::IndexInscriptionFilters,
+        0,
+      )
+    },
+    version: 29,
+  },
+  Migration {
+    description: "add rune balance index table",
+    migrate: |wtx| {
+      let outpoint_to_rune_balances = wtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+      let mut rune_id_to_outpoint = wtx.open_multimap_table(RUNE_ID_TO_OUTPOINT)?;
+
+      for entry in outpoint_to_rune_balances.iter()? {
+        let (outpoint, balances) = entry?;
+        let balances = balances.value();
+
+        let mut i = 0;
+        while i < balances.len() {
+          let ((id, _balance), length) = Index::decode_rune_balance(&balances[i..])?;
+          i += length;
+          rune_id_to_outpoint.insert(id.store(), outpoint.value())?;
+        }
+      }
+
+      Ok(())
+    },
+    version: 30,
+  },
+  Migration {
+    description: "add inscription content type table",
+    migrate: |wtx| {
+      wtx.open_table(SEQUENCE_NUMBER_TO_CONTENT_TYPE)?;
+      Ok(())
+    },
+    version: 31,
+  },
+];
 
+define_multimap_table! { CHARM_TO_SEQUENCE_NUMBER, u16, u32 }
+define_multimap_table! { CONTENT_TYPE_TO_SEQUENCE_NUMBER, &str, u32 }
+define_multimap_table! { METAPROTOCOL_TO_SEQUENCE_NUMBER, &str, u32 }
+define_multimap_table! { RUNE_ID_TO_OUTPOINT, RuneIdValue, &OutPointValue }
 define_multimap_table! { SATPOINT_TO_SEQUENCE_NUMBER, &SatPointValue, u32 }
 define_multimap_table! { SAT_TO_SEQUENCE_NUMBER, u64, u32 }
+define_multimap_table! { SCRIPT_PUBKEY_TO_OUTPOINT, &[u8], &OutPointValue }
+define_multimap_table! { SEARCH_TERM_TO_SEQUENCE_NUMBER, &str, u32 }
 define_multimap_table! { SEQUENCE_NUMBER_TO_CHILDREN, u32, u32 }
 define_table! { CONTENT_TYPE_TO_COUNT, Option<&[u8]>, u64 }
 define_table! { HEIGHT_TO_BLOCK_HEADER, u32, &HeaderValue }
 define_table! { HEIGHT_TO_LAST_SEQUENCE_NUMBER, u32, u32 }
+define_table! { HEIGHT_TO_UNDO_LOG, u32, &[u8] }
 define_table! { HOME_INSCRIPTIONS, u32, InscriptionIdValue }
 define_table! { INSCRIPTION_ID_TO_SEQUENCE_NUMBER, InscriptionIdValue, u32 }
 define_table! { INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER, i32, u32 }
 define_table! { OUTPOINT_TO_RUNE_BALANCES, &OutPointValue, &[u8] }
 define_table! { OUTPOINT_TO_SAT_RANGES, &OutPointValue, &[u8] }
+define_table! { OUTPOINT_TO_TXOUT, &OutPointValue, &[u8] }
 define_table! { OUTPOINT_TO_VALUE, &OutPointValue, u64}
 define_table! { RUNE_ID_TO_RUNE_ENTRY, RuneIdValue, RuneEntryValue }
 define_table! { RUNE_TO_RUNE_ID, u128, RuneIdValue }
 define_table! { SAT_TO_SATPOINT, u64, &SatPointValue }
+define_table! { SEQUENCE_NUMBER_TO_CONTENT_TYPE, u32, &str }
 define_table! { SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY, u32, InscriptionEntryValue }
 define_table! { SEQUENCE_NUMBER_TO_RUNE_ID, u32, RuneIdValue }
 define_table! { SEQUENCE_NUMBER_TO_SATPOINT, u32, &SatPointValue }
@@ -73,7 +174,7 @@ define_table! { TRANSACTION_ID_TO_RUNE, &TxidValue, u128 }
 define_table! { TRANSACTION_ID_TO_TRANSACTION, &TxidValue, &[u8] }
 define_table! { WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP, u32, u128 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub(crate) enum Statistic {
   Schema = 0,
   BlessedInscriptions = 1,
@@ -90,6 +191,9 @@ pub(crate) enum Statistic {
   IndexTransactions = 12,
   IndexSpentSats = 13,
   InitialSyncTime = 14,
+  IndexAddresses = 15,
+  IndexSearch = 16,
+  IndexInscriptionFilters = 17,
 }
 
 impl Statistic {
@@ -131,8 +235,8 @@ pub(crate) struct TableInfo {
   leaf_pages: u64,
   metadata_bytes: u64,
   proportion: f64,
-  stored_bytes: u64,
-  total_bytes: u64,
+  pub(crate) stored_bytes: u64,
+  pub(crate) total_bytes: u64,
   tree_height: u32,
 }
 
@@ -189,10 +293,15 @@ pub struct Index {
   genesis_block_coinbase_transaction: Transaction,
   genesis_block_coinbase_txid: Txid,
   height_limit: Option<u32>,
+  index_addresses: bool,
+  index_inscription_filters: bool,
   index_runes: bool,
   index_sats: bool,
+  index_search: bool,
   index_spent_sats: bool,
   index_transactions: bool,
+  metrics: Arc<Metrics>,
+  migrations: Vec<&'static Migration>,
   settings: Settings,
   path: PathBuf,
   started: DateTime<Utc>,
@@ -253,6 +362,8 @@ impl Index {
       }
     };
 
+    let mut migrations = Vec::new();
+
     let database = match Database::builder()
       .set_cache_size(index_cache_size)
       .set_repair_callback(repair_callback)
@@ -268,6 +379,16 @@ impl Index {
             .unwrap_or(0);
 
           match schema_version.cmp(&SCHEMA_VERSION) {
+            cmp::Ordering::Less if Migration::available(MIGRATIONS, schema_version, SCHEMA_VERSION) =>
+              migrations = Migration::run(
+                MIGRATIONS,
+                &database,
+                &path,
+                STATISTIC_TO_COUNT,
+                Statistic::Schema.key(),
+                schema_version,
+                SCHEMA_VERSION,
+              )?,
             cmp::Ordering::Less =>
               bail!(
                 "index at `{}` appears to have been built with an older, incompatible version of ord, consider deleting and rebuilding the index: index schema {schema_version}, ord schema {SCHEMA_VERSION}",
@@ -298,18 +419,27 @@ impl Index {
 
         tx.open_multimap_table(SATPOINT_TO_SEQUENCE_NUMBER)?;
         tx.open_multimap_table(SAT_TO_SEQUENCE_NUMBER)?;
+        tx.open_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)?;
+        tx.open_multimap_table(SEARCH_TERM_TO_SEQUENCE_NUMBER)?;
+        tx.open_multimap_table(CHARM_TO_SEQUENCE_NUMBER)?;
+        tx.open_multimap_table(CONTENT_TYPE_TO_SEQUENCE_NUMBER)?;
+        tx.open_multimap_table(METAPROTOCOL_TO_SEQUENCE_NUMBER)?;
+        tx.open_multimap_table(RUNE_ID_TO_OUTPOINT)?;
         tx.open_multimap_table(SEQUENCE_NUMBER_TO_CHILDREN)?;
         tx.open_table(CONTENT_TYPE_TO_COUNT)?;
         tx.open_table(HEIGHT_TO_BLOCK_HEADER)?;
         tx.open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
+        tx.open_table(HEIGHT_TO_UNDO_LOG)?;
         tx.open_table(HOME_INSCRIPTIONS)?;
         tx.open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?;
         tx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
         tx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+        tx.open_table(OUTPOINT_TO_TXOUT)?;
         tx.open_table(OUTPOINT_TO_VALUE)?;
         tx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
         tx.open_table(RUNE_TO_RUNE_ID)?;
         tx.open_table(SAT_TO_SATPOINT)?;
+        tx.open_table(SEQUENCE_NUMBER_TO_CONTENT_TYPE)?;
         tx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
         tx.open_table(SEQUENCE_NUMBER_TO_RUNE_ID)?;
         tx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
@@ -324,6 +454,12 @@ impl Index {
             outpoint_to_sat_ranges.insert(&OutPoint::null().store(), [].as_slice())?;
           }
 
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexAddresses,
+            u64::from(settings.index_addresses()),
+          )?;
+
           Self::set_statistic(
             &mut statistics,
             Statistic::IndexRunes,
@@ -336,6 +472,14 @@ impl Index {
             u64::from(settings.index_sats() || settings.index_spent_sats()),
           )?;
 
+          Self::set_statistic(&mut statistics, Statistic::IndexInscriptionFilters, 1)?;
+
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexSearch,
+            u64::from(settings.index_search()),
+          )?;
+
           Self::set_statistic(
             &mut statistics,
             Statistic::IndexSpentSats,
@@ -402,16 +546,23 @@ impl Index {
       Err(error) => bail!("failed to open index: {error}"),
     };
 
+    let index_addresses;
+    let index_inscription_filters;
     let index_runes;
     let index_sats;
+    let index_search;
     let index_spent_sats;
     let index_transactions;
 
     {
       let tx = database.begin_read()?;
       let statistics = tx.open_table(STATISTIC_TO_COUNT)?;
+      index_addresses = Self::is_statistic_set(&statistics, Statistic::IndexAddresses)?;
+      index_inscription_filters =
+        Self::is_statistic_set(&statistics, Statistic::IndexInscriptionFilters)?;
       index_runes = Self::is_statistic_set(&statistics, Statistic::IndexRunes)?;
       index_sats = Self::is_statistic_set(&statistics, Statistic::IndexSats)?;
+      index_search = Self::is_statistic_set(&statistics, Statistic::IndexSearch)?;
       index_spent_sats = Self::is_statistic_set(&statistics, Statistic::IndexSpentSats)?;
       index_transactions = Self::is_statistic_set(&statistics, Statistic::IndexTransactions)?;
     }
@@ -428,10 +579,15 @@ impl Index {
       first_inscription_height: settings.first_inscription_height(),
       genesis_block_coinbase_transaction,
       height_limit: settings.height_limit(),
+      index_addresses,
+      index_inscription_filters,
       index_runes,
       index_sats,
+      index_search,
       index_spent_sats,
       index_transactions,
+      metrics: Arc::new(Metrics::default()),
+      migrations,
       settings: settings.clone(),
       path,
       started: Utc::now(),
@@ -455,6 +611,19 @@ impl Index {
     )
   }
 
+  /// Migrations applied to the index when it was opened.
+  pub(crate) fn migrations(&self) -> &[&'static Migration] {
+    &self.migrations
+  }
+
+  pub(crate) fn has_address_index(&self) -> bool {
+    self.index_addresses
+  }
+
+  pub(crate) fn has_inscription_filter_index(&self) -> bool {
+    self.index_inscription_filters
+  }
+
   pub(crate) fn has_rune_index(&self) -> bool {
     self.index_runes
   }
@@ -463,6 +632,10 @@ impl Index {
     self.index_sats
   }
 
+  pub(crate) fn has_search_index(&self) -> bool {
+    self.index_search
+  }
+
   pub(crate) fn status(&self) -> Result<StatusHtml> {
     let rtx = self.database.begin_read()?;
 
@@ -528,27 +701,7 @@ impl Index {
 
     let rtx = self.database.begin_read()?;
 
-    let mut tables: BTreeMap<String, TableInfo> = BTreeMap::new();
-
-    for handle in rtx.list_tables()? {
-      let name = handle.name().into();
-      let stats = rtx.open_untyped_table(handle)?.stats()?;
-      tables.insert(name, stats.into());
-    }
-
-    for handle in rtx.list_multimap_tables()? {
-      let name = handle.name().into();
-      let stats = rtx.open_untyped_multimap_table(handle)?.stats()?;
-      tables.insert(name, stats.into());
-    }
-
-    for table in rtx.list_tables()? {
-      assert!(tables.contains_key(table.name()));
-    }
-
-    for table in rtx.list_multimap_tables()? {
-      assert!(tables.contains_key(table.name()));
-    }
+    let mut tables = Self::tables(&rtx)?;
 
     let total_bytes = tables
       .values()
@@ -609,6 +762,48 @@ impl Index {
     Ok(info)
   }
 
+  fn tables(rtx: &redb::ReadTransaction) -> Result<BTreeMap<String, TableInfo>> {
+    let mut tables: BTreeMap<String, TableInfo> = BTreeMap::new();
+
+    for handle in rtx.list_tables()? {
+      let name = handle.name().into();
+      let stats = rtx.open_untyped_table(handle)?.stats()?;
+      tables.insert(name, stats.into());
+    }
+
+    for handle in rtx.list_multimap_tables()? {
+      let name = handle.name().into();
+      let stats = rtx.open_untyped_multimap_table(handle)?.stats()?;
+      tables.insert(name, stats.into());
+    }
+
+    for table in rtx.list_tables()? {
+      assert!(tables.contains_key(table.name()));
+    }
+
+    for table in rtx.list_multimap_tables()? {
+      assert!(tables.contains_key(table.name()));
+    }
+
+    Ok(tables)
+  }
+
+  pub(crate) fn table_info(&self) -> Result<BTreeMap<String, TableInfo>> {
+    Self::tables(&self.database.begin_read()?)
+  }
+
+  pub(crate) fn index_file_size(&self) -> Result<u64> {
+    Ok(fs::metadata(&self.path)?.len())
+  }
+
+  pub(crate) fn metrics(&self) -> &Metrics {
+    &self.metrics
+  }
+
+  pub(crate) fn is_unrecoverably_reorged(&self) -> bool {
+    self.unrecoverably_reorged.load(atomic::Ordering::Relaxed)
+  }
+
   pub fn update(&self) -> Result {
     loop {
       let wtx = self.begin_write()?;
@@ -627,6 +822,7 @@ impl Index {
         outputs_traversed: 0,
         range_cache: HashMap::new(),
         sat_ranges_since_flush: 0,
+        uncommitted_events: false,
       };
 
       match updater.update_index(wtx) {
@@ -634,11 +830,24 @@ impl Index {
         Err(err) => {
           log::info!("{}", err.to_string());
 
+          // events for blocks indexed since the last commit were sent, but
+          // those blocks will never be committed
+          if updater.uncommitted_events {
+            if let Some(sender) = &self.event_sender {
+              sender.blocking_send(Event::Rollback {
+                from_height: updater.height,
+                to_height: self.begin_read()?.block_count()?,
+              })?;
+            }
+          }
+
           match err.downcast_ref() {
             Some(&reorg::Error::Recoverable { height, depth }) => {
+              Metrics::increment(&self.metrics.recoverable_reorgs, 1);
               Reorg::handle_reorg(self, height, depth)?;
             }
             Some(&reorg::Error::Unrecoverable) => {
+              Metrics::increment(&self.metrics.unrecoverable_reorgs, 1);
               self
                 .unrecoverably_reorged
                 .store(true, atomic::Ordering::Relaxed);
@@ -651,6 +860,67 @@ impl Index {
     }
   }
 
+  /// Revert all blocks above `height` using the undo log, returning the number
+  /// of blocks reverted.
+  pub(crate) fn rollback(&self, height: u32) -> Result<u32> {
+    let wtx = self.begin_write()?;
+
+    let tip = wtx
+      .open_table(HEIGHT_TO_BLOCK_HEADER)?
+      .range(0..)?
+      .next_back()
+      .transpose()?
+      .map(|(height, _header)| height.value())
+      .ok_or_else(|| anyhow!("cannot roll back empty index"))?;
+
+    ensure!(
+      height < tip,
+      "cannot roll back to height {height}: index is at height {tip}"
+    );
+
+    UndoLog::revert(&wtx, height, tip)?;
+
+    // savepoints may have been taken after `height`, and restoring one would
+    // bring back reverted blocks
+    for savepoint in wtx.list_persistent_savepoints()?.collect::<Vec<u64>>() {
+      wtx.delete_persistent_savepoint(savepoint)?;
+    }
+
+    Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
+    wtx.commit()?;
+
+    log::info!("rolled back index from height {tip} to height {height} using undo log");
+
+    if let Some(sender) = &self.event_sender {
+      sender.blocking_send(Event::Rollback {
+        from_height: tip,
+        to_height: height + 1,
+      })?;
+    }
+
+    Ok(tip - height)
+  }
+
+  pub(crate) fn undo_log_start(&self, tip: u32) -> Result<Option<u32>> {
+    UndoLog::start(
+      &self.database.begin_read()?.open_table(HEIGHT_TO_UNDO_LOG)?,
+      tip,
+    )
+  }
+
+  /// Check index invariants, returning a description of each violation.
+  pub(crate) fn verify(&self) -> Result<Verification> {
+    Verifier::verify(self)
+  }
+
+  pub(crate) fn export_snapshot(&self, path: &Path) -> Result<Manifest> {
+    Snapshot::export(self, path)
+  }
+
+  pub(crate) fn import_snapshot(settings: &Settings, path: &Path) -> Result<Manifest> {
+    Snapshot::import(settings, path)
+  }
+
   pub(crate) fn export(&self, filename: &String, include_addresses: bool) -> Result {
     let mut writer = BufWriter::new(fs::File::create(filename)?);
     let rtx = self.database.begin_read()?;
@@ -719,6 +989,82 @@ impl Index {
     Ok(())
   }
 
+  pub(crate) fn export_inscriptions(
+    &self,
+    heights: RangeInclusive<u32>,
+    mut f: impl FnMut(InscriptionRecord) -> Result,
+  ) -> Result {
+    let rtx = self.database.begin_read()?;
+
+    let sequence_number_to_inscription_entry =
+      rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    let sequence_number_to_satpoint = rtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
+
+    for result in sequence_number_to_inscription_entry.iter()? {
+      let entry = InscriptionEntry::load(result?.1.value());
+
+      // sequence numbers are assigned in block order
+      if entry.height > *heights.end() {
+        break;
+      }
+
+      if entry.height < *heights.start() {
+        continue;
+      }
+
+      let inscription = self
+        .get_inscription_by_id(entry.id)?
+        .ok_or_else(|| anyhow!("inscription {} not found", entry.id))?;
+
+      let parents = entry
+        .parents
+        .iter()
+        .map(|parent| {
+          Ok(
+            InscriptionEntry::load(
+              sequence_number_to_inscription_entry
+                .get(parent)?
+                .ok_or_else(|| anyhow!("parent {parent} of inscription {} not found", entry.id))?
+                .value(),
+            )
+            .id,
+          )
+        })
+        .collect::<Result<Vec<InscriptionId>>>()?;
+
+      let satpoint = SatPoint::load(
+        *sequence_number_to_satpoint
+          .get(entry.sequence_number)?
+          .ok_or_else(|| anyhow!("satpoint of inscription {} not found", entry.id))?
+          .value(),
+      );
+
+      f(InscriptionRecord {
+        charms: Charm::charms(entry.charms),
+        content_length: inscription.content_length(),
+        content_type: inscription.content_type().map(str::to_string),
+        delegate: inscription.delegate(),
+        fee: entry.fee,
+        height: entry.height,
+        id: entry.id,
+        metaprotocol: inscription.metaprotocol().map(str::to_string),
+        number: entry.inscription_number,
+        parents,
+        sat: entry.sat,
+        satpoint,
+        sequence_number: entry.sequence_number,
+        timestamp: entry.timestamp,
+      })?;
+
+      if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+        break;
+      }
+    }
+
+    Ok(())
+  }
+
   fn begin_read(&self) -> Result<rtx::Rtx> {
     Ok(rtx::Rtx(self.database.begin_read()?))
   }
@@ -1091,6 +1437,52 @@ impl Index {
     Ok(result)
   }
 
+  pub(crate) fn get_rune_balances_paginated(
+    &self,
+    id: RuneId,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<(OutPoint, u128)>, bool)> {
+    let rtx = self.database.begin_read()?;
+
+    let outpoint_to_rune_balances = rtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+
+    let mut balances = Vec::new();
+
+    for outpoint in rtx
+      .open_multimap_table(RUNE_ID_TO_OUTPOINT)?
+      .get(id.store())?
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+    {
+      let outpoint = outpoint?;
+
+      let buffer = outpoint_to_rune_balances
+        .get(outpoint.value())?
+        .ok_or_else(|| anyhow!("rune balance index is inconsistent"))?;
+      let buffer = buffer.value();
+
+      let mut i = 0;
+      while i < buffer.len() {
+        let ((rune_id, balance), length) = Index::decode_rune_balance(&buffer[i..])?;
+        i += length;
+
+        if rune_id == id {
+          balances.push((OutPoint::load(*outpoint.value()), balance));
+          break;
+        }
+      }
+    }
+
+    let more = balances.len() > page_size;
+
+    if more {
+      balances.pop();
+    }
+
+    Ok((balances, more))
+  }
+
   pub(crate) fn block_header(&self, hash: BlockHash) -> Result<Option<Header>> {
     self.client.get_block_header(&hash).into_option()
   }
@@ -1671,6 +2063,34 @@ impl Index {
     Ok(true)
   }
 
+  /// Height of the block containing `txid`, if that block has been indexed
+  /// and is part of the index's chain.
+  pub(crate) fn get_transaction_height(&self, txid: Txid) -> Result<Option<u32>> {
+    if txid == self.genesis_block_coinbase_txid {
+      return Ok(self.block_hash(Some(0))?.map(|_| 0));
+    }
+
+    let Some(info) = self
+      .client
+      .get_raw_transaction_info(&txid, None)
+      .into_option()?
+    else {
+      return Ok(None);
+    };
+
+    let Some(block_hash) = info.blockhash else {
+      return Ok(None);
+    };
+
+    let Some(header) = self.block_header_info(block_hash)? else {
+      return Ok(None);
+    };
+
+    let height = u32::try_from(header.height).unwrap();
+
+    Ok((self.block_hash(Some(height))? == Some(block_hash)).then_some(height))
+  }
+
   pub(crate) fn block_time(&self, height: Height) -> Result<Blocktime> {
     let height = height.n();
 
@@ -1741,55 +2161,389 @@ impl Index {
     Ok((inscriptions, more))
   }
 
-  pub(crate) fn get_inscriptions_in_block(&self, block_height: u32) -> Result<Vec<InscriptionId>> {
+  pub(crate) fn get_inscriptions_filtered(
+    &self,
+    filter: &InscriptionFilter,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<InscriptionId>, bool)> {
     let rtx = self.database.begin_read()?;
 
     let height_to_last_sequence_number = rtx.open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
-    let sequence_number_to_inscription_entry =
-      rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
 
-    let Some(newest_sequence_number) = height_to_last_sequence_number
-      .get(&block_height)?
-      .map(|ag| ag.value())
-    else {
-      return Ok(Vec::new());
+    // sequence numbers increase with height, so a height range is a sequence
+    // number range
+    let start = match filter.min_height {
+      Some(min_height) => height_to_last_sequence_number
+        .range(..min_height)?
+        .next_back()
+        .transpose()?
+        .map(|(_height, sequence_number)| sequence_number.value())
+        .unwrap_or_default(),
+      None => 0,
     };
 
-    let oldest_sequence_number = height_to_last_sequence_number
-      .get(block_height.saturating_sub(1))?
-      .map(|ag| ag.value())
-      .unwrap_or(0);
-
-    (oldest_sequence_number..newest_sequence_number)
-      .map(|num| match sequence_number_to_inscription_entry.get(&num) {
-        Ok(Some(inscription_id)) => Ok(InscriptionEntry::load(inscription_id.value()).id),
-        Ok(None) => Err(anyhow!(
-          "could not find inscription for inscription number {num}"
-        )),
-        Err(err) => Err(anyhow!(err)),
-      })
-      .collect::<Result<Vec<InscriptionId>>>()
-  }
-
-  pub(crate) fn get_runes_in_block(&self, block_height: u64) -> Result<Vec<SpacedRune>> {
-    let rtx = self.database.begin_read()?;
+    let end = match filter.max_height {
+      Some(max_height) => height_to_last_sequence_number
+        .range(..=max_height)?
+        .next_back()
+        .transpose()?
+        .map(|(_height, sequence_number)| sequence_number.value())
+        .unwrap_or_default(),
+      None => u32::MAX,
+    };
 
-    let rune_id_to_rune_entry = rtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
+    if start >= end {
+      return Ok((Vec::new(), false));
+    }
 
-    let min_id = RuneId {
-      block: block_height,
-      tx: 0,
-    };
+    let parent = match filter.parent {
+      Some(parent) => {
+        let Some(parent) = rtx
+          .open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?
+          .get(&parent.store())?
+          .map(|sequence_number| sequence_number.value())
+        else {
+          return Ok((Vec::new(), false));
+        };
 
-    let max_id = RuneId {
-      block: block_height,
-      tx: u32::MAX,
+        Some(parent)
+      }
+      None => None,
     };
 
-    let runes = rune_id_to_rune_entry
-      .range(min_id.store()..=max_id.store())?
-      .map(|result| result.map(|(_, entry)| RuneEntry::load(entry.value()).spaced_rune))
-      .collect::<Result<Vec<SpacedRune>, StorageError>>()?;
+    // buckets whose filter can be checked against the inscription entry, and
+    // buckets which must be checked by stepping through them in order
+    let mut buckets = Vec::new();
+
+    if let Some(charm) = filter.charm {
+      buckets.push((
+        true,
+        rtx
+          .open_multimap_table(CHARM_TO_SEQUENCE_NUMBER)?
+          .get(charm as u16)?,
+      ));
+    }
+
+    if let Some(content_type) = &filter.content_type {
+      buckets.push((
+        false,
+        rtx
+          .open_multimap_table(CONTENT_TYPE_TO_SEQUENCE_NUMBER)?
+          .get(search::media_type(content_type).as_str())?,
+      ));
+    }
+
+    if let Some(metaprotocol) = &filter.metaprotocol {
+      buckets.push((
+        false,
+        rtx
+          .open_multimap_table(METAPROTOCOL_TO_SEQUENCE_NUMBER)?
+          .get(metaprotocol.as_str())?,
+      ));
+    }
+
+    if let Some(parent) = parent {
+      buckets.push((
+        true,
+        rtx
+          .open_multimap_table(SEQUENCE_NUMBER_TO_CHILDREN)?
+          .get(parent)?,
+      ));
+    }
+
+    let order = filter.order;
+
+    let ordered = |bucket: MultimapValue<'static, u32>| -> Box<dyn Iterator<Item = Result<u32>>> {
+      let sequence_numbers = bucket.map(|result| {
+        result
+          .map(|sequence_number| sequence_number.value())
+          .map_err(Error::from)
+      });
+
+      match order {
+        Order::Newest => Box::new(sequence_numbers.rev()),
+        Order::Oldest => Box::new(sequence_numbers),
+      }
+    };
+
+    let sequence_number_to_inscription_entry =
+      rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    // iterate over the smallest bucket, or all inscriptions if there are no
+    // buckets, checking the other filters for each sequence number
+    buckets.sort_by_key(|(_entry_filter, bucket)| bucket.len());
+
+    let mut buckets = buckets.into_iter();
+
+    let sequence_numbers: Box<dyn Iterator<Item = Result<u32>>> = match buckets.next() {
+      Some((_entry_filter, bucket)) => ordered(bucket),
+      None => {
+        let range = sequence_number_to_inscription_entry
+          .range(start..end)?
+          .map(|result| {
+            result
+              .map(|(sequence_number, _entry)| sequence_number.value())
+              .map_err(Error::from)
+          });
+
+        match order {
+          Order::Newest => Box::new(range.rev()),
+          Order::Oldest => Box::new(range),
+        }
+      }
+    };
+
+    let mut cursors = buckets
+      .filter(|(entry_filter, _bucket)| !entry_filter)
+      .map(|(_entry_filter, bucket)| ordered(bucket).peekable())
+      .collect::<Vec<_>>();
+
+    let mut inscriptions = Vec::new();
+    let mut skip = page_size.saturating_mul(page_index);
+
+    'outer: for sequence_number in sequence_numbers {
+      let sequence_number = sequence_number?;
+
+      match order {
+        Order::Newest if sequence_number < start => break,
+        Order::Newest if sequence_number >= end => continue,
+        Order::Oldest if sequence_number >= end => break,
+        Order::Oldest if sequence_number < start => continue,
+        _ => {}
+      }
+
+      for cursor in &mut cursors {
+        // cursors are in the same order as sequence numbers, so advance past
+        // all entries preceding this sequence number
+        while let Some(next) = cursor.next_if(|next| {
+          next.as_ref().map_or(true, |next| match order {
+            Order::Newest => *next > sequence_number,
+            Order::Oldest => *next < sequence_number,
+          })
+        }) {
+          next?;
+        }
+
+        match cursor.peek() {
+          Some(Ok(next)) if *next == sequence_number => {}
+          _ => continue 'outer,
+        }
+      }
+
+      let entry = InscriptionEntry::load(
+        sequence_number_to_inscription_entry
+          .get(sequence_number)?
+          .ok_or_else(|| {
+            anyhow!("missing inscription entry for sequence number {sequence_number}")
+          })?
+          .value(),
+      );
+
+      if filter
+        .charm
+        .is_some_and(|charm| !charm.is_set(entry.charms))
+        || parent.is_some_and(|parent| !entry.parents.contains(&parent))
+      {
+        continue;
+      }
+
+      if skip > 0 {
+        skip -= 1;
+        continue;
+      }
+
+      inscriptions.push(entry.id);
+
+      if inscriptions.len() > page_size {
+        break;
+      }
+    }
+
+    let more = inscriptions.len() > page_size;
+
+    if more {
+      inscriptions.pop();
+    }
+
+    Ok((inscriptions, more))
+  }
+
+  pub(crate) fn search(
+    &self,
+    query: &str,
+    filter: &search::Filter,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<InscriptionId>, bool)> {
+    let terms = search::query_terms(query, filter);
+
+    if terms.is_empty() {
+      return Ok((Vec::new(), false));
+    }
+
+    let rtx = self.database.begin_read()?;
+
+    let search_term_to_sequence_number = rtx.open_multimap_table(SEARCH_TERM_TO_SEQUENCE_NUMBER)?;
+
+    // iterate over the smallest posting list, newest first, stepping through
+    // the other posting lists in the same order to check each sequence number
+    let mut postings = terms
+      .iter()
+      .map(|term| search_term_to_sequence_number.get(term.as_str()))
+      .collect::<Result<Vec<MultimapValue<'static, u32>>, StorageError>>()?;
+
+    postings.sort_by_key(|posting| posting.len());
+
+    let mut postings = postings.into_iter().map(|posting| {
+      posting
+        .rev()
+        .map(|result| result.map(|sequence_number| sequence_number.value()))
+    });
+
+    let Some(sequence_numbers) = postings.next() else {
+      return Ok((Vec::new(), false));
+    };
+
+    let mut cursors = postings.map(Iterator::peekable).collect::<Vec<_>>();
+
+    let parent = match filter.parent {
+      Some(parent) => {
+        let Some(parent) = rtx
+          .open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?
+          .get(&parent.store())?
+          .map(|sequence_number| sequence_number.value())
+        else {
+          return Ok((Vec::new(), false));
+        };
+
+        Some(parent)
+      }
+      None => None,
+    };
+
+    let sequence_number_to_inscription_entry =
+      rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    let mut inscriptions = Vec::new();
+    let mut skip = page_size.saturating_mul(page_index);
+
+    'outer: for sequence_number in sequence_numbers {
+      let sequence_number = sequence_number?;
+
+      for cursor in &mut cursors {
+        let matched = loop {
+          match cursor.peek() {
+            Some(Ok(next)) if *next > sequence_number => {
+              cursor.next();
+            }
+            Some(Ok(next)) => break *next == sequence_number,
+            Some(Err(_)) => {
+              cursor.next().transpose()?;
+            }
+            // no older sequence numbers can match
+            None => break 'outer,
+          }
+        };
+
+        if !matched {
+          continue 'outer;
+        }
+      }
+
+      let entry = InscriptionEntry::load(
+        sequence_number_to_inscription_entry
+          .get(sequence_number)?
+          .ok_or_else(|| {
+            anyhow!("missing inscription entry for sequence number {sequence_number}")
+          })?
+          .value(),
+      );
+
+      if filter
+        .from_height
+        .is_some_and(|from_height| entry.height < from_height)
+        || filter
+          .to_height
+          .is_some_and(|to_height| entry.height > to_height)
+        || filter
+          .charm
+          .is_some_and(|charm| !charm.is_set(entry.charms))
+        || parent.is_some_and(|parent| !entry.parents.contains(&parent))
+      {
+        continue;
+      }
+
+      if skip > 0 {
+        skip -= 1;
+        continue;
+      }
+
+      inscriptions.push(entry.id);
+
+      if inscriptions.len() > page_size {
+        break;
+      }
+    }
+
+    let more = inscriptions.len() > page_size;
+
+    if more {
+      inscriptions.pop();
+    }
+
+    Ok((inscriptions, more))
+  }
+
+  pub(crate) fn get_inscriptions_in_block(&self, block_height: u32) -> Result<Vec<InscriptionId>> {
+    let rtx = self.database.begin_read()?;
+
+    let height_to_last_sequence_number = rtx.open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
+    let sequence_number_to_inscription_entry =
+      rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    let Some(newest_sequence_number) = height_to_last_sequence_number
+      .get(&block_height)?
+      .map(|ag| ag.value())
+    else {
+      return Ok(Vec::new());
+    };
+
+    let oldest_sequence_number = height_to_last_sequence_number
+      .get(block_height.saturating_sub(1))?
+      .map(|ag| ag.value())
+      .unwrap_or(0);
+
+    (oldest_sequence_number..newest_sequence_number)
+      .map(|num| match sequence_number_to_inscription_entry.get(&num) {
+        Ok(Some(inscription_id)) => Ok(InscriptionEntry::load(inscription_id.value()).id),
+        Ok(None) => Err(anyhow!(
+          "could not find inscription for inscription number {num}"
+        )),
+        Err(err) => Err(anyhow!(err)),
+      })
+      .collect::<Result<Vec<InscriptionId>>>()
+  }
+
+  pub(crate) fn get_runes_in_block(&self, block_height: u64) -> Result<Vec<SpacedRune>> {
+    let rtx = self.database.begin_read()?;
+
+    let rune_id_to_rune_entry = rtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
+
+    let min_id = RuneId {
+      block: block_height,
+      tx: 0,
+    };
+
+    let max_id = RuneId {
+      block: block_height,
+      tx: u32::MAX,
+    };
+
+    let runes = rune_id_to_rune_entry
+      .range(min_id.store()..=max_id.store())?
+      .map(|result| result.map(|(_, entry)| RuneEntry::load(entry.value()).spaced_rune))
+      .collect::<Result<Vec<SpacedRune>, StorageError>>()?;
 
     Ok(runes)
   }
@@ -2186,6 +2940,29 @@ impl Index {
     )
   }
 
+  pub(crate) fn get_address_info(&self, address: &Address) -> Result<Vec<(OutPoint, TxOut)>> {
+    let rtx = self.database.begin_read()?;
+
+    let outpoint_to_txout = rtx.open_table(OUTPOINT_TO_TXOUT)?;
+
+    let mut outputs = Vec::new();
+
+    for result in rtx
+      .open_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)?
+      .get(address.script_pubkey().as_bytes())?
+    {
+      let outpoint = OutPoint::load(*result?.value());
+
+      let txout = outpoint_to_txout
+        .get(&outpoint.store())?
+        .ok_or_else(|| anyhow!("could not find output {outpoint} in index"))?;
+
+      outputs.push((outpoint, consensus::encode::deserialize(txout.value())?));
+    }
+
+    Ok(outputs)
+  }
+
   pub(crate) fn get_output_info(&self, outpoint: OutPoint) -> Result<Option<(api::Output, TxOut)>> {
     let sat_ranges = self.list(outpoint)?;
 
@@ -3357,6 +4134,67 @@ mod tests {
       format!("index at `{}{delimiter}regtest{delimiter}index.redb` appears to have been built with a newer, incompatible version of ord, consider updating ord: index schema {}, ord schema {SCHEMA_VERSION}", path.display(), u64::MAX));
   }
 
+  #[test]
+  fn previous_schemas_are_migrated() {
+    let tempdir = {
+      let context = Context::builder().build();
+
+      let wtx = context.index.database.begin_write().unwrap();
+
+      wtx.delete_table(HEIGHT_TO_UNDO_LOG).unwrap();
+      wtx.delete_table(OUTPOINT_TO_TXOUT).unwrap();
+      wtx.delete_table(SEQUENCE_NUMBER_TO_CONTENT_TYPE).unwrap();
+      wtx
+        .delete_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)
+        .unwrap();
+      wtx
+        .delete_multimap_table(SEARCH_TERM_TO_SEQUENCE_NUMBER)
+        .unwrap();
+      wtx.delete_multimap_table(CHARM_TO_SEQUENCE_NUMBER).unwrap();
+      wtx
+        .delete_multimap_table(CONTENT_TYPE_TO_SEQUENCE_NUMBER)
+        .unwrap();
+      wtx
+        .delete_multimap_table(METAPROTOCOL_TO_SEQUENCE_NUMBER)
+        .unwrap();
+      wtx.delete_multimap_table(RUNE_ID_TO_OUTPOINT).unwrap();
+
+      let mut statistics = wtx.open_table(STATISTIC_TO_COUNT).unwrap();
+      statistics.remove(&Statistic::IndexAddresses.key()).unwrap();
+      statistics.remove(&Statistic::IndexSearch.key()).unwrap();
+      statistics
+        .remove(&Statistic::IndexInscriptionFilters.key())
+        .unwrap();
+      statistics.insert(&Statistic::Schema.key(), &25).unwrap();
+      drop(statistics);
+
+      wtx.commit().unwrap();
+
+      context.tempdir
+    };
+
+    let context = Context::builder().tempdir(tempdir).build();
+
+    assert_eq!(context.index.statistic(Statistic::Schema), SCHEMA_VERSION);
+    assert_eq!(
+      context
+        .index
+        .migrations()
+        .iter()
+        .map(|migration| migration.version)
+        .collect::<Vec<u64>>(),
+      (26..=SCHEMA_VERSION).collect::<Vec<u64>>(),
+    );
+    assert!(!context.index.has_address_index());
+    assert!(!context.index.has_search_index());
+    assert!(!context.index.has_inscription_filter_index());
+
+    context.mine_blocks(1);
+
+    assert_eq!(context.index.block_count().unwrap(), 2);
+    assert_eq!(context.index.undo_log_start(1).unwrap(), Some(1));
+  }
+
   #[test]
   fn inscriptions_on_output() {
     for context in Context::configurations() {
@@ -4619,42 +5457,144 @@ mod tests {
   }
 
   #[test]
-  fn inscription_without_parent_tag_has_no_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
-
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+  fn deep_reorg_is_unrecoverable_with_default_savepoints() {
+    let mut context = Context::builder().arg("--undo-log-depth=0").build();
+    context.index.set_durability(redb::Durability::Immediate);
 
+    for _ in 0..40 {
       context.mine_blocks(1);
+    }
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    for _ in 0..25 {
+      context.core.invalidate_tip();
+    }
 
-      assert!(context
+    context.mine_blocks_with_update(27, false);
+
+    assert_eq!(
+      context
         .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap()
-        .parents
-        .is_empty());
-    }
+        .update()
+        .unwrap_err()
+        .downcast_ref::<reorg::Error>(),
+      Some(&reorg::Error::Unrecoverable),
+    );
   }
 
   #[test]
-  fn inscription_with_parent_tag_without_parent_has_no_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
-
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+  fn recover_from_deep_reorg_with_additional_savepoints() {
+    let mut context = Context::builder()
+      .args(["--max-savepoints=4", "--undo-log-depth=0"])
+      .build();
+    context.index.set_durability(redb::Durability::Immediate);
 
+    for _ in 0..40 {
       context.mine_blocks(1);
+    }
 
-      let parent_inscription_id = InscriptionId {
+    for _ in 0..25 {
+      context.core.invalidate_tip();
+    }
+
+    let blocks = context.mine_blocks(27);
+
+    assert_eq!(context.index.block_count().unwrap(), 43);
+    assert_eq!(
+      context.index.block_hash(None).unwrap(),
+      Some(blocks.last().unwrap().block_hash()),
+    );
+  }
+
+  #[test]
+  fn recover_from_deep_reorg_with_undo_log() {
+    let mut context = Context::builder().build();
+    context.index.set_durability(redb::Durability::Immediate);
+
+    for _ in 0..40 {
+      context.mine_blocks(1);
+    }
+
+    for _ in 0..25 {
+      context.core.invalidate_tip();
+    }
+
+    let blocks = context.mine_blocks(27);
+
+    assert_eq!(context.index.block_count().unwrap(), 43);
+    assert_eq!(
+      context.index.block_hash(None).unwrap(),
+      Some(blocks.last().unwrap().block_hash()),
+    );
+  }
+
+  #[test]
+  fn recover_from_reorg_without_savepoints() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+      let first_id = InscriptionId { txid, index: 0 };
+
+      context.mine_blocks(1);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+      let second_id = InscriptionId { txid, index: 0 };
+
+      context.mine_blocks(1);
+
+      assert!(context.index.inscription_exists(second_id).unwrap());
+
+      context.core.invalidate_tip();
+      context.mine_blocks(2);
+
+      assert!(context.index.inscription_exists(first_id).unwrap());
+      assert!(!context.index.inscription_exists(second_id).unwrap());
+    }
+  }
+
+  #[test]
+  fn inscription_without_parent_tag_has_no_parent_entry() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      assert!(context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap()
+        .parents
+        .is_empty());
+    }
+  }
+
+  #[test]
+  fn inscription_with_parent_tag_without_parent_has_no_parent_entry() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent_inscription_id = InscriptionId {
         txid: parent_txid,
         index: 0,
       };
@@ -6295,6 +7235,176 @@ mod tests {
     }
   }
 
+  fn next_event(event_receiver: &mut tokio::sync::mpsc::Receiver<Event>) -> Event {
+    loop {
+      match event_receiver.blocking_recv().unwrap() {
+        Event::BlockCommitted { .. } | Event::BlockIndexed { .. } => continue,
+        event => return event,
+      }
+    }
+  }
+
+  #[test]
+  fn block_events() {
+    let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
+    let context = Context::builder().event_sender(event_sender).build();
+
+    context.mine_blocks(2);
+
+    let block_hash = |block_height| {
+      context
+        .index
+        .block_hash(Some(block_height))
+        .unwrap()
+        .unwrap()
+    };
+
+    let mut events = Vec::new();
+    while let Ok(event) = event_receiver.try_recv() {
+      events.push(event);
+    }
+
+    pretty_assert_eq!(
+      events,
+      [
+        Event::BlockIndexed {
+          block_hash: block_hash(0),
+          block_height: 0,
+        },
+        Event::BlockCommitted {
+          block_hash: block_hash(0),
+          block_height: 0,
+        },
+        Event::BlockIndexed {
+          block_hash: block_hash(1),
+          block_height: 1,
+        },
+        Event::BlockIndexed {
+          block_hash: block_hash(2),
+          block_height: 2,
+        },
+        Event::BlockCommitted {
+          block_hash: block_hash(2),
+          block_height: 2,
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn rollback_event_is_sent_on_reorg() {
+    let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
+    let mut context = Context::builder().event_sender(event_sender).build();
+
+    context.index.set_durability(redb::Durability::Immediate);
+
+    context.mine_blocks(1);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    assert!(matches!(
+      next_event(&mut event_receiver),
+      Event::InscriptionCreated { inscription_id: id, .. } if id == inscription_id,
+    ));
+
+    context.core.invalidate_tip();
+    context.mine_blocks(2);
+
+    assert_eq!(
+      next_event(&mut event_receiver),
+      Event::Rollback {
+        from_height: 2,
+        to_height: 2,
+      }
+    );
+
+    let mut events = Vec::new();
+    while let Ok(event) = event_receiver.try_recv() {
+      events.push(event);
+    }
+
+    let block_hash = |block_height| {
+      context
+        .index
+        .block_hash(Some(block_height))
+        .unwrap()
+        .unwrap()
+    };
+
+    pretty_assert_eq!(
+      events,
+      [
+        Event::BlockIndexed {
+          block_hash: block_hash(2),
+          block_height: 2,
+        },
+        Event::BlockIndexed {
+          block_hash: block_hash(3),
+          block_height: 3,
+        },
+        Event::BlockCommitted {
+          block_hash: block_hash(3),
+          block_height: 3,
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn unrecoverable_reorg_leaves_no_uncommitted_events() {
+    let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
+    let mut context = Context::builder()
+      .arg("--undo-log-depth=0")
+      .event_sender(event_sender)
+      .build();
+
+    context.index.set_durability(redb::Durability::Immediate);
+
+    for _ in 0..40 {
+      context.mine_blocks(1);
+    }
+
+    for _ in 0..25 {
+      context.core.invalidate_tip();
+    }
+
+    context.mine_blocks_with_update(27, false);
+
+    assert_eq!(
+      context
+        .index
+        .update()
+        .unwrap_err()
+        .downcast_ref::<reorg::Error>(),
+      Some(&reorg::Error::Unrecoverable),
+    );
+
+    let mut uncommitted = Vec::new();
+
+    while let Ok(event) = event_receiver.try_recv() {
+      match event {
+        Event::BlockIndexed { block_height, .. } => uncommitted.push(block_height),
+        Event::BlockCommitted { block_height, .. } => {
+          uncommitted.retain(|height| *height > block_height)
+        }
+        Event::Rollback {
+          from_height,
+          to_height,
+        } => uncommitted.retain(|height| !(to_height..=from_height).contains(height)),
+        _ => {}
+      }
+    }
+
+    assert_eq!(uncommitted, Vec::<u32>::new());
+  }
+
   #[test]
   fn inscription_event_sender_channel() {
     let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
@@ -6316,7 +7426,7 @@ mod tests {
       txid: create_txid,
       index: 0,
     };
-    let create_event = event_receiver.blocking_recv().unwrap();
+    let create_event = next_event(&mut event_receiver);
     let expected_charms = if context.index.index_sats { 513 } else { 0 };
     assert_eq!(
       create_event,
@@ -6330,8 +7440,10 @@ mod tests {
           offset: 0
         }),
         sequence_number: 0,
+        block_hash: context.index.block_hash(Some(2)).unwrap().unwrap(),
         block_height: 2,
         charms: expected_charms,
+        content_type: None,
         parent_inscription_ids: Vec::new(),
       }
     );
@@ -6346,11 +7458,13 @@ mod tests {
 
     context.mine_blocks(1);
 
-    let transfer_event = event_receiver.blocking_recv().unwrap();
+    let transfer_event = next_event(&mut event_receiver);
     assert_eq!(
       transfer_event,
       Event::InscriptionTransferred {
+        block_hash: context.index.block_hash(Some(3)).unwrap().unwrap(),
         block_height: 3,
+        content_type: None,
         inscription_id,
         new_location: SatPoint {
           outpoint: OutPoint {
@@ -6366,11 +7480,87 @@ mod tests {
           },
           offset: 0
         },
+        parent_inscription_ids: Vec::new(),
         sequence_number: 0,
       }
     );
   }
 
+  #[test]
+  fn inscription_transferred_event_includes_uncommitted_parents_and_content_type() {
+    let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
+    let context = Context::builder().event_sender(event_sender).build();
+
+    context.mine_blocks(1);
+
+    let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "parent").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let parent = InscriptionId {
+      txid: parent_txid,
+      index: 0,
+    };
+
+    assert!(matches!(
+      next_event(&mut event_receiver),
+      Event::InscriptionCreated { .. }
+    ));
+
+    let child_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        2,
+        1,
+        0,
+        Inscription {
+          content_type: Some("image/png".into()),
+          body: Some("child".into()),
+          parents: vec![parent.value()],
+          ..default()
+        }
+        .to_witness(),
+      )],
+      outputs: 2,
+      ..default()
+    });
+
+    context.core.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 0, Default::default())],
+      ..default()
+    });
+
+    context.core.mine_blocks(1);
+
+    context.index.update().unwrap();
+
+    let child = InscriptionId {
+      txid: child_txid,
+      index: 0,
+    };
+
+    let mut transfers = Vec::new();
+
+    while let Ok(event) = event_receiver.try_recv() {
+      if let Event::InscriptionTransferred {
+        content_type,
+        inscription_id,
+        parent_inscription_ids,
+        ..
+      } = event
+      {
+        transfers.push((inscription_id, content_type, parent_inscription_ids));
+      }
+    }
+
+    assert!(transfers.contains(&(child, Some("image/png".into()), vec![parent])));
+    assert!(transfers.contains(&(parent, Some("text/plain".into()), Vec::new())));
+  }
+
   #[test]
   fn rune_event_sender_channel() {
     const RUNE: u128 = 99246114928149462;
@@ -6421,8 +7611,9 @@ mod tests {
     );
 
     assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_event(&mut event_receiver),
       Event::RuneEtched {
+        block_hash: context.index.block_hash(Some(8)).unwrap().unwrap(),
         block_height: 8,
         txid: txid0,
         rune_id: id,
@@ -6474,8 +7665,9 @@ mod tests {
     );
 
     assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_event(&mut event_receiver),
       Event::RuneMinted {
+        block_hash: context.index.block_hash(Some(9)).unwrap().unwrap(),
         block_height: 9,
         txid: txid1,
         rune_id: id,
@@ -6530,11 +7722,12 @@ mod tests {
       )],
     );
 
-    event_receiver.blocking_recv().unwrap();
+    next_event(&mut event_receiver);
 
     pretty_assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_event(&mut event_receiver),
       Event::RuneTransferred {
+        block_hash: context.index.block_hash(Some(10)).unwrap().unwrap(),
         block_height: 10,
         txid: txid2,
         rune_id: id,
@@ -6595,11 +7788,12 @@ mod tests {
       )],
     );
 
-    event_receiver.blocking_recv().unwrap();
+    next_event(&mut event_receiver);
 
     pretty_assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_event(&mut event_receiver),
       Event::RuneBurned {
+        block_hash: context.index.block_hash(Some(11)).unwrap().unwrap(),
         block_height: 11,
         txid: txid3,
         amount: 111,
@@ -6607,4 +7801,565 @@ mod tests {
       }
     );
   }
+
+  #[test]
+  fn address_index_tracks_unspent_outputs() {
+    let context = Context::builder().arg("--index-addresses").build();
+
+    context.mine_blocks(1);
+
+    let address = default_address(Chain::Regtest);
+
+    assert_eq!(
+      context.index.get_address_info(&address).unwrap(),
+      Vec::new()
+    );
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      outputs: 2,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let outputs = context.index.get_address_info(&address).unwrap();
+
+    assert_eq!(
+      outputs
+        .iter()
+        .map(|(outpoint, _txout)| *outpoint)
+        .collect::<Vec<OutPoint>>(),
+      [OutPoint { txid, vout: 0 }, OutPoint { txid, vout: 1 }],
+    );
+
+    assert_eq!(
+      outputs
+        .iter()
+        .map(|(_outpoint, txout)| txout.value)
+        .sum::<u64>(),
+      50 * COIN_VALUE,
+    );
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default()), (2, 1, 1, Default::default())],
+      p2tr: true,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context.index.get_address_info(&address).unwrap(),
+      Vec::new()
+    );
+  }
+
+  #[test]
+  fn address_index_is_rolled_back_on_reorg() {
+    let mut context = Context::builder().arg("--index-addresses").build();
+
+    context.index.set_durability(redb::Durability::Immediate);
+
+    context.mine_blocks(1);
+
+    let address = default_address(Chain::Regtest);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      ..default()
+    });
+
+    context.mine_blocks(6);
+
+    let outpoint = OutPoint { txid, vout: 0 };
+
+    assert_eq!(
+      context
+        .index
+        .get_address_info(&address)
+        .unwrap()
+        .into_iter()
+        .map(|(outpoint, _txout)| outpoint)
+        .collect::<Vec<OutPoint>>(),
+      [outpoint],
+    );
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      p2tr: true,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context.index.get_address_info(&address).unwrap(),
+      Vec::new()
+    );
+
+    context.core.invalidate_tip();
+    context.mine_blocks(2);
+
+    assert_eq!(
+      context
+        .index
+        .get_address_info(&address)
+        .unwrap()
+        .into_iter()
+        .map(|(outpoint, _txout)| outpoint)
+        .collect::<Vec<OutPoint>>(),
+      [outpoint],
+    );
+  }
+
+  #[test]
+  fn search_results_are_paginated_and_filtered() {
+    let context = Context::builder().arg("--index-search").build();
+
+    context.mine_blocks(1);
+
+    let mut inscriptions = Vec::new();
+
+    for i in 0..3 {
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(
+          i + 1,
+          0,
+          0,
+          inscription("text/plain", format!("foo bar{i}")).to_witness(),
+        )],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      inscriptions.push(InscriptionId { txid, index: 0 });
+    }
+
+    let search = |query: &str, filter: search::Filter, page_size, page_index| {
+      context
+        .index
+        .search(query, &filter, page_size, page_index)
+        .unwrap()
+    };
+
+    assert_eq!(
+      search("foo", default(), 2, 0),
+      (vec![inscriptions[2], inscriptions[1]], true)
+    );
+
+    assert_eq!(
+      search("foo", default(), 2, 1),
+      (vec![inscriptions[0]], false)
+    );
+
+    assert_eq!(
+      search("foo bar1", default(), 2, 0),
+      (vec![inscriptions[1]], false)
+    );
+
+    assert_eq!(search("x", default(), 2, 0), (Vec::new(), false));
+
+    assert_eq!(
+      search(
+        "foo",
+        search::Filter {
+          from_height: Some(3),
+          ..default()
+        },
+        10,
+        0
+      ),
+      (vec![inscriptions[2], inscriptions[1]], false)
+    );
+
+    assert_eq!(
+      search(
+        "foo",
+        search::Filter {
+          content_type: Some("text/html".into()),
+          ..default()
+        },
+        10,
+        0
+      ),
+      (Vec::new(), false)
+    );
+  }
+
+  #[test]
+  fn filtered_inscriptions_are_paginated() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let mut inscriptions = Vec::new();
+
+    for i in 0..3 {
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(
+          i + 1,
+          0,
+          0,
+          inscription("text/plain;charset=utf-8", "foo").to_witness(),
+        )],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      inscriptions.push(InscriptionId { txid, index: 0 });
+    }
+
+    assert!(context.index.has_inscription_filter_index());
+
+    let filter = |order| InscriptionFilter {
+      content_type: Some("text/plain".into()),
+      order,
+      ..default()
+    };
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter(Order::Newest), 2, 0)
+        .unwrap(),
+      (vec![inscriptions[2], inscriptions[1]], true)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter(Order::Newest), 2, 1)
+        .unwrap(),
+      (vec![inscriptions[0]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter(Order::Oldest), 2, 0)
+        .unwrap(),
+      (vec![inscriptions[0], inscriptions[1]], true)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(
+          &InscriptionFilter {
+            min_height: Some(3),
+            max_height: Some(3),
+            ..default()
+          },
+          2,
+          0
+        )
+        .unwrap(),
+      (vec![inscriptions[1]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(
+          &InscriptionFilter {
+            min_height: Some(5),
+            ..default()
+          },
+          2,
+          0
+        )
+        .unwrap(),
+      (Vec::new(), false)
+    );
+  }
+
+  #[test]
+  fn inscription_filters_are_combined() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let mut inscriptions = Vec::new();
+
+    for (i, (content_type, metaprotocol)) in [
+      ("text/plain", Some("foo")),
+      ("image/png", Some("foo")),
+      ("text/plain", None),
+      ("text/plain", Some("foo")),
+      ("text/plain", Some("bar")),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(
+          i + 1,
+          0,
+          0,
+          Inscription {
+            content_type: Some(content_type.into()),
+            metaprotocol: metaprotocol.map(|metaprotocol| metaprotocol.into()),
+            body: Some("foo".into()),
+            ..default()
+          }
+          .to_witness(),
+        )],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      inscriptions.push(InscriptionId { txid, index: 0 });
+    }
+
+    let filter = |content_type: &str, order| InscriptionFilter {
+      content_type: Some(content_type.into()),
+      metaprotocol: Some("foo".into()),
+      order,
+      ..default()
+    };
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter("text/plain", Order::Newest), 10, 0)
+        .unwrap(),
+      (vec![inscriptions[3], inscriptions[0]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter("text/plain", Order::Oldest), 10, 0)
+        .unwrap(),
+      (vec![inscriptions[0], inscriptions[3]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter("text/plain", Order::Newest), 1, 0)
+        .unwrap(),
+      (vec![inscriptions[3]], true)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter("text/plain", Order::Newest), 1, 1)
+        .unwrap(),
+      (vec![inscriptions[0]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter("image/png", Order::Oldest), 10, 0)
+        .unwrap(),
+      (vec![inscriptions[1]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(
+          &InscriptionFilter {
+            min_height: Some(3),
+            ..filter("text/plain", Order::Oldest)
+          },
+          10,
+          0
+        )
+        .unwrap(),
+      (vec![inscriptions[3]], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_filtered(&filter("video/mp4", Order::Newest), 10, 0)
+        .unwrap(),
+      (Vec::new(), false)
+    );
+  }
+
+  #[test]
+  fn search_results_match_all_terms() {
+    let context = Context::builder().arg("--index-search").build();
+
+    context.mine_blocks(6);
+
+    let mut inscriptions = Vec::new();
+
+    for i in 0..6 {
+      let mut body = "foo".to_string();
+
+      if i % 2 == 0 {
+        body.push_str(" bar");
+      }
+
+      if i % 3 == 0 {
+        body.push_str(" baz");
+      }
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 0, 0, inscription("text/plain", body).to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      inscriptions.push(InscriptionId { txid, index: 0 });
+    }
+
+    let search = |query: &str, page_size, page_index| {
+      context
+        .index
+        .search(query, &default(), page_size, page_index)
+        .unwrap()
+    };
+
+    assert_eq!(search("foo bar baz", 10, 0), (vec![inscriptions[0]], false));
+
+    assert_eq!(
+      search("baz foo", 10, 0),
+      (vec![inscriptions[3], inscriptions[0]], false)
+    );
+
+    assert_eq!(search("bar foo", 1, 1), (vec![inscriptions[2]], true));
+
+    assert_eq!(search("bar baz qux", 10, 0), (Vec::new(), false));
+  }
+
+  #[test]
+  fn search_terms_are_not_indexed_without_flag() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(!context.index.has_search_index());
+
+    assert_eq!(
+      context.index.search("foo", &default(), 10, 0).unwrap(),
+      (Vec::new(), false)
+    );
+  }
+
+  #[test]
+  fn rune_balances_are_paginated() {
+    const RUNE: u128 = 99246114928149462;
+
+    let context = Context::builder().arg("--index-runes").build();
+
+    let (txid, id) = context.etch(
+      Runestone {
+        edicts: (0..4)
+          .map(|output| Edict {
+            id: RuneId::default(),
+            amount: output + 1,
+            output: output.try_into().unwrap(),
+          })
+          .collect(),
+        etching: Some(Etching {
+          premine: Some(10),
+          rune: Some(Rune(RUNE)),
+          ..default()
+        }),
+        ..default()
+      },
+      4,
+    );
+
+    let balances = (0..4)
+      .map(|vout| (OutPoint { txid, vout }, u128::from(vout) + 1))
+      .collect::<Vec<(OutPoint, u128)>>();
+
+    assert_eq!(
+      context.index.get_rune_balances_paginated(id, 2, 0).unwrap(),
+      (balances[..2].to_vec(), true)
+    );
+
+    assert_eq!(
+      context.index.get_rune_balances_paginated(id, 2, 1).unwrap(),
+      (balances[2..].to_vec(), false)
+    );
+
+    assert_eq!(
+      context.index.get_rune_balances_paginated(id, 4, 0).unwrap(),
+      (balances, false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_rune_balances_paginated(RuneId { block: 1, tx: 1 }, 3, 0)
+        .unwrap(),
+      (Vec::new(), false)
+    );
+  }
+
+  #[test]
+  fn rune_balance_index_is_built_by_migration() {
+    const RUNE: u128 = 99246114928149462;
+
+    let context = Context::builder().arg("--index-runes").build();
+
+    let (txid, id) = context.etch(
+      Runestone {
+        edicts: (0..2)
+          .map(|output| Edict {
+            id: RuneId::default(),
+            amount: output + 1,
+            output: output.try_into().unwrap(),
+          })
+          .collect(),
+        etching: Some(Etching {
+          premine: Some(3),
+          rune: Some(Rune(RUNE)),
+          ..default()
+        }),
+        ..default()
+      },
+      2,
+    );
+
+    let wtx = context.index.database.begin_write().unwrap();
+    wtx.delete_multimap_table(RUNE_ID_TO_OUTPOINT).unwrap();
+    wtx.commit().unwrap();
+
+    Migration::run(
+      MIGRATIONS,
+      &context.index.database,
+      context.tempdir.path(),
+      STATISTIC_TO_COUNT,
+      Statistic::Schema.key(),
+      29,
+      SCHEMA_VERSION,
+    )
+    .unwrap();
+
+    assert_eq!(
+      context
+        .index
+        .get_rune_balances_paginated(id, 10, 0)
+        .unwrap(),
+      (
+        vec![
+          (OutPoint { txid, vout: 0 }, 1),
+          (OutPoint { txid, vout: 1 }, 2),
+        ],
+        false
+      )
+    );
+  }
 }
@@ -1,41 +1,64 @@
 use super::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
+  BlockCommitted {
+    block_hash: BlockHash,
+    block_height: u32,
+  },
+  BlockIndexed {
+    block_hash: BlockHash,
+    block_height: u32,
+  },
   InscriptionCreated {
+    block_hash: BlockHash,
     block_height: u32,
     charms: u16,
+    content_type: Option<String>,
     inscription_id: InscriptionId,
     location: Option<SatPoint>,
     parent_inscription_ids: Vec<InscriptionId>,
     sequence_number: u32,
   },
   InscriptionTransferred {
+    block_hash: BlockHash,
     block_height: u32,
+    /// Always `None` for inscriptions indexed before index schema 31.
+    content_type: Option<String>,
     inscription_id: InscriptionId,
     new_location: SatPoint,
     old_location: SatPoint,
+    parent_inscription_ids: Vec<InscriptionId>,
     sequence_number: u32,
   },
+  /// Events for blocks at heights `to_height..=from_height` were reverted and
+  /// must be discarded, either because of a reorg or because those blocks were
+  /// indexed but never committed.
+  Rollback { from_height: u32, to_height: u32 },
   RuneBurned {
     amount: u128,
+    block_hash: BlockHash,
     block_height: u32,
     rune_id: RuneId,
     txid: Txid,
   },
   RuneEtched {
+    block_hash: BlockHash,
     block_height: u32,
     rune_id: RuneId,
     txid: Txid,
   },
   RuneMinted {
     amount: u128,
+    block_hash: BlockHash,
     block_height: u32,
     rune_id: RuneId,
     txid: Txid,
   },
   RuneTransferred {
     amount: u128,
+    block_hash: BlockHash,
     block_height: u32,
     outpoint: OutPoint,
     rune_id: RuneId,
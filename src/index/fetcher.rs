@@ -8,6 +8,7 @@ use {
 pub(crate) struct Fetcher {
   auth: String,
   client: Client<HttpConnector>,
+  metrics: Arc<Metrics>,
   url: Uri,
 }
 
@@ -25,7 +26,7 @@ struct JsonError {
 }
 
 impl Fetcher {
-  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+  pub(crate) fn new(settings: &Settings, metrics: Arc<Metrics>) -> Result<Self> {
     let client = Client::new();
 
     let url = if settings.bitcoin_rpc_url(None).starts_with("http://") {
@@ -42,7 +43,12 @@ impl Fetcher {
       "Basic {}",
       &base64::engine::general_purpose::STANDARD.encode(auth)
     );
-    Ok(Fetcher { client, url, auth })
+    Ok(Fetcher {
+      client,
+      metrics,
+      url,
+      auth,
+    })
   }
 
   pub(crate) async fn get_transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
@@ -70,6 +76,8 @@ impl Fetcher {
       results = match self.try_get_transactions(body.clone()).await {
         Ok(results) => results,
         Err(error) => {
+          Metrics::increment(&self.metrics.transaction_fetch_errors, 1);
+
           if retries >= 5 {
             return Err(anyhow!(
               "failed to fetch raw transactions after 5 retries: {}",
@@ -89,6 +97,7 @@ impl Fetcher {
 
     // Return early on any error, because we need all results to proceed
     if let Some(err) = results.iter().find_map(|res| res.error.as_ref()) {
+      Metrics::increment(&self.metrics.transaction_fetch_errors, 1);
       return Err(anyhow!(
         "failed to fetch raw transaction: code {} message {}",
         err.code,
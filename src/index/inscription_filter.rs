@@ -0,0 +1,67 @@
+use super::*;
+
+#[derive(Debug, Default)]
+pub(crate) struct InscriptionFilter {
+  pub(crate) charm: Option<Charm>,
+  pub(crate) content_type: Option<String>,
+  pub(crate) max_height: Option<u32>,
+  pub(crate) metaprotocol: Option<String>,
+  pub(crate) min_height: Option<u32>,
+  pub(crate) order: Order,
+  pub(crate) parent: Option<InscriptionId>,
+}
+
+impl InscriptionFilter {
+  /// Whether the filter needs the charm, content type, and metaprotocol
+  /// tables, which are empty in indices migrated from older schemas.
+  pub(crate) fn requires_filter_index(&self) -> bool {
+    self.charm.is_some() || self.content_type.is_some() || self.metaprotocol.is_some()
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum Order {
+  #[default]
+  Newest,
+  Oldest,
+}
+
+impl Display for Order {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Newest => write!(f, "newest"),
+      Self::Oldest => write!(f, "oldest"),
+    }
+  }
+}
+
+impl FromStr for Order {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    match s {
+      "newest" => Ok(Self::Newest),
+      "oldest" => Ok(Self::Oldest),
+      _ => bail!("invalid order `{s}`, expected `newest` or `oldest`"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn order_from_str() {
+    assert_eq!("newest".parse::<Order>().unwrap(), Order::Newest);
+    assert_eq!("oldest".parse::<Order>().unwrap(), Order::Oldest);
+    assert!("foo".parse::<Order>().is_err());
+  }
+
+  #[test]
+  fn order_display_round_trips() {
+    for order in [Order::Newest, Order::Oldest] {
+      assert_eq!(order.to_string().parse::<Order>().unwrap(), order);
+    }
+  }
+}
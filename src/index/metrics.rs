@@ -0,0 +1,30 @@
+use super::*;
+
+/// Counters updated by the indexer and reported by the server's `/metrics`
+/// endpoint. Counters start at zero when the index is opened.
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+  pub(crate) block_fetch_errors: AtomicU64,
+  pub(crate) blocks_indexed: AtomicU64,
+  pub(crate) indexing_time: AtomicU64,
+  pub(crate) outputs_traversed: AtomicU64,
+  pub(crate) recoverable_reorgs: AtomicU64,
+  pub(crate) transaction_fetch_errors: AtomicU64,
+  pub(crate) unrecoverable_reorgs: AtomicU64,
+}
+
+impl Metrics {
+  pub(crate) fn get(counter: &AtomicU64) -> u64 {
+    counter.load(atomic::Ordering::Relaxed)
+  }
+
+  pub(crate) fn increment(counter: &AtomicU64, n: u64) {
+    counter.fetch_add(n, atomic::Ordering::Relaxed);
+  }
+
+  /// Time spent indexing blocks, excluding time spent waiting for blocks to
+  /// be fetched.
+  pub(crate) fn indexing_time(&self) -> Duration {
+    Duration::from_micros(Self::get(&self.indexing_time))
+  }
+}
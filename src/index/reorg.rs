@@ -19,10 +19,6 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
-const MAX_SAVEPOINTS: u32 = 2;
-const SAVEPOINT_INTERVAL: u32 = 10;
-const CHAIN_TIP_DISTANCE: u32 = 21;
-
 pub(crate) struct Reorg {}
 
 impl Reorg {
@@ -32,8 +28,14 @@ impl Reorg {
     match index.block_hash(height.checked_sub(1))? {
       Some(index_prev_blockhash) if index_prev_blockhash == bitcoind_prev_blockhash => Ok(()),
       Some(index_prev_blockhash) if index_prev_blockhash != bitcoind_prev_blockhash => {
+        // the undo log can revert every block from `start` to the tip
+        let max_undo_log_depth = index
+          .undo_log_start(height - 1)?
+          .map(|start| height + 2 - start)
+          .unwrap_or_default();
+
         let max_recoverable_reorg_depth =
-          (MAX_SAVEPOINTS - 1) * SAVEPOINT_INTERVAL + height % SAVEPOINT_INTERVAL;
+          Self::max_savepoint_depth(&index.settings, height).max(max_undo_log_depth);
 
         for depth in 1..max_recoverable_reorg_depth {
           let index_block_hash = index.block_hash(height.checked_sub(depth))?;
@@ -53,27 +55,45 @@ impl Reorg {
     }
   }
 
+  fn max_savepoint_depth(settings: &Settings, height: u32) -> u32 {
+    let savepoint_interval = settings.savepoint_interval();
+    (settings.max_savepoints() - 1) * savepoint_interval + height % savepoint_interval
+  }
+
   pub(crate) fn handle_reorg(index: &Index, height: u32, depth: u32) -> Result {
     log::info!("rolling back database after reorg of depth {depth} at height {height}");
 
-    if let redb::Durability::None = index.durability {
-      panic!("set index durability to `Durability::Immediate` to test reorg handling");
-    }
-
     let mut wtx = index.begin_write()?;
 
-    let oldest_savepoint =
-      wtx.get_persistent_savepoint(wtx.list_persistent_savepoints()?.min().unwrap())?;
+    let oldest_savepoint = if depth < Self::max_savepoint_depth(&index.settings, height) {
+      wtx.list_persistent_savepoints()?.min()
+    } else {
+      None
+    };
+
+    let Some(oldest_savepoint) = oldest_savepoint else {
+      drop(wtx);
+      index.rollback(height - depth)?;
+      return Ok(());
+    };
+
+    let oldest_savepoint = wtx.get_persistent_savepoint(oldest_savepoint)?;
 
     wtx.restore_savepoint(&oldest_savepoint)?;
 
     Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
     wtx.commit()?;
 
-    log::info!(
-      "successfully rolled back database to height {}",
-      index.begin_read()?.block_count()?
-    );
+    let block_count = index.begin_read()?.block_count()?;
+
+    log::info!("successfully rolled back database to height {block_count}");
+
+    if let Some(sender) = &index.event_sender {
+      sender.blocking_send(Event::Rollback {
+        from_height: height - 1,
+        to_height: block_count,
+      })?;
+    }
 
     Ok(())
   }
@@ -83,7 +103,9 @@ impl Reorg {
       return Ok(());
     }
 
-    if (height < SAVEPOINT_INTERVAL || height % SAVEPOINT_INTERVAL == 0)
+    let savepoint_interval = index.settings.savepoint_interval();
+
+    if (height < savepoint_interval || height % savepoint_interval == 0)
       && u32::try_from(
         index
           .settings
@@ -93,13 +115,13 @@ impl Reorg {
       )
       .unwrap()
       .saturating_sub(height)
-        <= CHAIN_TIP_DISTANCE
+        <= index.settings.chain_tip_distance()
     {
       let wtx = index.begin_write()?;
 
       let savepoints = wtx.list_persistent_savepoints()?.collect::<Vec<u64>>();
 
-      if savepoints.len() >= usize::try_from(MAX_SAVEPOINTS).unwrap() {
+      if savepoints.len() >= usize::try_from(index.settings.max_savepoints()).unwrap() {
         wtx.delete_persistent_savepoint(savepoints.into_iter().min().unwrap())?;
       }
 
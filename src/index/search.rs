@@ -0,0 +1,259 @@
+use super::*;
+
+const MAX_TERMS: usize = 1024;
+const MAX_TERM_LENGTH: usize = 64;
+const MIN_TERM_LENGTH: usize = 2;
+
+const CONTENT_TYPES: &[&str] = &[
+  "application/json",
+  "text/html",
+  "text/markdown",
+  "text/plain",
+];
+
+#[derive(Debug, Default)]
+pub(crate) struct Filter {
+  pub(crate) charm: Option<Charm>,
+  pub(crate) content_type: Option<String>,
+  pub(crate) from_height: Option<u32>,
+  pub(crate) parent: Option<InscriptionId>,
+  pub(crate) to_height: Option<u32>,
+}
+
+/// Terms under which `inscription` is indexed. Unencoded text, markdown, JSON,
+/// and HTML bodies are indexed, along with the metaprotocol and any text or
+/// integers in the metadata. The content type is indexed as a single term
+/// which cannot collide with terms produced by tokenizing text.
+pub(super) fn terms(inscription: &Inscription) -> Vec<String> {
+  let mut terms = Terms::default();
+
+  let content_type = inscription.content_type().map(media_type);
+
+  if let Some(content_type) = &content_type {
+    terms.push(content_type_term(content_type));
+  }
+
+  if let Some(metaprotocol) = inscription.metaprotocol() {
+    terms.tokenize(metaprotocol);
+  }
+
+  if let Some(metadata) = inscription.metadata() {
+    terms.metadata(&metadata);
+  }
+
+  if let (Some(content_type), Some(body), None) = (
+    content_type,
+    inscription.body(),
+    inscription.content_encoding(),
+  ) {
+    if CONTENT_TYPES.contains(&content_type.as_str()) {
+      if let Ok(text) = std::str::from_utf8(body) {
+        if content_type == "text/html" {
+          terms.tokenize(&strip_tags(text));
+        } else {
+          terms.tokenize(text);
+        }
+      }
+    }
+  }
+
+  terms.terms
+}
+
+/// Terms to look up for a user-supplied query.
+pub(super) fn query_terms(query: &str, filter: &Filter) -> Vec<String> {
+  let mut terms = Terms::default();
+
+  terms.tokenize(query);
+
+  if let Some(content_type) = &filter.content_type {
+    terms.push(content_type_term(&media_type(content_type)));
+  }
+
+  terms.terms
+}
+
+fn content_type_term(media_type: &str) -> String {
+  format!("content-type:{media_type}")
+}
+
+pub(super) fn media_type(content_type: &str) -> String {
+  content_type
+    .split(';')
+    .next()
+    .unwrap_or_default()
+    .trim()
+    .to_lowercase()
+}
+
+fn strip_tags(html: &str) -> String {
+  let mut text = String::with_capacity(html.len());
+  let mut in_tag = false;
+
+  for c in html.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' if in_tag => {
+        in_tag = false;
+        text.push(' ');
+      }
+      _ if !in_tag => text.push(c),
+      _ => {}
+    }
+  }
+
+  text
+}
+
+#[derive(Default)]
+struct Terms {
+  seen: HashSet<String>,
+  terms: Vec<String>,
+}
+
+impl Terms {
+  fn push(&mut self, term: String) {
+    if self.terms.len() < MAX_TERMS && self.seen.insert(term.clone()) {
+      self.terms.push(term);
+    }
+  }
+
+  fn tokenize(&mut self, text: &str) {
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+      let length = word.chars().count();
+
+      if (MIN_TERM_LENGTH..=MAX_TERM_LENGTH).contains(&length) {
+        self.push(word.to_lowercase());
+      }
+    }
+  }
+
+  fn metadata(&mut self, value: &Value) {
+    match value {
+      Value::Array(values) => {
+        for value in values {
+          self.metadata(value);
+        }
+      }
+      Value::Integer(integer) => self.tokenize(&i128::from(*integer).to_string()),
+      Value::Map(entries) => {
+        for (key, value) in entries {
+          self.metadata(key);
+          self.metadata(value);
+        }
+      }
+      Value::Tag(_, value) => self.metadata(value),
+      Value::Text(text) => self.tokenize(text),
+      _ => {}
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn text_is_tokenized() {
+    assert_eq!(
+      terms(&inscription(
+        "text/plain;charset=utf-8",
+        "Hello, World! a hello-world"
+      )),
+      ["content-type:text/plain", "hello", "world"],
+    );
+  }
+
+  #[test]
+  fn html_tags_are_stripped() {
+    assert_eq!(
+      terms(&inscription(
+        "text/html",
+        "<html><body class=foo>bar<b>baz</b></body></html>"
+      )),
+      ["content-type:text/html", "bar", "baz"],
+    );
+  }
+
+  #[test]
+  fn unindexed_content_types_are_not_tokenized() {
+    assert_eq!(
+      terms(&inscription("image/png", "hello")),
+      ["content-type:image/png"],
+    );
+  }
+
+  #[test]
+  fn encoded_bodies_are_not_tokenized() {
+    assert_eq!(
+      terms(&Inscription {
+        content_encoding: Some("br".into()),
+        ..inscription("text/plain", "hello")
+      }),
+      ["content-type:text/plain"],
+    );
+  }
+
+  #[test]
+  fn metaprotocol_and_metadata_are_tokenized() {
+    let mut metadata = Vec::new();
+
+    ciborium::into_writer(
+      &Value::Map(vec![
+        (Value::Text("name".into()), Value::Text("Foo Bar".into())),
+        (
+          Value::Text("traits".into()),
+          Value::Array(vec![Value::Integer(42.into())]),
+        ),
+      ]),
+      &mut metadata,
+    )
+    .unwrap();
+
+    assert_eq!(
+      terms(&Inscription {
+        metaprotocol: Some("brc-20".into()),
+        metadata: Some(metadata),
+        ..default()
+      }),
+      ["brc", "20", "name", "foo", "bar", "traits", "42"],
+    );
+  }
+
+  #[test]
+  fn long_terms_are_skipped() {
+    assert_eq!(
+      query_terms(&format!("{} foo", "a".repeat(65)), &Filter::default()),
+      ["foo"],
+    );
+
+    assert_eq!(
+      query_terms(&"a".repeat(64), &Filter::default()),
+      ["a".repeat(64)],
+    );
+  }
+
+  #[test]
+  fn number_of_terms_is_limited() {
+    let text = (0..2000)
+      .map(|i| format!("t{i}"))
+      .collect::<Vec<String>>()
+      .join(" ");
+
+    assert_eq!(terms(&inscription("text/plain", text)).len(), MAX_TERMS);
+  }
+
+  #[test]
+  fn query_terms_include_content_type_filter() {
+    assert_eq!(
+      query_terms(
+        "Foo foo",
+        &Filter {
+          content_type: Some("Text/Plain; charset=utf-8".into()),
+          ..default()
+        }
+      ),
+      ["foo", "content-type:text/plain"],
+    );
+  }
+}
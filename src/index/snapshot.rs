@@ -0,0 +1,532 @@
+use {
+  super::*,
+  bitcoin::hashes::{sha256, HashEngine},
+  redb::{Key, ReadTransaction},
+  std::io::{BufReader, Read},
+};
+
+const MAGIC: &[u8; 8] = b"ordsnap\0";
+
+const VERSION: u32 = 1;
+
+const END: u8 = 0;
+const TABLE: u8 = 1;
+const MULTIMAP_TABLE: u8 = 2;
+
+macro_rules! snapshot_tables {
+  {
+    tables: [$($table:ident),* $(,)?],
+    multimap_tables: [$($multimap:ident),* $(,)?] $(,)?
+  } => {
+    #[cfg(test)]
+    const TABLES: &[&str] = &[$(stringify!($table)),*];
+
+    #[cfg(test)]
+    const MULTIMAP_TABLES: &[&str] = &[$(stringify!($multimap)),*];
+
+    fn export_tables(rtx: &ReadTransaction, writer: &mut Writer<impl Write>) -> Result {
+      $(Snapshot::export_table(rtx, writer, $table)?;)*
+      $(Snapshot::export_multimap(rtx, writer, $multimap)?;)*
+      Ok(())
+    }
+
+    fn import_section(
+      wtx: &WriteTransaction,
+      reader: &mut Reader<impl Read>,
+      kind: u8,
+      name: &str,
+    ) -> Result {
+      match (kind, name) {
+        $((TABLE, stringify!($table)) => Snapshot::import_table(wtx, reader, $table),)*
+        $((MULTIMAP_TABLE, stringify!($multimap)) => Snapshot::import_multimap(wtx, reader, $multimap),)*
+        _ => bail!("unknown table `{name}` in snapshot"),
+      }
+    }
+  };
+}
+
+snapshot_tables! {
+  tables: [
+    CONTENT_TYPE_TO_COUNT,
+    HEIGHT_TO_BLOCK_HEADER,
+    HEIGHT_TO_LAST_SEQUENCE_NUMBER,
+    HEIGHT_TO_UNDO_LOG,
+    HOME_INSCRIPTIONS,
+    INSCRIPTION_ID_TO_SEQUENCE_NUMBER,
+    INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER,
+    OUTPOINT_TO_RUNE_BALANCES,
+    OUTPOINT_TO_SAT_RANGES,
+    OUTPOINT_TO_TXOUT,
+    OUTPOINT_TO_VALUE,
+    RUNE_ID_TO_RUNE_ENTRY,
+    RUNE_TO_RUNE_ID,
+    SAT_TO_SATPOINT,
+    SEQUENCE_NUMBER_TO_CONTENT_TYPE,
+    SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY,
+    SEQUENCE_NUMBER_TO_RUNE_ID,
+    SEQUENCE_NUMBER_TO_SATPOINT,
+    STATISTIC_TO_COUNT,
+    TRANSACTION_ID_TO_RUNE,
+    TRANSACTION_ID_TO_TRANSACTION,
+    WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP,
+  ],
+  multimap_tables: [
+    CHARM_TO_SEQUENCE_NUMBER,
+    CONTENT_TYPE_TO_SEQUENCE_NUMBER,
+    METAPROTOCOL_TO_SEQUENCE_NUMBER,
+    RUNE_ID_TO_OUTPOINT,
+    SATPOINT_TO_SEQUENCE_NUMBER,
+    SAT_TO_SEQUENCE_NUMBER,
+    SCRIPT_PUBKEY_TO_OUTPOINT,
+    SEARCH_TERM_TO_SEQUENCE_NUMBER,
+    SEQUENCE_NUMBER_TO_CHILDREN,
+  ],
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+  pub block_hash: BlockHash,
+  pub height: u32,
+  pub schema_version: u64,
+}
+
+/// A snapshot is a checksummed dump of every index table, preceded by a
+/// manifest identifying the schema version and the block the index was at.
+///
+/// ```text
+/// magic | version | schema version | height | block hash
+/// (kind | name | (1 | key | value)* | 0)*
+/// 0 | sha256 of all preceding bytes
+/// ```
+pub(crate) struct Snapshot;
+
+impl Snapshot {
+  pub(crate) fn export(index: &Index, path: &Path) -> Result<Manifest> {
+    let rtx = index.database.begin_read()?;
+
+    let (height, header) = rtx
+      .open_table(HEIGHT_TO_BLOCK_HEADER)?
+      .range(0..)?
+      .next_back()
+      .transpose()?
+      .map(|(height, header)| (height.value(), Header::load(*header.value())))
+      .ok_or_else(|| anyhow!("cannot export snapshot of empty index"))?;
+
+    let manifest = Manifest {
+      block_hash: header.block_hash(),
+      height,
+      schema_version: SCHEMA_VERSION,
+    };
+
+    log::info!(
+      "exporting snapshot at height {height} to {}",
+      path.display()
+    );
+
+    let mut writer = Writer {
+      engine: sha256::Hash::engine(),
+      inner: BufWriter::new(fs::File::create(path)?),
+    };
+
+    writer.write(MAGIC)?;
+    writer.write(&VERSION.to_le_bytes())?;
+    writer.write(&manifest.schema_version.to_le_bytes())?;
+    writer.write(&manifest.height.to_le_bytes())?;
+    writer.write(manifest.block_hash.as_byte_array())?;
+
+    export_tables(&rtx, &mut writer)?;
+
+    writer.write(&[END])?;
+    writer.finish()?;
+
+    Ok(manifest)
+  }
+
+  pub(crate) fn import(settings: &Settings, path: &Path) -> Result<Manifest> {
+    let index_path = settings.index();
+
+    ensure!(
+      !index_path.exists(),
+      "index already exists at `{}`",
+      index_path.display()
+    );
+
+    if let Some(parent) = index_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    let partial = PathBuf::from(format!("{}.import", index_path.display()));
+
+    if partial.exists() {
+      fs::remove_file(&partial)?;
+    }
+
+    let result = Self::import_into(settings, path, &partial);
+
+    match result {
+      Ok(manifest) => {
+        fs::rename(&partial, index_path)?;
+        Ok(manifest)
+      }
+      Err(err) => {
+        fs::remove_file(&partial).ok();
+        Err(err)
+      }
+    }
+  }
+
+  fn import_into(settings: &Settings, path: &Path, partial: &Path) -> Result<Manifest> {
+    let mut reader = Reader {
+      engine: sha256::Hash::engine(),
+      inner: BufReader::new(
+        fs::File::open(path)
+          .with_context(|| format!("failed to open snapshot `{}`", path.display()))?,
+      ),
+    };
+
+    ensure!(
+      reader.read(MAGIC.len())? == MAGIC,
+      "`{}` is not an ord index snapshot",
+      path.display()
+    );
+
+    let version = reader.u32()?;
+
+    ensure!(
+      version == VERSION,
+      "unsupported snapshot version {version}, expected {VERSION}"
+    );
+
+    let manifest = Manifest {
+      schema_version: reader.u64()?,
+      height: reader.u32()?,
+      block_hash: BlockHash::from_byte_array(reader.read(32)?.try_into().unwrap()),
+    };
+
+    // older snapshots are migrated when the imported index is first opened
+    ensure!(
+      manifest.schema_version == SCHEMA_VERSION
+        || (manifest.schema_version < SCHEMA_VERSION
+          && Migration::available(MIGRATIONS, manifest.schema_version, SCHEMA_VERSION)),
+      "snapshot has index schema {}, but ord schema is {SCHEMA_VERSION}",
+      manifest.schema_version,
+    );
+
+    let block_hash = settings
+      .bitcoin_rpc_client(None)?
+      .get_block_hash(manifest.height.into())
+      .with_context(|| {
+        format!(
+          "failed to get hash of block {} from bitcoin core",
+          manifest.height
+        )
+      })?;
+
+    ensure!(
+      block_hash == manifest.block_hash,
+      "snapshot block {} at height {} is not in the active chain",
+      manifest.block_hash,
+      manifest.height,
+    );
+
+    log::info!(
+      "importing snapshot at height {} from {}",
+      manifest.height,
+      path.display()
+    );
+
+    let database = Database::builder()
+      .set_cache_size(settings.index_cache_size())
+      .create(partial)?;
+
+    loop {
+      let kind = reader.u8()?;
+
+      if kind == END {
+        break;
+      }
+
+      let name = String::from_utf8(reader.bytes()?).context("invalid table name in snapshot")?;
+
+      let mut wtx = database.begin_write()?;
+      wtx.set_durability(redb::Durability::None);
+      import_section(&wtx, &mut reader, kind, &name)?;
+      wtx.commit()?;
+    }
+
+    reader.finish()?;
+
+    let wtx = database.begin_write()?;
+
+    Self::verify_headers(&wtx, &manifest)?;
+
+    wtx.commit()?;
+
+    Ok(manifest)
+  }
+
+  fn verify_headers(wtx: &WriteTransaction, manifest: &Manifest) -> Result {
+    let mut previous: Option<(u32, BlockHash)> = None;
+
+    for result in wtx.open_table(HEIGHT_TO_BLOCK_HEADER)?.iter()? {
+      let (height, header) = result?;
+      let (height, header) = (height.value(), Header::load(*header.value()));
+
+      if let Some((previous_height, previous_hash)) = previous {
+        ensure!(
+          height == previous_height + 1 && header.prev_blockhash == previous_hash,
+          "snapshot block header at height {height} does not extend block header at height \
+          {previous_height}",
+        );
+      }
+
+      previous = Some((height, header.block_hash()));
+    }
+
+    ensure!(
+      previous == Some((manifest.height, manifest.block_hash)),
+      "snapshot block headers do not end at block {} at height {}",
+      manifest.block_hash,
+      manifest.height,
+    );
+
+    Ok(())
+  }
+
+  fn export_table<K: Key + 'static, V: redb::Value + 'static>(
+    rtx: &ReadTransaction,
+    writer: &mut Writer<impl Write>,
+    definition: TableDefinition<K, V>,
+  ) -> Result {
+    writer.write(&[TABLE])?;
+    writer.bytes(definition.name().as_bytes())?;
+
+    for result in rtx.open_table(definition)?.iter()? {
+      let (key, value) = result?;
+      writer.write(&[1])?;
+      writer.bytes(K::as_bytes(&key.value()).as_ref())?;
+      writer.bytes(V::as_bytes(&value.value()).as_ref())?;
+    }
+
+    writer.write(&[0])
+  }
+
+  fn export_multimap<K: Key + 'static, V: Key + 'static>(
+    rtx: &ReadTransaction,
+    writer: &mut Writer<impl Write>,
+    definition: MultimapTableDefinition<K, V>,
+  ) -> Result {
+    writer.write(&[MULTIMAP_TABLE])?;
+    writer.bytes(definition.name().as_bytes())?;
+
+    for result in rtx.open_multimap_table(definition)?.iter()? {
+      let (key, values) = result?;
+      let key = K::as_bytes(&key.value()).as_ref().to_vec();
+
+      for value in values {
+        writer.write(&[1])?;
+        writer.bytes(&key)?;
+        writer.bytes(V::as_bytes(&value?.value()).as_ref())?;
+      }
+    }
+
+    writer.write(&[0])
+  }
+
+  fn import_table<K: Key + 'static, V: redb::Value + 'static>(
+    wtx: &WriteTransaction,
+    reader: &mut Reader<impl Read>,
+    definition: TableDefinition<K, V>,
+  ) -> Result {
+    let mut table = wtx.open_table(definition)?;
+
+    while let Some((key, value)) = reader.entry()? {
+      table.insert(K::from_bytes(&key), V::from_bytes(&value))?;
+    }
+
+    Ok(())
+  }
+
+  fn import_multimap<K: Key + 'static, V: Key + 'static>(
+    wtx: &WriteTransaction,
+    reader: &mut Reader<impl Read>,
+    definition: MultimapTableDefinition<K, V>,
+  ) -> Result {
+    let mut table = wtx.open_multimap_table(definition)?;
+
+    while let Some((key, value)) = reader.entry()? {
+      table.insert(K::from_bytes(&key), V::from_bytes(&value))?;
+    }
+
+    Ok(())
+  }
+}
+
+struct Writer<W: Write> {
+  engine: sha256::HashEngine,
+  inner: W,
+}
+
+impl<W: Write> Writer<W> {
+  fn write(&mut self, bytes: &[u8]) -> Result {
+    self.engine.input(bytes);
+    self.inner.write_all(bytes)?;
+    Ok(())
+  }
+
+  fn bytes(&mut self, bytes: &[u8]) -> Result {
+    self.write(&u32::try_from(bytes.len())?.to_le_bytes())?;
+    self.write(bytes)
+  }
+
+  fn finish(mut self) -> Result {
+    let checksum = sha256::Hash::from_engine(self.engine);
+    self.inner.write_all(checksum.as_byte_array())?;
+    self.inner.flush()?;
+    Ok(())
+  }
+}
+
+struct Reader<R: Read> {
+  engine: sha256::HashEngine,
+  inner: R,
+}
+
+impl<R: Read> Reader<R> {
+  fn read(&mut self, len: usize) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+
+    (&mut self.inner)
+      .take(len.try_into()?)
+      .read_to_end(&mut buffer)?;
+
+    ensure!(buffer.len() == len, "truncated snapshot");
+
+    self.engine.input(&buffer);
+
+    Ok(buffer)
+  }
+
+  fn u8(&mut self) -> Result<u8> {
+    Ok(self.read(1)?[0])
+  }
+
+  fn u32(&mut self) -> Result<u32> {
+    Ok(u32::from_le_bytes(self.read(4)?.try_into().unwrap()))
+  }
+
+  fn u64(&mut self) -> Result<u64> {
+    Ok(u64::from_le_bytes(self.read(8)?.try_into().unwrap()))
+  }
+
+  fn bytes(&mut self) -> Result<Vec<u8>> {
+    let len = self.u32()?;
+    self.read(len.into_usize())
+  }
+
+  fn entry(&mut self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+    match self.u8()? {
+      0 => Ok(None),
+      1 => Ok(Some((self.bytes()?, self.bytes()?))),
+      tag => bail!("invalid snapshot entry tag {tag}"),
+    }
+  }
+
+  fn finish(mut self) -> Result {
+    let expected = sha256::Hash::from_engine(self.engine);
+
+    let mut checksum = [0; 32];
+    self
+      .inner
+      .read_exact(&mut checksum)
+      .context("truncated snapshot")?;
+
+    ensure!(
+      checksum == expected.to_byte_array(),
+      "snapshot checksum mismatch"
+    );
+
+    ensure!(
+      self.inner.read(&mut [0])? == 0,
+      "unexpected data after snapshot checksum"
+    );
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  #[test]
+  fn snapshot_includes_all_tables() {
+    let context = Context::builder().build();
+
+    let rtx = context.index.database.begin_read().unwrap();
+
+    assert_eq!(
+      rtx
+        .list_tables()
+        .unwrap()
+        .map(|table| table.name().to_string())
+        .collect::<BTreeSet<String>>(),
+      TABLES.iter().map(|table| table.to_string()).collect(),
+    );
+
+    assert_eq!(
+      rtx
+        .list_multimap_tables()
+        .unwrap()
+        .map(|table| table.name().to_string())
+        .collect::<BTreeSet<String>>(),
+      MULTIMAP_TABLES
+        .iter()
+        .map(|table| table.to_string())
+        .collect(),
+    );
+  }
+
+  #[test]
+  fn reader_rejects_bad_checksum() {
+    let mut buffer = Vec::new();
+
+    let mut writer = Writer {
+      engine: sha256::Hash::engine(),
+      inner: &mut buffer,
+    };
+    writer.bytes(b"foo").unwrap();
+    writer.finish().unwrap();
+
+    let read = |buffer: &[u8]| -> Result<Vec<u8>> {
+      let mut reader = Reader {
+        engine: sha256::Hash::engine(),
+        inner: buffer,
+      };
+      let bytes = reader.bytes()?;
+      reader.finish()?;
+      Ok(bytes)
+    };
+
+    assert_eq!(read(&buffer).unwrap(), b"foo");
+
+    let mut corrupted = buffer.clone();
+    corrupted[4] = b'g';
+    assert_eq!(
+      read(&corrupted).unwrap_err().to_string(),
+      "snapshot checksum mismatch"
+    );
+
+    assert_eq!(
+      read(&buffer[..buffer.len() - 1]).unwrap_err().to_string(),
+      "truncated snapshot"
+    );
+
+    let mut extended = buffer.clone();
+    extended.push(0);
+    assert_eq!(
+      read(&extended).unwrap_err().to_string(),
+      "unexpected data after snapshot checksum"
+    );
+  }
+}
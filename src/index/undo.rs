@@ -0,0 +1,909 @@
+use {
+  super::*,
+  redb::{AccessGuard, Key},
+  std::{borrow::Borrow, cell::RefCell},
+};
+
+/// Tables are identified in undo records by the ids given here, which are
+/// persisted in `HEIGHT_TO_UNDO_LOG` and carried across migrations, so an id
+/// must never be changed or reused, and new tables must be given new ids.
+macro_rules! undo_log_tables {
+  {
+    tables: [$($table:ident = $table_id:literal),* $(,)?],
+    multimap_tables: [$($multimap:ident = $multimap_id:literal),* $(,)?] $(,)?
+  } => {
+    const TABLES: &[(&str, u8)] = &[$((stringify!($table), $table_id)),*];
+
+    const MULTIMAP_TABLES: &[(&str, u8)] = &[$((stringify!($multimap), $multimap_id)),*];
+
+    fn restore(wtx: &WriteTransaction, record: &Record) -> Result {
+      match record {
+        Record::Table { table, .. } => match table {
+          $($table_id => UndoLog::restore_table(wtx, $table, record),)*
+          _ => unreachable!(),
+        },
+        Record::Multimap { table, .. } => match table {
+          $($multimap_id => UndoLog::restore_multimap(wtx, $multimap, record),)*
+          _ => unreachable!(),
+        },
+      }
+    }
+
+    #[cfg(test)]
+    fn snapshot(index: &Index) -> BTreeMap<&'static str, Vec<(Vec<u8>, Vec<u8>)>> {
+      let rtx = index.database.begin_read().unwrap();
+
+      let mut snapshot = BTreeMap::new();
+
+      $(
+        snapshot.insert(
+          stringify!($table),
+          rtx
+            .open_table($table)
+            .unwrap()
+            .iter()
+            .unwrap()
+            .map(|entry| {
+              let (key, value) = entry.unwrap();
+              (bytes(&key), bytes(&value))
+            })
+            .collect(),
+        );
+      )*
+
+      $(
+        snapshot.insert(
+          stringify!($multimap),
+          rtx
+            .open_multimap_table($multimap)
+            .unwrap()
+            .iter()
+            .unwrap()
+            .flat_map(|entry| {
+              let (key, values) = entry.unwrap();
+              let key = bytes(&key);
+              values
+                .map(|value| (key.clone(), bytes(&value.unwrap())))
+                .collect::<Vec<(Vec<u8>, Vec<u8>)>>()
+            })
+            .collect(),
+        );
+      )*
+
+      snapshot
+    }
+  };
+}
+
+undo_log_tables! {
+  tables: [
+    CONTENT_TYPE_TO_COUNT = 0,
+    HEIGHT_TO_BLOCK_HEADER = 1,
+    HEIGHT_TO_LAST_SEQUENCE_NUMBER = 2,
+    HOME_INSCRIPTIONS = 3,
+    INSCRIPTION_ID_TO_SEQUENCE_NUMBER = 4,
+    INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER = 5,
+    OUTPOINT_TO_RUNE_BALANCES = 6,
+    OUTPOINT_TO_SAT_RANGES = 7,
+    OUTPOINT_TO_TXOUT = 8,
+    RUNE_ID_TO_RUNE_ENTRY = 9,
+    RUNE_TO_RUNE_ID = 10,
+    SAT_TO_SATPOINT = 11,
+    SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY = 12,
+    SEQUENCE_NUMBER_TO_RUNE_ID = 13,
+    SEQUENCE_NUMBER_TO_SATPOINT = 14,
+    STATISTIC_TO_COUNT = 15,
+    TRANSACTION_ID_TO_RUNE = 16,
+    TRANSACTION_ID_TO_TRANSACTION = 17,
+    SEQUENCE_NUMBER_TO_CONTENT_TYPE = 18,
+  ],
+  multimap_tables: [
+    SATPOINT_TO_SEQUENCE_NUMBER = 0,
+    SAT_TO_SEQUENCE_NUMBER = 1,
+    SCRIPT_PUBKEY_TO_OUTPOINT = 2,
+    SEQUENCE_NUMBER_TO_CHILDREN = 3,
+    SEARCH_TERM_TO_SEQUENCE_NUMBER = 4,
+    CHARM_TO_SEQUENCE_NUMBER = 5,
+    CONTENT_TYPE_TO_SEQUENCE_NUMBER = 6,
+    METAPROTOCOL_TO_SEQUENCE_NUMBER = 7,
+    RUNE_ID_TO_OUTPOINT = 8,
+  ],
+}
+
+#[derive(Debug, PartialEq)]
+enum Record {
+  Table {
+    table: u8,
+    key: Vec<u8>,
+    value: Option<Vec<u8>>,
+  },
+  Multimap {
+    table: u8,
+    key: Vec<u8>,
+    value: Vec<u8>,
+    present: bool,
+  },
+}
+
+/// Records the previous value of every key written while indexing a block,
+/// so that the block can later be reverted by restoring those values in
+/// reverse order. `OUTPOINT_TO_VALUE` is not recorded, since it only caches
+/// output values, which never change, and missing entries are refetched.
+#[derive(Debug, Default)]
+pub(crate) struct UndoLog {
+  enabled: bool,
+  records: RefCell<Vec<Record>>,
+}
+
+impl UndoLog {
+  pub(crate) fn new(enabled: bool) -> Self {
+    Self {
+      enabled,
+      records: RefCell::new(Vec::new()),
+    }
+  }
+
+  fn id(tables: &[(&str, u8)], name: &str) -> Result<u8> {
+    tables
+      .iter()
+      .find(|(table, _)| *table == name)
+      .map(|(_, id)| *id)
+      .ok_or_else(|| anyhow!("table `{name}` is not recorded in undo log"))
+  }
+
+  /// Record that `key` in `table` had `value` before this block, for changes
+  /// that are buffered in memory rather than written to the table directly.
+  pub(crate) fn record(&self, table: &str, key: &[u8], value: Option<&[u8]>) -> Result {
+    if self.enabled {
+      self.records.borrow_mut().push(Record::Table {
+        table: Self::id(TABLES, table)?,
+        key: key.to_vec(),
+        value: value.map(<[u8]>::to_vec),
+      });
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn insert<'k, 'v, K: Key + 'static, V: redb::Value + 'static>(
+    &self,
+    table: &mut Table<K, V>,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result {
+    let id = self
+      .enabled
+      .then(|| Self::id(TABLES, table.name()))
+      .transpose()?;
+
+    let previous = table.insert(key.borrow(), value)?;
+
+    if let Some(table) = id {
+      self.records.borrow_mut().push(Record::Table {
+        table,
+        key: K::as_bytes(key.borrow()).as_ref().to_vec(),
+        value: previous.map(|previous| V::as_bytes(&previous.value()).as_ref().to_vec()),
+      });
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn remove<'t, 'k, K: Key + 'static, V: redb::Value + 'static>(
+    &self,
+    table: &'t mut Table<K, V>,
+    key: impl Borrow<K::SelfType<'k>>,
+  ) -> Result<Option<AccessGuard<'t, V>>> {
+    let id = self
+      .enabled
+      .then(|| Self::id(TABLES, table.name()))
+      .transpose()?;
+
+    let previous = table.remove(key.borrow())?;
+
+    if let (Some(table), Some(previous)) = (id, &previous) {
+      self.records.borrow_mut().push(Record::Table {
+        table,
+        key: K::as_bytes(key.borrow()).as_ref().to_vec(),
+        value: Some(V::as_bytes(&previous.value()).as_ref().to_vec()),
+      });
+    }
+
+    Ok(previous)
+  }
+
+  pub(crate) fn pop_first<K: Key + 'static, V: redb::Value + 'static>(
+    &self,
+    table: &mut Table<K, V>,
+  ) -> Result {
+    let id = self
+      .enabled
+      .then(|| Self::id(TABLES, table.name()))
+      .transpose()?;
+
+    let first = table.pop_first()?;
+
+    if let (Some(table), Some((key, value))) = (id, first) {
+      self.records.borrow_mut().push(Record::Table {
+        table,
+        key: K::as_bytes(&key.value()).as_ref().to_vec(),
+        value: Some(V::as_bytes(&value.value()).as_ref().to_vec()),
+      });
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn multimap_insert<'k, 'v, K: Key + 'static, V: Key + 'static>(
+    &self,
+    table: &mut MultimapTable<K, V>,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result {
+    let id = self
+      .enabled
+      .then(|| Self::id(MULTIMAP_TABLES, table.name()))
+      .transpose()?;
+
+    let present = table.insert(key.borrow(), value.borrow())?;
+
+    if let Some(table) = id {
+      self.records.borrow_mut().push(Record::Multimap {
+        table,
+        key: K::as_bytes(key.borrow()).as_ref().to_vec(),
+        value: V::as_bytes(value.borrow()).as_ref().to_vec(),
+        present,
+      });
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn multimap_remove<'k, 'v, K: Key + 'static, V: Key + 'static>(
+    &self,
+    table: &mut MultimapTable<K, V>,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result {
+    let id = self
+      .enabled
+      .then(|| Self::id(MULTIMAP_TABLES, table.name()))
+      .transpose()?;
+
+    let present = table.remove(key.borrow(), value.borrow())?;
+
+    if let Some(table) = id {
+      self.records.borrow_mut().push(Record::Multimap {
+        table,
+        key: K::as_bytes(key.borrow()).as_ref().to_vec(),
+        value: V::as_bytes(value.borrow()).as_ref().to_vec(),
+        present,
+      });
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn multimap_remove_all<'k, K: Key + 'static, V: Key + 'static>(
+    &self,
+    table: &mut MultimapTable<K, V>,
+    key: impl Borrow<K::SelfType<'k>>,
+  ) -> Result {
+    let id = self
+      .enabled
+      .then(|| Self::id(MULTIMAP_TABLES, table.name()))
+      .transpose()?;
+
+    let values = table.remove_all(key.borrow())?;
+
+    if let Some(table) = id {
+      let key = K::as_bytes(key.borrow()).as_ref().to_vec();
+
+      for value in values {
+        self.records.borrow_mut().push(Record::Multimap {
+          table,
+          key: key.clone(),
+          value: V::as_bytes(&value?.value()).as_ref().to_vec(),
+          present: true,
+        });
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Persist the records for the block at `height`, and prune records for
+  /// blocks that are more than `depth` blocks deep.
+  pub(crate) fn save(self, wtx: &WriteTransaction, height: u32, depth: u32) -> Result {
+    let mut height_to_undo_log = wtx.open_table(HEIGHT_TO_UNDO_LOG)?;
+
+    if self.enabled {
+      height_to_undo_log.insert(height, Self::encode(&self.records.into_inner()).as_slice())?;
+    }
+
+    let cutoff = (height + 1).saturating_sub(depth);
+
+    let pruned = height_to_undo_log
+      .range(..cutoff)?
+      .map(|result| result.map(|(height, _)| height.value()))
+      .collect::<Result<Vec<u32>, StorageError>>()?;
+
+    for height in pruned {
+      height_to_undo_log.remove(height)?;
+    }
+
+    Ok(())
+  }
+
+  /// The lowest height from which every block up to and including `tip` can
+  /// be reverted using the undo log.
+  pub(crate) fn start(
+    height_to_undo_log: &impl ReadableTable<u32, &'static [u8]>,
+    tip: u32,
+  ) -> Result<Option<u32>> {
+    let mut start = None;
+
+    for height in (0..=tip).rev() {
+      if height_to_undo_log.get(height)?.is_none() {
+        break;
+      }
+
+      start = Some(height);
+    }
+
+    Ok(start)
+  }
+
+  /// Revert all blocks above `height` by applying their undo records in
+  /// reverse order.
+  pub(crate) fn revert(wtx: &WriteTransaction, height: u32, tip: u32) -> Result {
+    let mut height_to_undo_log = wtx.open_table(HEIGHT_TO_UNDO_LOG)?;
+
+    for block in ((height + 1)..=tip).rev() {
+      let records = Self::decode(
+        height_to_undo_log
+          .remove(block)?
+          .ok_or_else(|| anyhow!("undo log for block {block} not available"))?
+          .value(),
+      )?;
+
+      for record in records.iter().rev() {
+        restore(wtx, record)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn restore_table<K: Key + 'static, V: redb::Value + 'static>(
+    wtx: &WriteTransaction,
+    definition: TableDefinition<K, V>,
+    record: &Record,
+  ) -> Result {
+    let Record::Table { key, value, .. } = record else {
+      unreachable!()
+    };
+
+    let mut table = wtx.open_table(definition)?;
+
+    match value {
+      Some(value) => table.insert(K::from_bytes(key), V::from_bytes(value))?,
+      None => table.remove(K::from_bytes(key))?,
+    };
+
+    Ok(())
+  }
+
+  fn restore_multimap<K: Key + 'static, V: Key + 'static>(
+    wtx: &WriteTransaction,
+    definition: MultimapTableDefinition<K, V>,
+    record: &Record,
+  ) -> Result {
+    let Record::Multimap {
+      key,
+      value,
+      present,
+      ..
+    } = record
+    else {
+      unreachable!()
+    };
+
+    let mut table = wtx.open_multimap_table(definition)?;
+
+    if *present {
+      table.insert(K::from_bytes(key), V::from_bytes(value))?;
+    } else {
+      table.remove(K::from_bytes(key), V::from_bytes(value))?;
+    }
+
+    Ok(())
+  }
+
+  fn encode(records: &[Record]) -> Vec<u8> {
+    fn push(buffer: &mut Vec<u8>, bytes: &[u8]) {
+      buffer.extend_from_slice(&u32::try_from(bytes.len()).unwrap().to_le_bytes());
+      buffer.extend_from_slice(bytes);
+    }
+
+    let mut buffer = Vec::new();
+
+    for record in records {
+      match record {
+        Record::Table { table, key, value } => {
+          buffer.push(if value.is_some() { 1 } else { 0 });
+          buffer.push(*table);
+          push(&mut buffer, key);
+          if let Some(value) = value {
+            push(&mut buffer, value);
+          }
+        }
+        Record::Multimap {
+          table,
+          key,
+          value,
+          present,
+        } => {
+          buffer.push(if *present { 3 } else { 2 });
+          buffer.push(*table);
+          push(&mut buffer, key);
+          push(&mut buffer, value);
+        }
+      }
+    }
+
+    buffer
+  }
+
+  fn decode(mut buffer: &[u8]) -> Result<Vec<Record>> {
+    fn take<'a>(buffer: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+      ensure!(buffer.len() >= n, "truncated undo log");
+      let (head, tail) = buffer.split_at(n);
+      *buffer = tail;
+      Ok(head)
+    }
+
+    fn take_bytes(buffer: &mut &[u8]) -> Result<Vec<u8>> {
+      let len = u32::from_le_bytes(take(buffer, 4)?.try_into().unwrap());
+      Ok(take(buffer, len.try_into().unwrap())?.to_vec())
+    }
+
+    let mut records = Vec::new();
+
+    while !buffer.is_empty() {
+      let header = take(&mut buffer, 2)?;
+      let (tag, table) = (header[0], header[1]);
+
+      records.push(match tag {
+        0 | 1 => {
+          ensure!(
+            TABLES.iter().any(|(_, id)| *id == table),
+            "invalid undo log table"
+          );
+          Record::Table {
+            table,
+            key: take_bytes(&mut buffer)?,
+            value: if tag == 1 {
+              Some(take_bytes(&mut buffer)?)
+            } else {
+              None
+            },
+          }
+        }
+        2 | 3 => {
+          ensure!(
+            MULTIMAP_TABLES.iter().any(|(_, id)| *id == table),
+            "invalid undo log table"
+          );
+          Record::Multimap {
+            table,
+            key: take_bytes(&mut buffer)?,
+            value: take_bytes(&mut buffer)?,
+            present: tag == 3,
+          }
+        }
+        tag => bail!("invalid undo log record tag {tag}"),
+      });
+    }
+
+    Ok(records)
+  }
+}
+
+#[cfg(test)]
+fn bytes<T: redb::Value>(guard: &AccessGuard<T>) -> Vec<u8> {
+  T::as_bytes(&guard.value()).as_ref().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  const RUNE: u128 = 99246114928149462;
+
+  fn state(context: &Context) -> BTreeMap<&'static str, Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut snapshot = snapshot(&context.index);
+
+    // these statistics are updated on commit, not per block
+    snapshot
+      .get_mut(STATISTIC_TO_COUNT.name())
+      .unwrap()
+      .retain(|(key, _)| {
+        ![
+          Statistic::Commits,
+          Statistic::OutputsTraversed,
+          Statistic::SatRanges,
+        ]
+        .into_iter()
+        .any(|statistic| statistic.key().to_le_bytes() == key.as_slice())
+      });
+
+    snapshot
+  }
+
+  #[test]
+  fn rollback_restores_inscription_state() {
+    for args in [
+      vec![],
+      vec!["--index-sats"],
+      vec!["--index-sats", "--index-spent-sats"],
+      vec!["--index-addresses", "--index-transactions"],
+      vec!["--index-search"],
+    ] {
+      let context = Context::builder().args(args).build();
+
+      context.mine_blocks(2);
+
+      let before = state(&context);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent = InscriptionId { txid, index: 0 };
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[
+          (3, 1, 0, Default::default()),
+          (
+            2,
+            0,
+            0,
+            Inscription {
+              content_type: Some("text/plain".into()),
+              body: Some("child".into()),
+              parents: vec![parent.value()],
+              ..default()
+            }
+            .to_witness(),
+          ),
+        ],
+        outputs: 2,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      assert!(context.index.block_count().unwrap() > 3);
+      assert_ne!(state(&context), before);
+
+      assert_eq!(context.index.rollback(2).unwrap(), 2);
+
+      assert_eq!(context.index.block_count().unwrap(), 3);
+      pretty_assert_eq!(state(&context), before);
+
+      context.mine_blocks(1);
+
+      assert_eq!(context.index.block_count().unwrap(), 6);
+      assert!(context.index.inscription_exists(parent).unwrap());
+    }
+  }
+
+  #[test]
+  fn rollback_restores_rune_state() {
+    let context = Context::builder()
+      .args(["--index-runes", "--index-addresses"])
+      .build();
+
+    let (txid, id) = context.etch(
+      Runestone {
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          terms: Some(Terms {
+            amount: Some(1000),
+            cap: Some(10),
+            ..default()
+          }),
+          premine: Some(u128::MAX / 2),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    let height = context.index.block_count().unwrap() - 1;
+
+    let before = state(&context);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(usize::try_from(id.block).unwrap(), 1, 0, Witness::new())],
+      op_return: Some(
+        Runestone {
+          edicts: vec![Edict {
+            id,
+            amount: 0,
+            output: 0,
+          }],
+          mint: Some(id),
+          ..default()
+        }
+        .encipher(),
+      ),
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_ne!(state(&context), before);
+
+    context.index.rollback(height).unwrap();
+
+    pretty_assert_eq!(state(&context), before);
+
+    assert_eq!(
+      context.index.get_rune_by_id(id).unwrap().unwrap(),
+      Rune(RUNE)
+    );
+
+    context.mine_blocks(1);
+
+    assert!(context.index.get_etching(txid).unwrap().is_some());
+  }
+
+  #[test]
+  fn rollback_after_migration_restores_state() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(2);
+
+    let mut before = state(&context);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (3, 1, 0, Default::default()),
+        (
+          2,
+          0,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("child".into()),
+            parents: vec![InscriptionId { txid, index: 0 }.value()],
+            ..default()
+          }
+          .to_witness(),
+        ),
+      ],
+      outputs: 2,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    // downgrade to schema 28, dropping the filter tables and their undo
+    // records, as if the undo log had been written before schema 29
+    {
+      let filter_tables = [
+        CHARM_TO_SEQUENCE_NUMBER.name(),
+        CONTENT_TYPE_TO_SEQUENCE_NUMBER.name(),
+        METAPROTOCOL_TO_SEQUENCE_NUMBER.name(),
+      ]
+      .map(|table| UndoLog::id(MULTIMAP_TABLES, table).unwrap());
+
+      let wtx = context.index.database.begin_write().unwrap();
+
+      wtx.delete_multimap_table(CHARM_TO_SEQUENCE_NUMBER).unwrap();
+      wtx
+        .delete_multimap_table(CONTENT_TYPE_TO_SEQUENCE_NUMBER)
+        .unwrap();
+      wtx
+        .delete_multimap_table(METAPROTOCOL_TO_SEQUENCE_NUMBER)
+        .unwrap();
+
+      let mut height_to_undo_log = wtx.open_table(HEIGHT_TO_UNDO_LOG).unwrap();
+
+      for height in [3, 4] {
+        let records = UndoLog::decode(height_to_undo_log.get(height).unwrap().unwrap().value())
+          .unwrap()
+          .into_iter()
+          .filter(|record| {
+            !matches!(record, Record::Multimap { table, .. } if filter_tables.contains(table))
+          })
+          .collect::<Vec<Record>>();
+
+        height_to_undo_log
+          .insert(height, UndoLog::encode(&records).as_slice())
+          .unwrap();
+      }
+
+      // the child is recorded under the id `SEQUENCE_NUMBER_TO_CHILDREN` had
+      // in schema 28
+      assert!(
+        UndoLog::decode(height_to_undo_log.get(4).unwrap().unwrap().value())
+          .unwrap()
+          .contains(&Record::Multimap {
+            table: 3,
+            key: 0u32.to_le_bytes().to_vec(),
+            value: 1u32.to_le_bytes().to_vec(),
+            present: false,
+          })
+      );
+
+      drop(height_to_undo_log);
+
+      let mut statistics = wtx.open_table(STATISTIC_TO_COUNT).unwrap();
+      statistics
+        .remove(&Statistic::IndexInscriptionFilters.key())
+        .unwrap();
+      statistics.insert(&Statistic::Schema.key(), &28).unwrap();
+      drop(statistics);
+
+      wtx.commit().unwrap();
+    }
+
+    Migration::run(
+      MIGRATIONS,
+      &context.index.database,
+      context.tempdir.path(),
+      STATISTIC_TO_COUNT,
+      Statistic::Schema.key(),
+      28,
+      SCHEMA_VERSION,
+    )
+    .unwrap();
+
+    assert_eq!(context.index.rollback(2).unwrap(), 2);
+
+    let mut after = state(&context);
+
+    // the migration adds the filter tables empty and marks them as not indexed
+    for snapshot in [&mut before, &mut after] {
+      snapshot
+        .get_mut(STATISTIC_TO_COUNT.name())
+        .unwrap()
+        .retain(|(key, _)| {
+          Statistic::IndexInscriptionFilters.key().to_le_bytes() != key.as_slice()
+        });
+    }
+
+    pretty_assert_eq!(after, before);
+  }
+
+  #[test]
+  fn rollback_requires_undo_log() {
+    let context = Context::builder().arg("--undo-log-depth=2").build();
+
+    context.mine_blocks(5);
+
+    assert_eq!(
+      context.index.rollback(1).unwrap_err().to_string(),
+      "undo log for block 3 not available",
+    );
+
+    assert_eq!(context.index.block_count().unwrap(), 6);
+
+    assert_eq!(context.index.rollback(3).unwrap(), 2);
+
+    assert_eq!(context.index.block_count().unwrap(), 4);
+  }
+
+  #[test]
+  fn rollback_height_must_be_below_tip() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(2);
+
+    assert_eq!(
+      context.index.rollback(2).unwrap_err().to_string(),
+      "cannot roll back to height 2: index is at height 2",
+    );
+  }
+
+  #[test]
+  fn undo_log_is_pruned() {
+    let context = Context::builder().arg("--undo-log-depth=3").build();
+
+    context.mine_blocks(10);
+
+    assert_eq!(context.index.undo_log_start(10).unwrap(), Some(8));
+  }
+
+  #[test]
+  fn table_ids_are_unique_and_stable() {
+    for tables in [TABLES, MULTIMAP_TABLES] {
+      assert_eq!(
+        tables
+          .iter()
+          .map(|(_, id)| id)
+          .collect::<BTreeSet<&u8>>()
+          .len(),
+        tables.len(),
+      );
+    }
+
+    // ids of tables recorded before schema 28 must match existing undo logs
+    for (table, id) in [
+      (SATPOINT_TO_SEQUENCE_NUMBER.name(), 0),
+      (SAT_TO_SEQUENCE_NUMBER.name(), 1),
+      (SCRIPT_PUBKEY_TO_OUTPOINT.name(), 2),
+      (SEQUENCE_NUMBER_TO_CHILDREN.name(), 3),
+    ] {
+      assert_eq!(UndoLog::id(MULTIMAP_TABLES, table).unwrap(), id);
+    }
+
+    assert_eq!(
+      UndoLog::id(TABLES, TRANSACTION_ID_TO_TRANSACTION.name()).unwrap(),
+      17
+    );
+  }
+
+  #[test]
+  fn records_round_trip() {
+    let records = vec![
+      Record::Table {
+        table: 1,
+        key: vec![1, 2, 3],
+        value: None,
+      },
+      Record::Table {
+        table: 2,
+        key: Vec::new(),
+        value: Some(vec![4, 5]),
+      },
+      Record::Multimap {
+        table: 3,
+        key: vec![6],
+        value: vec![7, 8, 9],
+        present: true,
+      },
+      Record::Multimap {
+        table: 0,
+        key: vec![10],
+        value: Vec::new(),
+        present: false,
+      },
+    ];
+
+    assert_eq!(
+      UndoLog::decode(&UndoLog::encode(&records)).unwrap(),
+      records
+    );
+  }
+
+  #[test]
+  fn truncated_records_are_rejected() {
+    let encoded = UndoLog::encode(&[Record::Table {
+      table: 0,
+      key: vec![1, 2, 3],
+      value: Some(vec![4]),
+    }]);
+
+    assert_eq!(
+      UndoLog::decode(&encoded[..encoded.len() - 1])
+        .unwrap_err()
+        .to_string(),
+      "truncated undo log",
+    );
+  }
+}
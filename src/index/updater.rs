@@ -38,6 +38,7 @@ pub(crate) struct Updater<'index> {
   pub(super) outputs_traversed: u64,
   pub(super) range_cache: HashMap<OutPointValue, Vec<u8>>,
   pub(super) sat_ranges_since_flush: u64,
+  pub(super) uncommitted_events: bool,
 }
 
 impl<'index> Updater<'index> {
@@ -71,19 +72,28 @@ impl<'index> Updater<'index> {
       Some(progress_bar)
     };
 
-    let rx = Self::fetch_blocks_from(self.index, self.height, self.index.index_sats)?;
+    let rx = Self::fetch_blocks_from(
+      self.index,
+      self.height,
+      self.index.index_sats || self.index.index_addresses,
+    )?;
 
-    let (mut outpoint_sender, mut value_receiver) = Self::spawn_fetcher(&self.index.settings)?;
+    let (mut outpoint_sender, mut value_receiver) = Self::spawn_fetcher(self.index)?;
+
+    let undo_log_depth = self.index.settings.undo_log_depth();
 
     let mut uncommitted = 0;
     let mut value_cache = HashMap::new();
     while let Ok(block) = rx.recv() {
+      let record_undo_log = undo_log_depth > 0 && self.height + undo_log_depth >= starting_height;
+
       self.index_block(
         &mut outpoint_sender,
         &mut value_receiver,
         &mut wtx,
         block,
         &mut value_cache,
+        record_undo_log,
       )?;
 
       if let Some(progress_bar) = &mut progress_bar {
@@ -153,7 +163,7 @@ impl<'index> Updater<'index> {
   fn fetch_blocks_from(
     index: &Index,
     mut height: u32,
-    index_sats: bool,
+    full_blocks: bool,
   ) -> Result<mpsc::Receiver<BlockData>> {
     let (tx, rx) = mpsc::sync_channel(32);
 
@@ -163,6 +173,8 @@ impl<'index> Updater<'index> {
 
     let first_inscription_height = index.first_inscription_height;
 
+    let metrics = index.metrics.clone();
+
     thread::spawn(move || loop {
       if let Some(height_limit) = height_limit {
         if height >= height_limit {
@@ -170,7 +182,13 @@ impl<'index> Updater<'index> {
         }
       }
 
-      match Self::get_block_with_retries(&client, height, index_sats, first_inscription_height) {
+      match Self::get_block_with_retries(
+        &client,
+        &metrics,
+        height,
+        full_blocks,
+        first_inscription_height,
+      ) {
         Ok(Some(block)) => {
           if let Err(err) = tx.send(block.into()) {
             log::info!("Block receiver disconnected: {err}");
@@ -191,8 +209,9 @@ impl<'index> Updater<'index> {
 
   fn get_block_with_retries(
     client: &Client,
+    metrics: &Metrics,
     height: u32,
-    index_sats: bool,
+    full_blocks: bool,
     first_inscription_height: u32,
   ) -> Result<Option<Block>> {
     let mut errors = 0;
@@ -203,7 +222,7 @@ impl<'index> Updater<'index> {
         .and_then(|option| {
           option
             .map(|hash| {
-              if index_sats || height >= first_inscription_height {
+              if full_blocks || height >= first_inscription_height {
                 Ok(client.get_block(&hash)?)
               } else {
                 Ok(Block {
@@ -215,6 +234,8 @@ impl<'index> Updater<'index> {
             .transpose()
         }) {
         Err(err) => {
+          Metrics::increment(&metrics.block_fetch_errors, 1);
+
           if cfg!(test) {
             return Err(err);
           }
@@ -235,8 +256,8 @@ impl<'index> Updater<'index> {
     }
   }
 
-  fn spawn_fetcher(settings: &Settings) -> Result<(Sender<OutPoint>, Receiver<u64>)> {
-    let fetcher = Fetcher::new(settings)?;
+  fn spawn_fetcher(index: &Index) -> Result<(Sender<OutPoint>, Receiver<u64>)> {
+    let fetcher = Fetcher::new(&index.settings, index.metrics.clone())?;
 
     // Not sure if any block has more than 20k inputs, but none so far after first inscription block
     const CHANNEL_BUFFER_SIZE: usize = 20_000;
@@ -311,9 +332,14 @@ impl<'index> Updater<'index> {
     wtx: &mut WriteTransaction,
     block: BlockData,
     value_cache: &mut HashMap<OutPoint, u64>,
+    record_undo_log: bool,
   ) -> Result<()> {
     Reorg::detect_reorg(&block, self.height, self.index)?;
 
+    let undo_log = UndoLog::new(record_undo_log);
+
+    self.uncommitted_events |= self.index.event_sender.is_some();
+
     let start = Instant::now();
     let mut sat_ranges_written = 0;
     let mut outputs_in_block = 0;
@@ -370,7 +396,10 @@ impl<'index> Updater<'index> {
       }
     }
 
+    let mut charm_to_sequence_number = wtx.open_multimap_table(CHARM_TO_SEQUENCE_NUMBER)?;
     let mut content_type_to_count = wtx.open_table(CONTENT_TYPE_TO_COUNT)?;
+    let mut content_type_to_sequence_number =
+      wtx.open_multimap_table(CONTENT_TYPE_TO_SEQUENCE_NUMBER)?;
     let mut height_to_block_header = wtx.open_table(HEIGHT_TO_BLOCK_HEADER)?;
     let mut height_to_last_sequence_number = wtx.open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
     let mut home_inscriptions = wtx.open_table(HOME_INSCRIPTIONS)?;
@@ -379,8 +408,13 @@ impl<'index> Updater<'index> {
     let mut inscription_number_to_sequence_number =
       wtx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
     let mut sat_to_sequence_number = wtx.open_multimap_table(SAT_TO_SEQUENCE_NUMBER)?;
+    let mut metaprotocol_to_sequence_number =
+      wtx.open_multimap_table(METAPROTOCOL_TO_SEQUENCE_NUMBER)?;
     let mut satpoint_to_sequence_number = wtx.open_multimap_table(SATPOINT_TO_SEQUENCE_NUMBER)?;
+    let mut search_term_to_sequence_number =
+      wtx.open_multimap_table(SEARCH_TERM_TO_SEQUENCE_NUMBER)?;
     let mut sequence_number_to_children = wtx.open_multimap_table(SEQUENCE_NUMBER_TO_CHILDREN)?;
+    let mut sequence_number_to_content_type = wtx.open_table(SEQUENCE_NUMBER_TO_CONTENT_TYPE)?;
     let mut sequence_number_to_inscription_entry =
       wtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
     let mut sequence_number_to_satpoint = wtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
@@ -416,10 +450,15 @@ impl<'index> Updater<'index> {
 
     let home_inscription_count = home_inscriptions.len()?;
 
+    let block_hash = block.header.block_hash();
+
     let mut inscription_updater = InscriptionUpdater {
       blessed_inscription_count,
+      block_hash,
       chain: self.index.settings.chain(),
+      charm_to_sequence_number: &mut charm_to_sequence_number,
       content_type_to_count: &mut content_type_to_count,
+      content_type_to_sequence_number: &mut content_type_to_sequence_number,
       cursed_inscription_count,
       event_sender: self.index.event_sender.as_ref(),
       flotsam: Vec::new(),
@@ -427,21 +466,26 @@ impl<'index> Updater<'index> {
       home_inscription_count,
       home_inscriptions: &mut home_inscriptions,
       id_to_sequence_number: &mut inscription_id_to_sequence_number,
+      index_search: self.index.index_search,
       index_transactions: self.index.index_transactions,
       inscription_number_to_sequence_number: &mut inscription_number_to_sequence_number,
       lost_sats,
+      metaprotocol_to_sequence_number: &mut metaprotocol_to_sequence_number,
       next_sequence_number,
       outpoint_to_value: &mut outpoint_to_value,
       reward: Height(self.height).subsidy(),
       sat_to_sequence_number: &mut sat_to_sequence_number,
       satpoint_to_sequence_number: &mut satpoint_to_sequence_number,
+      search_term_to_sequence_number: &mut search_term_to_sequence_number,
       sequence_number_to_children: &mut sequence_number_to_children,
+      sequence_number_to_content_type: &mut sequence_number_to_content_type,
       sequence_number_to_entry: &mut sequence_number_to_inscription_entry,
       sequence_number_to_satpoint: &mut sequence_number_to_satpoint,
       timestamp: block.header.time,
       transaction_buffer: Vec::new(),
       transaction_id_to_transaction: &mut transaction_id_to_transaction,
       unbound_inscriptions,
+      undo_log: &undo_log,
       value_cache,
       value_receiver,
     };
@@ -473,13 +517,16 @@ impl<'index> Updater<'index> {
             self.range_cache.remove(&key)
           } {
             Some(sat_ranges) => {
+              if !self.index.index_spent_sats {
+                undo_log.record(OUTPOINT_TO_SAT_RANGES.name(), &key, Some(&sat_ranges))?;
+              }
               self.outputs_cached += 1;
               sat_ranges
             }
             None => if self.index.index_spent_sats {
               outpoint_to_sat_ranges.get(&key)?
             } else {
-              outpoint_to_sat_ranges.remove(&key)?
+              undo_log.remove(&mut outpoint_to_sat_ranges, &key)?
             }
             .ok_or_else(|| anyhow!("Could not find outpoint {} in index", input.previous_output))?
             .value()
@@ -500,6 +547,7 @@ impl<'index> Updater<'index> {
           &mut outputs_in_block,
           &mut inscription_updater,
           index_inscriptions,
+          &undo_log,
         )?;
 
         coinbase_inputs.extend(input_sat_ranges);
@@ -515,18 +563,20 @@ impl<'index> Updater<'index> {
           &mut outputs_in_block,
           &mut inscription_updater,
           index_inscriptions,
+          &undo_log,
         )?;
       }
 
       if !coinbase_inputs.is_empty() {
-        let mut lost_sat_ranges = outpoint_to_sat_ranges
-          .remove(&OutPoint::null().store())?
+        let mut lost_sat_ranges = undo_log
+          .remove(&mut outpoint_to_sat_ranges, &OutPoint::null().store())?
           .map(|ranges| ranges.value().to_vec())
           .unwrap_or_default();
 
         for (start, end) in coinbase_inputs {
           if !Sat(start).common() {
-            sat_to_satpoint.insert(
+            undo_log.insert(
+              &mut sat_to_satpoint,
               &start,
               &SatPoint {
                 outpoint: OutPoint::null(),
@@ -541,7 +591,11 @@ impl<'index> Updater<'index> {
           lost_sats += end - start;
         }
 
-        outpoint_to_sat_ranges.insert(&OutPoint::null().store(), lost_sat_ranges.as_slice())?;
+        undo_log.insert(
+          &mut outpoint_to_sat_ranges,
+          &OutPoint::null().store(),
+          lost_sat_ranges.as_slice(),
+        )?;
       }
     } else if index_inscriptions {
       for (tx, txid) in block.txdata.iter().skip(1).chain(block.txdata.first()) {
@@ -550,11 +604,15 @@ impl<'index> Updater<'index> {
     }
 
     if index_inscriptions {
-      height_to_last_sequence_number
-        .insert(&self.height, inscription_updater.next_sequence_number)?;
+      undo_log.insert(
+        &mut height_to_last_sequence_number,
+        &self.height,
+        inscription_updater.next_sequence_number,
+      )?;
     }
 
-    statistic_to_count.insert(
+    undo_log.insert(
+      &mut statistic_to_count,
       &Statistic::LostSats.key(),
       &if self.index.index_sats {
         lost_sats
@@ -563,23 +621,27 @@ impl<'index> Updater<'index> {
       },
     )?;
 
-    statistic_to_count.insert(
+    undo_log.insert(
+      &mut statistic_to_count,
       &Statistic::CursedInscriptions.key(),
       &inscription_updater.cursed_inscription_count,
     )?;
 
-    statistic_to_count.insert(
+    undo_log.insert(
+      &mut statistic_to_count,
       &Statistic::BlessedInscriptions.key(),
       &inscription_updater.blessed_inscription_count,
     )?;
 
-    statistic_to_count.insert(
+    undo_log.insert(
+      &mut statistic_to_count,
       &Statistic::UnboundInscriptions.key(),
       &inscription_updater.unbound_inscriptions,
     )?;
 
     if self.index.index_runes && self.height >= self.index.settings.first_rune_height() {
       let mut outpoint_to_rune_balances = wtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+      let mut rune_id_to_outpoint = wtx.open_multimap_table(RUNE_ID_TO_OUTPOINT)?;
       let mut rune_id_to_rune_entry = wtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
       let mut rune_to_rune_id = wtx.open_table(RUNE_TO_RUNE_ID)?;
       let mut sequence_number_to_rune_id = wtx.open_table(SEQUENCE_NUMBER_TO_RUNE_ID)?;
@@ -591,10 +653,11 @@ impl<'index> Updater<'index> {
         .unwrap_or(0);
 
       let mut rune_updater = RuneUpdater {
-        event_sender: self.index.event_sender.as_ref(),
+        block_hash,
         block_time: block.header.time,
         burned: HashMap::new(),
         client: &self.index.client,
+        event_sender: self.index.event_sender.as_ref(),
         height: self.height,
         id_to_entry: &mut rune_id_to_rune_entry,
         inscription_id_to_sequence_number: &mut inscription_id_to_sequence_number,
@@ -603,11 +666,13 @@ impl<'index> Updater<'index> {
           Height(self.height),
         ),
         outpoint_to_balances: &mut outpoint_to_rune_balances,
+        rune_id_to_outpoint: &mut rune_id_to_outpoint,
         rune_to_id: &mut rune_to_rune_id,
         runes,
         sequence_number_to_rune_id: &mut sequence_number_to_rune_id,
         statistic_to_count: &mut statistic_to_count,
         transaction_id_to_rune: &mut transaction_id_to_rune,
+        undo_log: &undo_log,
       };
 
       for (i, (tx, txid)) in block.txdata.iter().enumerate() {
@@ -617,11 +682,47 @@ impl<'index> Updater<'index> {
       rune_updater.update()?;
     }
 
-    height_to_block_header.insert(&self.height, &block.header.store())?;
+    if self.index.index_addresses {
+      let mut outpoint_to_txout = wtx.open_table(OUTPOINT_TO_TXOUT)?;
+      let mut script_pubkey_to_outpoint = wtx.open_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)?;
+
+      for (tx, txid) in &block.txdata {
+        Self::index_transaction_addresses(
+          tx,
+          *txid,
+          &mut outpoint_to_txout,
+          &mut script_pubkey_to_outpoint,
+          &undo_log,
+        )?;
+      }
+    }
+
+    undo_log.insert(
+      &mut height_to_block_header,
+      &self.height,
+      &block.header.store(),
+    )?;
+
+    undo_log.save(wtx, self.height, self.index.settings.undo_log_depth())?;
+
+    if let Some(sender) = &self.index.event_sender {
+      sender.blocking_send(Event::BlockIndexed {
+        block_hash,
+        block_height: self.height,
+      })?;
+    }
 
     self.height += 1;
     self.outputs_traversed += outputs_in_block;
 
+    let metrics = &self.index.metrics;
+    Metrics::increment(&metrics.blocks_indexed, 1);
+    Metrics::increment(&metrics.outputs_traversed, outputs_in_block);
+    Metrics::increment(
+      &metrics.indexing_time,
+      start.elapsed().as_micros().try_into().unwrap_or(u64::MAX),
+    );
+
     log::info!(
       "Wrote {sat_ranges_written} sat ranges from {outputs_in_block} outputs in {} ms",
       (Instant::now() - start).as_millis(),
@@ -640,6 +741,7 @@ impl<'index> Updater<'index> {
     outputs_traversed: &mut u64,
     inscription_updater: &mut InscriptionUpdater,
     index_inscriptions: bool,
+    undo_log: &UndoLog,
   ) -> Result {
     if index_inscriptions {
       inscription_updater.index_inscriptions(tx, txid, Some(input_sat_ranges))?;
@@ -659,7 +761,8 @@ impl<'index> Updater<'index> {
           .ok_or_else(|| anyhow!("insufficient inputs for transaction outputs"))?;
 
         if !Sat(range.0).common() {
-          sat_to_satpoint.insert(
+          undo_log.insert(
+            sat_to_satpoint,
             &range.0,
             &SatPoint {
               outpoint,
@@ -689,6 +792,8 @@ impl<'index> Updater<'index> {
 
       *outputs_traversed += 1;
 
+      undo_log.record(OUTPOINT_TO_SAT_RANGES.name(), &outpoint.store(), None)?;
+
       self.range_cache.insert(outpoint.store(), sats);
       self.outputs_inserted_since_flush += 1;
     }
@@ -696,6 +801,61 @@ impl<'index> Updater<'index> {
     Ok(())
   }
 
+  fn index_transaction_addresses(
+    tx: &Transaction,
+    txid: Txid,
+    outpoint_to_txout: &mut Table<&OutPointValue, &[u8]>,
+    script_pubkey_to_outpoint: &mut MultimapTable<&[u8], &OutPointValue>,
+    undo_log: &UndoLog,
+  ) -> Result {
+    for input in &tx.input {
+      if input.previous_output.is_null() {
+        continue;
+      }
+
+      let key = input.previous_output.store();
+
+      let Some(txout) = undo_log
+        .remove(outpoint_to_txout, &key)?
+        .map(|txout| consensus::encode::deserialize::<TxOut>(txout.value()))
+        .transpose()?
+      else {
+        continue;
+      };
+
+      undo_log.multimap_remove(
+        script_pubkey_to_outpoint,
+        txout.script_pubkey.as_bytes(),
+        &key,
+      )?;
+    }
+
+    for (vout, txout) in tx.output.iter().enumerate() {
+      if txout.script_pubkey.is_op_return() {
+        continue;
+      }
+
+      let key = OutPoint {
+        txid,
+        vout: vout.try_into().unwrap(),
+      }
+      .store();
+
+      undo_log.insert(
+        outpoint_to_txout,
+        &key,
+        consensus::encode::serialize(txout).as_slice(),
+      )?;
+      undo_log.multimap_insert(
+        script_pubkey_to_outpoint,
+        txout.script_pubkey.as_bytes(),
+        &key,
+      )?;
+    }
+
+    Ok(())
+  }
+
   fn commit(&mut self, wtx: WriteTransaction, value_cache: HashMap<OutPoint, u64>) -> Result {
     log::info!(
       "Committing at block height {}, {} outputs traversed, {} in map, {} cached",
@@ -735,8 +895,29 @@ impl<'index> Updater<'index> {
     Index::increment_statistic(&wtx, Statistic::SatRanges, self.sat_ranges_since_flush)?;
     self.sat_ranges_since_flush = 0;
     Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
+
+    let block_height = self.height - 1;
+
+    let block_hash = Header::load(
+      *wtx
+        .open_table(HEIGHT_TO_BLOCK_HEADER)?
+        .get(block_height)?
+        .ok_or_else(|| anyhow!("missing header for block {block_height}"))?
+        .value(),
+    )
+    .block_hash();
+
     wtx.commit()?;
 
+    self.uncommitted_events = false;
+
+    if let Some(sender) = &self.index.event_sender {
+      sender.blocking_send(Event::BlockCommitted {
+        block_hash,
+        block_height,
+      })?;
+    }
+
     Reorg::update_savepoints(self.index, self.height)?;
 
     Ok(())
@@ -23,12 +23,15 @@ pub(super) struct Flotsam {
 #[derive(Debug, Clone)]
 enum Origin {
   New {
+    content_type: Option<String>,
     cursed: bool,
     fee: u64,
     hidden: bool,
+    metaprotocol: Option<String>,
     parents: Vec<InscriptionId>,
     pointer: Option<u64>,
     reinscription: bool,
+    search_terms: Vec<String>,
     unbound: bool,
     vindicated: bool,
   },
@@ -39,8 +42,11 @@ enum Origin {
 
 pub(super) struct InscriptionUpdater<'a, 'tx> {
   pub(super) blessed_inscription_count: u64,
+  pub(super) block_hash: BlockHash,
   pub(super) chain: Chain,
+  pub(super) charm_to_sequence_number: &'a mut MultimapTable<'tx, u16, u32>,
   pub(super) content_type_to_count: &'a mut Table<'tx, Option<&'static [u8]>, u64>,
+  pub(super) content_type_to_sequence_number: &'a mut MultimapTable<'tx, &'static str, u32>,
   pub(super) cursed_inscription_count: u64,
   pub(super) event_sender: Option<&'a Sender<Event>>,
   pub(super) flotsam: Vec<Flotsam>,
@@ -48,9 +54,11 @@ pub(super) struct InscriptionUpdater<'a, 'tx> {
   pub(super) home_inscription_count: u64,
   pub(super) home_inscriptions: &'a mut Table<'tx, u32, InscriptionIdValue>,
   pub(super) id_to_sequence_number: &'a mut Table<'tx, InscriptionIdValue, u32>,
+  pub(super) index_search: bool,
   pub(super) index_transactions: bool,
   pub(super) inscription_number_to_sequence_number: &'a mut Table<'tx, i32, u32>,
   pub(super) lost_sats: u64,
+  pub(super) metaprotocol_to_sequence_number: &'a mut MultimapTable<'tx, &'static str, u32>,
   pub(super) next_sequence_number: u32,
   pub(super) outpoint_to_value: &'a mut Table<'tx, &'static OutPointValue, u64>,
   pub(super) reward: u64,
@@ -58,11 +66,14 @@ pub(super) struct InscriptionUpdater<'a, 'tx> {
   pub(super) transaction_id_to_transaction: &'a mut Table<'tx, &'static TxidValue, &'static [u8]>,
   pub(super) sat_to_sequence_number: &'a mut MultimapTable<'tx, u64, u32>,
   pub(super) satpoint_to_sequence_number: &'a mut MultimapTable<'tx, &'static SatPointValue, u32>,
+  pub(super) search_term_to_sequence_number: &'a mut MultimapTable<'tx, &'static str, u32>,
   pub(super) sequence_number_to_children: &'a mut MultimapTable<'tx, u32, u32>,
+  pub(super) sequence_number_to_content_type: &'a mut Table<'tx, u32, &'static str>,
   pub(super) sequence_number_to_entry: &'a mut Table<'tx, u32, InscriptionEntryValue>,
   pub(super) sequence_number_to_satpoint: &'a mut Table<'tx, u32, &'static SatPointValue>,
   pub(super) timestamp: u32,
   pub(super) unbound_inscriptions: u64,
+  pub(super) undo_log: &'a UndoLog,
   pub(super) value_cache: &'a mut HashMap<OutPoint, u64>,
   pub(super) value_receiver: &'a mut Receiver<u64>,
 }
@@ -202,20 +213,29 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .map(|entry| entry.value())
           .unwrap_or_default();
 
-        self
-          .content_type_to_count
-          .insert(content_type, content_type_count + 1)?;
+        self.undo_log.insert(
+          self.content_type_to_count,
+          content_type,
+          content_type_count + 1,
+        )?;
 
         floating_inscriptions.push(Flotsam {
           inscription_id,
           offset,
           origin: Origin::New {
+            content_type: inscription.payload.content_type().map(str::to_owned),
             cursed: curse.is_some() && !jubilant,
             fee: 0,
             hidden: inscription.payload.hidden(),
+            metaprotocol: inscription.payload.metaprotocol().map(str::to_owned),
             parents: inscription.payload.parents(),
             pointer: inscription.payload.pointer(),
             reinscription: inscribed_offsets.get(&offset).is_some(),
+            search_terms: if self.index_search {
+              search::terms(&inscription.payload)
+            } else {
+              Vec::new()
+            },
             unbound: current_input_value == 0
               || curse == Some(Curse::UnrecognizedEvenField)
               || inscription.payload.unrecognized_even_field,
@@ -237,9 +257,11 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
       tx.consensus_encode(&mut self.transaction_buffer)
         .expect("in-memory writers don't error");
 
-      self
-        .transaction_id_to_transaction
-        .insert(&txid.store(), self.transaction_buffer.as_slice())?;
+      self.undo_log.insert(
+        self.transaction_id_to_transaction,
+        &txid.store(),
+        self.transaction_buffer.as_slice(),
+      )?;
 
       self.transaction_buffer.clear();
     }
@@ -397,8 +419,8 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
     let (unbound, sequence_number) = match flotsam.origin {
       Origin::Old { old_satpoint } => {
         self
-          .satpoint_to_sequence_number
-          .remove_all(&old_satpoint.store())?;
+          .undo_log
+          .multimap_remove_all(self.satpoint_to_sequence_number, &old_satpoint.store())?;
 
         let sequence_number = self
           .id_to_sequence_number
@@ -407,11 +429,34 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .value();
 
         if let Some(sender) = self.event_sender {
+          let parents = InscriptionEntry::load(
+            self
+              .sequence_number_to_entry
+              .get(sequence_number)?
+              .unwrap()
+              .value(),
+          )
+          .parents;
+
+          let mut parent_inscription_ids = Vec::new();
+          for parent in parents {
+            parent_inscription_ids.push(
+              InscriptionEntry::load(self.sequence_number_to_entry.get(parent)?.unwrap().value())
+                .id,
+            );
+          }
+
           sender.blocking_send(Event::InscriptionTransferred {
+            block_hash: self.block_hash,
             block_height: self.height,
+            content_type: self
+              .sequence_number_to_content_type
+              .get(sequence_number)?
+              .map(|content_type| content_type.value().into()),
             inscription_id,
             new_location: new_satpoint,
             old_location: old_satpoint,
+            parent_inscription_ids,
             sequence_number,
           })?;
         }
@@ -419,12 +464,15 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
         (false, sequence_number)
       }
       Origin::New {
+        content_type,
         cursed,
         fee,
         hidden,
+        metaprotocol,
         parents,
         pointer: _,
         reinscription,
+        search_terms,
         unbound,
         vindicated,
       } => {
@@ -441,9 +489,11 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
         let sequence_number = self.next_sequence_number;
         self.next_sequence_number += 1;
 
-        self
-          .inscription_number_to_sequence_number
-          .insert(inscription_number, sequence_number)?;
+        self.undo_log.insert(
+          self.inscription_number_to_sequence_number,
+          inscription_number,
+          sequence_number,
+        )?;
 
         let sat = if unbound {
           None
@@ -478,7 +528,49 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
         }
 
         if let Some(Sat(n)) = sat {
-          self.sat_to_sequence_number.insert(&n, &sequence_number)?;
+          self
+            .undo_log
+            .multimap_insert(self.sat_to_sequence_number, &n, &sequence_number)?;
+        }
+
+        for charm in Charm::ALL {
+          if charm.is_set(charms) {
+            self.undo_log.multimap_insert(
+              self.charm_to_sequence_number,
+              &(charm as u16),
+              &sequence_number,
+            )?;
+          }
+        }
+
+        if let Some(content_type) = &content_type {
+          self.undo_log.insert(
+            self.sequence_number_to_content_type,
+            sequence_number,
+            content_type.as_str(),
+          )?;
+
+          self.undo_log.multimap_insert(
+            self.content_type_to_sequence_number,
+            search::media_type(content_type).as_str(),
+            &sequence_number,
+          )?;
+        }
+
+        if let Some(metaprotocol) = &metaprotocol {
+          self.undo_log.multimap_insert(
+            self.metaprotocol_to_sequence_number,
+            metaprotocol.as_str(),
+            &sequence_number,
+          )?;
+        }
+
+        for term in &search_terms {
+          self.undo_log.multimap_insert(
+            self.search_term_to_sequence_number,
+            term.as_str(),
+            &sequence_number,
+          )?;
         }
 
         let parent_sequence_numbers = parents
@@ -490,9 +582,11 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
               .unwrap()
               .value();
 
-            self
-              .sequence_number_to_children
-              .insert(parent_sequence_number, sequence_number)?;
+            self.undo_log.multimap_insert(
+              self.sequence_number_to_children,
+              parent_sequence_number,
+              sequence_number,
+            )?;
 
             Ok(parent_sequence_number)
           })
@@ -500,8 +594,10 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
 
         if let Some(sender) = self.event_sender {
           sender.blocking_send(Event::InscriptionCreated {
+            block_hash: self.block_hash,
             block_height: self.height,
             charms,
+            content_type,
             inscription_id,
             location: (!unbound).then_some(new_satpoint),
             parent_inscription_ids: parents,
@@ -509,7 +605,8 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           })?;
         }
 
-        self.sequence_number_to_entry.insert(
+        self.undo_log.insert(
+          self.sequence_number_to_entry,
           sequence_number,
           &InscriptionEntry {
             charms,
@@ -525,17 +622,21 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .store(),
         )?;
 
-        self
-          .id_to_sequence_number
-          .insert(&inscription_id.store(), sequence_number)?;
+        self.undo_log.insert(
+          self.id_to_sequence_number,
+          &inscription_id.store(),
+          sequence_number,
+        )?;
 
         if !hidden {
-          self
-            .home_inscriptions
-            .insert(&sequence_number, inscription_id.store())?;
+          self.undo_log.insert(
+            self.home_inscriptions,
+            &sequence_number,
+            inscription_id.store(),
+          )?;
 
           if self.home_inscription_count == 100 {
-            self.home_inscriptions.pop_first()?;
+            self.undo_log.pop_first(self.home_inscriptions)?;
           } else {
             self.home_inscription_count += 1;
           }
@@ -557,11 +658,11 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
     };
 
     self
-      .satpoint_to_sequence_number
-      .insert(&satpoint, sequence_number)?;
+      .undo_log
+      .multimap_insert(self.satpoint_to_sequence_number, &satpoint, sequence_number)?;
     self
-      .sequence_number_to_satpoint
-      .insert(sequence_number, &satpoint)?;
+      .undo_log
+      .insert(self.sequence_number_to_satpoint, sequence_number, &satpoint)?;
 
     Ok(())
   }
@@ -1,6 +1,7 @@
 use super::*;
 
 pub(super) struct RuneUpdater<'a, 'tx, 'client> {
+  pub(super) block_hash: BlockHash,
   pub(super) block_time: u32,
   pub(super) burned: HashMap<RuneId, Lot>,
   pub(super) client: &'client Client,
@@ -10,11 +11,13 @@ pub(super) struct RuneUpdater<'a, 'tx, 'client> {
   pub(super) inscription_id_to_sequence_number: &'a Table<'tx, InscriptionIdValue, u32>,
   pub(super) minimum: Rune,
   pub(super) outpoint_to_balances: &'a mut Table<'tx, &'static OutPointValue, &'static [u8]>,
+  pub(super) rune_id_to_outpoint: &'a mut MultimapTable<'tx, RuneIdValue, &'static OutPointValue>,
   pub(super) rune_to_id: &'a mut Table<'tx, u128, RuneIdValue>,
   pub(super) runes: u64,
   pub(super) sequence_number_to_rune_id: &'a mut Table<'tx, u32, RuneIdValue>,
   pub(super) statistic_to_count: &'a mut Table<'tx, u64, u64>,
   pub(super) transaction_id_to_rune: &'a mut Table<'tx, &'static TxidValue, u128>,
+  pub(super) undo_log: &'a UndoLog,
 }
 
 impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
@@ -32,6 +35,7 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
 
           if let Some(sender) = self.event_sender {
             sender.blocking_send(Event::RuneMinted {
+              block_hash: self.block_hash,
               block_height: self.height,
               txid,
               rune_id: id,
@@ -198,8 +202,13 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
       for (id, balance) in balances {
         Index::encode_rune_balance(id, balance.n(), &mut buffer);
 
+        self
+          .undo_log
+          .multimap_insert(self.rune_id_to_outpoint, id.store(), &outpoint.store())?;
+
         if let Some(sender) = self.event_sender {
           sender.blocking_send(Event::RuneTransferred {
+            block_hash: self.block_hash,
             outpoint,
             block_height: self.height,
             txid,
@@ -209,9 +218,11 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
         }
       }
 
-      self
-        .outpoint_to_balances
-        .insert(&outpoint.store(), buffer.as_slice())?;
+      self.undo_log.insert(
+        self.outpoint_to_balances,
+        &outpoint.store(),
+        buffer.as_slice(),
+      )?;
     }
 
     // increment entries with burned runes
@@ -220,6 +231,7 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
 
       if let Some(sender) = self.event_sender {
         sender.blocking_send(Event::RuneBurned {
+          block_hash: self.block_hash,
           block_height: self.height,
           txid,
           rune_id: id,
@@ -235,7 +247,9 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
     for (rune_id, burned) in self.burned {
       let mut entry = RuneEntry::load(self.id_to_entry.get(&rune_id.store())?.unwrap().value());
       entry.burned = entry.burned.checked_add(burned.n()).unwrap();
-      self.id_to_entry.insert(&rune_id.store(), entry.store())?;
+      self
+        .undo_log
+        .insert(self.id_to_entry, &rune_id.store(), entry.store())?;
     }
 
     Ok(())
@@ -248,17 +262,21 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
     id: RuneId,
     rune: Rune,
   ) -> Result {
-    self.rune_to_id.insert(rune.store(), id.store())?;
     self
-      .transaction_id_to_rune
-      .insert(&txid.store(), rune.store())?;
+      .undo_log
+      .insert(self.rune_to_id, rune.store(), id.store())?;
+    self
+      .undo_log
+      .insert(self.transaction_id_to_rune, &txid.store(), rune.store())?;
 
     let number = self.runes;
     self.runes += 1;
 
-    self
-      .statistic_to_count
-      .insert(&Statistic::Runes.into(), self.runes)?;
+    self.undo_log.insert(
+      self.statistic_to_count,
+      &Statistic::Runes.into(),
+      self.runes,
+    )?;
 
     let entry = match artifact {
       Artifact::Cenotaph(_) => RuneEntry {
@@ -306,10 +324,13 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
       }
     };
 
-    self.id_to_entry.insert(id.store(), entry.store())?;
+    self
+      .undo_log
+      .insert(self.id_to_entry, id.store(), entry.store())?;
 
     if let Some(sender) = self.event_sender {
       sender.blocking_send(Event::RuneEtched {
+        block_hash: self.block_hash,
         block_height: self.height,
         txid,
         rune_id: id,
@@ -322,9 +343,11 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
       .inscription_id_to_sequence_number
       .get(&inscription_id.store())?
     {
-      self
-        .sequence_number_to_rune_id
-        .insert(sequence_number.value(), id.store())?;
+      self.undo_log.insert(
+        self.sequence_number_to_rune_id,
+        sequence_number.value(),
+        id.store(),
+      )?;
     }
 
     Ok(())
@@ -363,9 +386,11 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
         .map(|entry| entry.value())
         .unwrap_or_default();
 
-      self
-        .statistic_to_count
-        .insert(&Statistic::ReservedRunes.into(), reserved_runes + 1)?;
+      self.undo_log.insert(
+        self.statistic_to_count,
+        &Statistic::ReservedRunes.into(),
+        reserved_runes + 1,
+      )?;
 
       Rune::reserved(self.height.into(), tx_index)
     };
@@ -394,7 +419,9 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
 
     rune_entry.mints += 1;
 
-    self.id_to_entry.insert(&id.store(), rune_entry.store())?;
+    self
+      .undo_log
+      .insert(self.id_to_entry, &id.store(), rune_entry.store())?;
 
     Ok(Some(Lot(amount)))
   }
@@ -473,8 +500,8 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
     // increment unallocated runes with the runes in tx inputs
     for input in &tx.input {
       if let Some(guard) = self
-        .outpoint_to_balances
-        .remove(&input.previous_output.store())?
+        .undo_log
+        .remove(self.outpoint_to_balances, &input.previous_output.store())?
       {
         let buffer = guard.value();
         let mut i = 0;
@@ -482,6 +509,11 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
           let ((id, balance), len) = Index::decode_rune_balance(&buffer[i..]).unwrap();
           i += len;
           *unallocated.entry(id).or_default() += balance;
+          self.undo_log.multimap_remove(
+            self.rune_id_to_outpoint,
+            id.store(),
+            &input.previous_output.store(),
+          )?;
         }
       }
     }
@@ -0,0 +1,638 @@
+use super::*;
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Verification {
+  pub(crate) errors: Vec<String>,
+  pub(crate) warnings: Vec<String>,
+}
+
+pub(super) struct Verifier<'a> {
+  errors: Vec<String>,
+  index: &'a Index,
+  rtx: redb::ReadTransaction,
+  warnings: Vec<String>,
+}
+
+impl<'a> Verifier<'a> {
+  pub(super) fn verify(index: &'a Index) -> Result<Verification> {
+    let mut verifier = Self {
+      errors: Vec::new(),
+      index,
+      rtx: index.database.begin_read()?,
+      warnings: Vec::new(),
+    };
+
+    verifier.verify_inscription_locations()?;
+    verifier.verify_inscription_numbers()?;
+
+    if index.index_sats {
+      verifier.verify_sat_locations()?;
+    }
+
+    if index.index_runes {
+      verifier.verify_rune_supply()?;
+    }
+
+    Ok(Verification {
+      errors: verifier.errors,
+      warnings: verifier.warnings,
+    })
+  }
+
+  fn statistic(&self, statistic: Statistic) -> Result<u64> {
+    Ok(
+      self
+        .rtx
+        .open_table(STATISTIC_TO_COUNT)?
+        .get(statistic.key())?
+        .map(|count| count.value())
+        .unwrap_or_default(),
+    )
+  }
+
+  fn verify_statistic(&mut self, statistic: Statistic, actual: u64) -> Result {
+    let expected = self.statistic(statistic)?;
+
+    if expected != actual {
+      self.errors.push(format!(
+        "statistic {statistic:?} is {expected} but index contains {actual}"
+      ));
+    }
+
+    Ok(())
+  }
+
+  fn verify_inscription_locations(&mut self) -> Result {
+    let sequence_number_to_satpoint = self.rtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
+
+    let mut outpoints = BTreeMap::new();
+    let mut unbound = 0;
+
+    for result in sequence_number_to_satpoint.iter()? {
+      let (sequence_number, satpoint) = result?;
+
+      let outpoint = SatPoint::load(*satpoint.value()).outpoint;
+
+      if outpoint == unbound_outpoint() {
+        unbound += 1;
+      } else if outpoint != OutPoint::null() {
+        outpoints
+          .entry(outpoint)
+          .or_insert_with(|| sequence_number.value());
+      }
+    }
+
+    // without spent sat ranges, the sat index contains exactly the unspent
+    // outputs, including unspendable ones, so bitcoin core need not be asked
+    let outpoint_to_sat_ranges = (self.index.index_sats && !self.index.index_spent_sats)
+      .then(|| self.rtx.open_table(OUTPOINT_TO_SAT_RANGES))
+      .transpose()?;
+
+    // otherwise, outputs are checked against bitcoin core's UTXO set, which
+    // only matches the index if both are at the same block
+    let tip = match outpoint_to_sat_ranges {
+      Some(_) => None,
+      None => {
+        let tip = self.index.client.get_best_block_hash()?;
+
+        let index_tip = self
+          .rtx
+          .open_table(HEIGHT_TO_BLOCK_HEADER)?
+          .range(0..)?
+          .next_back()
+          .transpose()?
+          .map(|(_height, header)| Header::load(*header.value()).block_hash());
+
+        if index_tip != Some(tip) {
+          self.warnings.push(format!(
+            "index is not at bitcoin core chain tip {tip}, skipping check that inscriptions are in unspent outputs"
+          ));
+          return self.verify_statistic(Statistic::UnboundInscriptions, unbound);
+        }
+
+        Some(tip)
+      }
+    };
+
+    let mut spent = Vec::new();
+
+    for (outpoint, sequence_number) in outpoints {
+      let unspent = match &outpoint_to_sat_ranges {
+        Some(outpoint_to_sat_ranges) => outpoint_to_sat_ranges.get(&outpoint.store())?.is_some(),
+        None => self.is_unspent(outpoint)?,
+      };
+
+      if !unspent {
+        spent.push(format!(
+          "inscription with sequence number {sequence_number} is in spent output {outpoint}"
+        ));
+      }
+    }
+
+    if let Some(tip) = tip {
+      if self.index.client.get_best_block_hash()? != tip {
+        self.warnings.push(format!(
+          "bitcoin core chain tip changed from {tip} during verification, skipping check that inscriptions are in unspent outputs"
+        ));
+        spent.clear();
+      }
+    }
+
+    self.errors.extend(spent);
+
+    self.verify_statistic(Statistic::UnboundInscriptions, unbound)
+  }
+
+  fn is_unspent(&self, outpoint: OutPoint) -> Result<bool> {
+    if self
+      .index
+      .client
+      .get_tx_out(&outpoint.txid, outpoint.vout, Some(false))?
+      .is_some()
+    {
+      return Ok(true);
+    }
+
+    // OP_RETURN outputs are never spent, but are not in the UTXO set
+    Ok(
+      self
+        .index
+        .get_transaction(outpoint.txid)?
+        .and_then(|tx| tx.output.into_iter().nth(outpoint.vout.into_usize()))
+        .is_some_and(|output| output.script_pubkey.is_op_return()),
+    )
+  }
+
+  fn verify_inscription_numbers(&mut self) -> Result {
+    let inscription_number_to_sequence_number =
+      self.rtx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
+
+    let sequence_number_to_inscription_entry =
+      self.rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    let mut blessed = 0;
+    let mut cursed = 0;
+    let mut lowest = 0;
+    let mut highest = -1;
+
+    for result in inscription_number_to_sequence_number.iter()? {
+      let (number, sequence_number) = result?;
+      let (number, sequence_number) = (number.value(), sequence_number.value());
+
+      if number < 0 {
+        cursed += 1;
+      } else {
+        blessed += 1;
+      }
+
+      lowest = lowest.min(number);
+      highest = highest.max(number);
+
+      match sequence_number_to_inscription_entry.get(sequence_number)? {
+        Some(entry) => {
+          let entry = InscriptionEntry::load(entry.value());
+
+          if entry.inscription_number != number {
+            self.errors.push(format!(
+              "inscription number {number} refers to inscription with sequence number \
+              {sequence_number} which has number {}",
+              entry.inscription_number
+            ));
+          }
+        }
+        None => self.errors.push(format!(
+          "inscription number {number} refers to missing sequence number {sequence_number}"
+        )),
+      }
+    }
+
+    if i64::from(lowest) != -cursed {
+      self.errors.push(format!(
+        "cursed inscription numbers are not contiguous: lowest is {lowest} but there are {cursed}"
+      ));
+    }
+
+    if i64::from(highest) + 1 != blessed {
+      self.errors.push(format!(
+        "blessed inscription numbers are not contiguous: highest is {highest} but there are {blessed}"
+      ));
+    }
+
+    self.verify_statistic(Statistic::BlessedInscriptions, blessed.try_into().unwrap())?;
+    self.verify_statistic(Statistic::CursedInscriptions, cursed.try_into().unwrap())
+  }
+
+  fn verify_sat_locations(&mut self) -> Result {
+    let outpoint_to_sat_ranges = self.rtx.open_table(OUTPOINT_TO_SAT_RANGES)?;
+
+    for result in self.rtx.open_table(SAT_TO_SATPOINT)?.iter()? {
+      let (sat, satpoint) = result?;
+      let (sat, satpoint) = (sat.value(), SatPoint::load(*satpoint.value()));
+
+      let Some(sat_ranges) = outpoint_to_sat_ranges.get(&satpoint.outpoint.store())? else {
+        self.errors.push(format!(
+          "sat {sat} is at {satpoint} but output {} has no sat ranges",
+          satpoint.outpoint
+        ));
+        continue;
+      };
+
+      let mut offset = 0;
+      let mut found = None;
+
+      for chunk in sat_ranges.value().chunks_exact(11) {
+        let (start, end) = SatRange::load(chunk.try_into().unwrap());
+
+        if (start..end).contains(&sat) {
+          found = Some(offset + sat - start);
+          break;
+        }
+
+        offset += end - start;
+      }
+
+      if found != Some(satpoint.offset) {
+        self.errors.push(format!(
+          "sat {sat} is at {satpoint} but sat ranges of output {} {}",
+          satpoint.outpoint,
+          match found {
+            Some(offset) => format!("place it at offset {offset}"),
+            None => "do not contain it".into(),
+          }
+        ));
+      }
+    }
+
+    let lost = outpoint_to_sat_ranges
+      .get(&OutPoint::null().store())?
+      .map(|sat_ranges| {
+        sat_ranges
+          .value()
+          .chunks_exact(11)
+          .map(|chunk| {
+            let (start, end) = SatRange::load(chunk.try_into().unwrap());
+            end - start
+          })
+          .sum()
+      })
+      .unwrap_or_default();
+
+    self.verify_statistic(Statistic::LostSats, lost)
+  }
+
+  fn verify_rune_supply(&mut self) -> Result {
+    let mut balances = HashMap::<RuneId, u128>::new();
+
+    for result in self.rtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?.iter()? {
+      let (outpoint, buffer) = result?;
+      let buffer = buffer.value();
+
+      let mut i = 0;
+      while i < buffer.len() {
+        let Ok(((id, amount), length)) = Index::decode_rune_balance(&buffer[i..]) else {
+          self.errors.push(format!(
+            "rune balances of output {} are malformed",
+            OutPoint::load(*outpoint.value())
+          ));
+          break;
+        };
+
+        i += length;
+
+        let balance = balances.entry(id).or_default();
+        *balance = balance.saturating_add(amount);
+      }
+    }
+
+    let mut runes = 0;
+
+    for result in self.rtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?.iter()? {
+      let (id, entry) = result?;
+      let (id, entry) = (RuneId::load(id.value()), RuneEntry::load(entry.value()));
+
+      runes += 1;
+
+      let balance = balances.remove(&id).unwrap_or_default();
+
+      if entry.supply().checked_sub(entry.burned) != Some(balance) {
+        self.errors.push(format!(
+          "rune {} has supply {} and {} burned but {balance} in outputs",
+          entry.spaced_rune,
+          entry.supply(),
+          entry.burned,
+        ));
+      }
+    }
+
+    for (id, balance) in balances {
+      self
+        .errors
+        .push(format!("outputs contain {balance} of unknown rune {id}"));
+    }
+
+    self.verify_statistic(Statistic::Runes, runes)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  const RUNE: u128 = 99246114928149462;
+
+  fn inscribe(context: &Context) -> InscriptionId {
+    let block_count = usize::try_from(context.index.block_count().unwrap()).unwrap();
+
+    context.mine_blocks(1);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        block_count,
+        0,
+        0,
+        inscription("text/plain", "hello").to_witness(),
+      )],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    InscriptionId { txid, index: 0 }
+  }
+
+  #[test]
+  fn consistent_index_has_no_errors() {
+    for context in [
+      Context::builder().build(),
+      Context::builder().arg("--index-sats").build(),
+      Context::builder()
+        .args(["--index-sats", "--index-spent-sats"])
+        .build(),
+    ] {
+      let inscription_id = inscribe(&context);
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(
+          2,
+          0,
+          0,
+          Inscription {
+            unrecognized_even_field: true,
+            ..inscription("text/plain", "cursed")
+          }
+          .to_witness(),
+        )],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(3, 0, 0, Default::default()), (2, 1, 0, Default::default())],
+        fee: 50 * COIN_VALUE,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      assert_eq!(
+        context
+          .index
+          .get_inscription_entry(inscription_id)
+          .unwrap()
+          .unwrap()
+          .inscription_number,
+        0
+      );
+
+      assert_eq!(context.index.verify().unwrap().errors, Vec::<String>::new());
+    }
+  }
+
+  #[test]
+  fn consistent_rune_index_has_no_errors() {
+    let context = Context::builder()
+      .args(["--index-runes", "--index-sats"])
+      .build();
+
+    let (_txid, id) = context.etch(
+      Runestone {
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(1000),
+          terms: Some(Terms {
+            amount: Some(100),
+            cap: Some(10),
+            ..default()
+          }),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(usize::try_from(id.block).unwrap(), 1, 0, Witness::new())],
+      op_return: Some(
+        Runestone {
+          edicts: vec![Edict {
+            id,
+            amount: 10,
+            output: 1,
+          }],
+          mint: Some(id),
+          ..default()
+        }
+        .encipher(),
+      ),
+      outputs: 1,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(context.index.get_rune_by_id(id).unwrap().is_some());
+
+    assert_eq!(context.index.verify().unwrap().errors, Vec::<String>::new());
+  }
+
+  #[test]
+  fn inscription_in_spent_output_is_detected() {
+    for context in Context::configurations() {
+      inscribe(&context);
+
+      let spent = OutPoint {
+        txid: context.core.tx(1, 0).txid(),
+        vout: 0,
+      };
+
+      {
+        let wtx = context.index.database.begin_write().unwrap();
+        wtx
+          .open_table(SEQUENCE_NUMBER_TO_SATPOINT)
+          .unwrap()
+          .insert(
+            0,
+            &SatPoint {
+              outpoint: spent,
+              offset: 0,
+            }
+            .store(),
+          )
+          .unwrap();
+        wtx.commit().unwrap();
+      }
+
+      assert_eq!(
+        context.index.verify().unwrap().errors,
+        [format!(
+          "inscription with sequence number 0 is in spent output {spent}"
+        )],
+      );
+    }
+  }
+
+  #[test]
+  fn unspent_outputs_are_not_checked_when_index_is_behind_bitcoin_core() {
+    let context = Context::builder().build();
+
+    inscribe(&context);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      ..default()
+    });
+
+    let tip = context.mine_blocks_with_update(1, false)[0].block_hash();
+
+    assert_eq!(
+      context.index.verify().unwrap(),
+      Verification {
+        errors: Vec::new(),
+        warnings: vec![format!(
+          "index is not at bitcoin core chain tip {tip}, skipping check that inscriptions are in unspent outputs"
+        )],
+      },
+    );
+
+    context.index.update().unwrap();
+
+    assert_eq!(context.index.verify().unwrap(), Verification::default());
+  }
+
+  #[test]
+  fn inscription_number_gap_is_detected() {
+    let context = Context::builder().build();
+
+    inscribe(&context);
+    inscribe(&context);
+
+    {
+      let wtx = context.index.database.begin_write().unwrap();
+      wtx
+        .open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)
+        .unwrap()
+        .remove(0)
+        .unwrap();
+      wtx.commit().unwrap();
+    }
+
+    assert_eq!(
+      context.index.verify().unwrap().errors,
+      [
+        "blessed inscription numbers are not contiguous: highest is 1 but there are 1",
+        "statistic BlessedInscriptions is 2 but index contains 1",
+      ],
+    );
+  }
+
+  #[test]
+  fn statistic_mismatch_is_detected() {
+    let context = Context::builder().build();
+
+    inscribe(&context);
+
+    {
+      let wtx = context.index.database.begin_write().unwrap();
+      wtx
+        .open_table(STATISTIC_TO_COUNT)
+        .unwrap()
+        .insert(&Statistic::UnboundInscriptions.key(), 5)
+        .unwrap();
+      wtx.commit().unwrap();
+    }
+
+    assert_eq!(
+      context.index.verify().unwrap().errors,
+      ["statistic UnboundInscriptions is 5 but index contains 0"],
+    );
+  }
+
+  #[test]
+  fn sat_location_mismatch_is_detected() {
+    let context = Context::builder().arg("--index-sats").build();
+
+    context.mine_blocks(1);
+
+    let outpoint = OutPoint {
+      txid: context.core.tx(1, 0).txid(),
+      vout: 0,
+    };
+
+    let satpoint = SatPoint {
+      outpoint,
+      offset: 1,
+    };
+
+    {
+      let wtx = context.index.database.begin_write().unwrap();
+      wtx
+        .open_table(SAT_TO_SATPOINT)
+        .unwrap()
+        .insert(&(50 * COIN_VALUE), &satpoint.store())
+        .unwrap();
+      wtx.commit().unwrap();
+    }
+
+    assert_eq!(
+      context.index.verify().unwrap().errors,
+      [format!(
+        "sat 5000000000 is at {satpoint} but sat ranges of output {outpoint} place it at offset 0"
+      )],
+    );
+  }
+
+  #[test]
+  fn rune_supply_mismatch_is_detected() {
+    let context = Context::builder().arg("--index-runes").build();
+
+    let (txid, _id) = context.etch(
+      Runestone {
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(1000),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    {
+      let wtx = context.index.database.begin_write().unwrap();
+      wtx
+        .open_table(OUTPOINT_TO_RUNE_BALANCES)
+        .unwrap()
+        .remove(&OutPoint { txid, vout: 0 }.store())
+        .unwrap()
+        .unwrap();
+      wtx.commit().unwrap();
+    }
+
+    assert_eq!(
+      context.index.verify().unwrap().errors,
+      ["rune AAAAAAAAAAAAA has supply 1000 and 0 burned but 0 in outputs"],
+    );
+  }
+}
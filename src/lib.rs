@@ -24,6 +24,7 @@ use {
       teleburn, ParsedEnvelope,
     },
     into_usize::IntoUsize,
+    migration::Migration,
     representation::Representation,
     settings::Settings,
     subcommand::{Subcommand, SubcommandResult},
@@ -56,6 +57,7 @@ use {
   },
   regex::Regex,
   reqwest::Url,
+  schemars::JsonSchema,
   serde::{Deserialize, Deserializer, Serialize},
   serde_with::{DeserializeFromStr, SerializeDisplay},
   std::{
@@ -71,7 +73,7 @@ use {
     process::{self, Command, Stdio},
     str::FromStr,
     sync::{
-      atomic::{self, AtomicBool},
+      atomic::{self, AtomicBool, AtomicU64},
       Arc, Mutex,
     },
     thread,
@@ -109,6 +111,7 @@ pub mod index;
 mod inscriptions;
 mod into_usize;
 mod macros;
+mod migration;
 mod object;
 pub mod options;
 pub mod outgoing;
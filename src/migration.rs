@@ -0,0 +1,183 @@
+use {
+  super::*,
+  redb::{Database, TableDefinition, WriteTransaction},
+};
+
+/// An in-place upgrade of a database from schema `version - 1` to `version`.
+pub(crate) struct Migration {
+  pub(crate) description: &'static str,
+  pub(crate) migrate: fn(&WriteTransaction) -> Result,
+  pub(crate) version: u64,
+}
+
+impl Migration {
+  pub(crate) fn available(migrations: &[Self], from: u64, to: u64) -> bool {
+    (from + 1..=to).all(|version| {
+      migrations
+        .iter()
+        .any(|migration| migration.version == version)
+    })
+  }
+
+  /// Apply migrations from schema `from` to schema `to`, each in its own write
+  /// transaction which also records the new schema version under `key` in
+  /// `statistics`, and return the migrations which were applied.
+  pub(crate) fn run<'a>(
+    migrations: &'a [Self],
+    database: &Database,
+    path: &Path,
+    statistics: TableDefinition<u64, u64>,
+    key: u64,
+    from: u64,
+    to: u64,
+  ) -> Result<Vec<&'a Self>> {
+    let mut applied = Vec::new();
+
+    for version in from + 1..=to {
+      let migration = migrations
+        .iter()
+        .find(|migration| migration.version == version)
+        .ok_or_else(|| anyhow!("no migration to schema {version}"))?;
+
+      let wtx = database.begin_write()?;
+
+      (migration.migrate)(&wtx).with_context(|| {
+        format!(
+          "failed to migrate `{}` to schema {version}: {}",
+          path.display(),
+          migration.description,
+        )
+      })?;
+
+      wtx.open_table(statistics)?.insert(&key, &version)?;
+
+      wtx.commit()?;
+
+      log::info!(
+        "Migrated `{}` to schema {version}: {}",
+        path.display(),
+        migration.description
+      );
+
+      applied.push(migration);
+    }
+
+    Ok(applied)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, redb::ReadableTable, tempfile::TempDir};
+
+  define_table! { STATISTICS, u64, u64 }
+  define_table! { VALUES, u64, u64 }
+
+  const MIGRATIONS: &[Migration] = &[
+    Migration {
+      description: "add values",
+      migrate: |wtx| {
+        wtx.open_table(VALUES)?.insert(0, 1)?;
+        Ok(())
+      },
+      version: 2,
+    },
+    Migration {
+      description: "double values",
+      migrate: |wtx| {
+        let mut values = wtx.open_table(VALUES)?;
+        let value = values.get(0)?.unwrap().value();
+        values.insert(0, value * 2)?;
+        Ok(())
+      },
+      version: 3,
+    },
+  ];
+
+  #[test]
+  fn available() {
+    assert!(Migration::available(MIGRATIONS, 1, 3));
+    assert!(Migration::available(MIGRATIONS, 2, 3));
+    assert!(Migration::available(MIGRATIONS, 3, 3));
+    assert!(!Migration::available(MIGRATIONS, 0, 3));
+    assert!(!Migration::available(MIGRATIONS, 1, 4));
+  }
+
+  #[test]
+  fn run() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("database.redb");
+
+    let database = Database::create(&path).unwrap();
+
+    assert_eq!(
+      Migration::run(MIGRATIONS, &database, &path, STATISTICS, 0, 1, 3)
+        .unwrap()
+        .iter()
+        .map(|migration| migration.version)
+        .collect::<Vec<u64>>(),
+      [2, 3],
+    );
+
+    let rtx = database.begin_read().unwrap();
+
+    assert_eq!(
+      rtx
+        .open_table(STATISTICS)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .unwrap()
+        .value(),
+      3
+    );
+
+    assert_eq!(
+      rtx
+        .open_table(VALUES)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .unwrap()
+        .value(),
+      2
+    );
+  }
+
+  #[test]
+  fn failed_migration_leaves_schema_unchanged() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("database.redb");
+
+    let database = Database::create(&path).unwrap();
+
+    assert_eq!(
+      Migration::run(
+        &[Migration {
+          description: "fail",
+          migrate: |_| bail!("oops"),
+          version: 1,
+        }],
+        &database,
+        &path,
+        STATISTICS,
+        0,
+        0,
+        1,
+      )
+      .err()
+      .unwrap()
+      .to_string(),
+      format!("failed to migrate `{}` to schema 1: fail", path.display()),
+    );
+
+    let wtx = database.begin_write().unwrap();
+
+    assert!(wtx
+      .open_table(STATISTICS)
+      .unwrap()
+      .get(0)
+      .unwrap()
+      .is_none());
+  }
+}
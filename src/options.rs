@@ -23,6 +23,11 @@ pub struct Options {
   pub(crate) bitcoin_rpc_username: Option<String>,
   #[arg(long = "chain", value_enum, help = "Use <CHAIN>. [default: mainnet]")]
   pub(crate) chain_argument: Option<Chain>,
+  #[arg(
+    long,
+    help = "Only create savepoints within <CHAIN_TIP_DISTANCE> blocks of the chain tip. [default: 21]"
+  )]
+  pub(crate) chain_tip_distance: Option<u32>,
   #[arg(
     long,
     help = "Commit to index every <COMMIT_INTERVAL> blocks. [default: 5000]"
@@ -45,6 +50,8 @@ pub struct Options {
   pub(crate) height_limit: Option<u32>,
   #[arg(long, help = "Use index at <INDEX>.")]
   pub(crate) index: Option<PathBuf>,
+  #[arg(long, help = "Track unspent output addresses.")]
+  pub(crate) index_addresses: bool,
   #[arg(
     long,
     help = "Set index cache size to <INDEX_CACHE_SIZE> bytes. [default: 1/4 available RAM]"
@@ -57,12 +64,22 @@ pub struct Options {
   pub(crate) index_runes: bool,
   #[arg(long, help = "Track location of all satoshis.")]
   pub(crate) index_sats: bool,
+  #[arg(
+    long,
+    help = "Index text of inscription content, metaprotocols, and metadata for full-text search."
+  )]
+  pub(crate) index_search: bool,
   #[arg(long, help = "Keep sat index entries of spent outputs.")]
   pub(crate) index_spent_sats: bool,
   #[arg(long, help = "Store transactions in index.")]
   pub(crate) index_transactions: bool,
   #[arg(long, help = "Run in integration test mode.")]
   pub(crate) integration_test: bool,
+  #[arg(
+    long,
+    help = "Keep <MAX_SAVEPOINTS> savepoints for recovering from reorgs. [default: 2]"
+  )]
+  pub(crate) max_savepoints: Option<u32>,
   #[arg(long, help = "Minify JSON output.")]
   pub(crate) minify: bool,
   #[arg(
@@ -72,11 +89,26 @@ pub struct Options {
     help = "Do not index inscriptions."
   )]
   pub(crate) no_index_inscriptions: bool,
+  #[arg(
+    long,
+    help = "Create a savepoint every <SAVEPOINT_INTERVAL> blocks. [default: 10]"
+  )]
+  pub(crate) savepoint_interval: Option<u32>,
   #[arg(
     long,
     help = "Require basic HTTP authentication with <SERVER_PASSWORD>. Credentials are sent in cleartext. Consider using authentication in conjunction with HTTPS."
   )]
   pub(crate) server_password: Option<String>,
+  #[arg(
+    long,
+    help = "Limit requests without an API key to <SERVER_RATE_LIMIT> per minute per IP address."
+  )]
+  pub(crate) server_rate_limit: Option<u32>,
+  #[arg(
+    long,
+    help = "Identify clients by the address forwarded in `X-Forwarded-For` or `Forwarded` headers by <SERVER_TRUSTED_PROXIES> reverse proxies in front of the server."
+  )]
+  pub(crate) server_trusted_proxies: Option<u32>,
   #[arg(
     long,
     help = "Require basic HTTP authentication with <SERVER_USERNAME>. Credentials are sent in cleartext. Consider using authentication in conjunction with HTTPS."
@@ -88,4 +120,9 @@ pub struct Options {
   pub(crate) signet: bool,
   #[arg(long, short, help = "Use testnet. Equivalent to `--chain testnet`.")]
   pub(crate) testnet: bool,
+  #[arg(
+    long,
+    help = "Keep undo log for the last <UNDO_LOG_DEPTH> blocks, allowing the index to be rolled back that far. [default: 100]"
+  )]
+  pub(crate) undo_log_depth: Option<u32>,
 }
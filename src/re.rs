@@ -5,6 +5,9 @@ fn re(s: &'static str) -> Regex {
 }
 
 lazy_static! {
+  pub(crate) static ref ADDRESS: Regex = re(
+    r"(bc1|tb1|bcrt1)[a-z0-9]{25,}|(BC1|TB1|BCRT1)[A-Z0-9]{25,}|[123mn][a-km-zA-HJ-NP-Z1-9]{25,34}"
+  );
   pub(crate) static ref HASH: Regex = re(r"[[:xdigit:]]{64}");
   pub(crate) static ref INSCRIPTION_ID: Regex = re(r"[[:xdigit:]]{64}i\d+");
   pub(crate) static ref INSCRIPTION_NUMBER: Regex = re(r"-?[0-9]+");
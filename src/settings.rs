@@ -1,13 +1,19 @@
 use {super::*, bitcoincore_rpc::Auth};
 
+pub(crate) use api_key::ApiKey;
+
+mod api_key;
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default, deny_unknown_fields)]
 pub struct Settings {
+  api_keys: Option<Vec<ApiKey>>,
   bitcoin_data_dir: Option<PathBuf>,
   bitcoin_rpc_password: Option<String>,
   bitcoin_rpc_url: Option<String>,
   bitcoin_rpc_username: Option<String>,
   chain: Option<Chain>,
+  chain_tip_distance: Option<u32>,
   commit_interval: Option<usize>,
   config: Option<PathBuf>,
   config_dir: Option<PathBuf>,
@@ -17,16 +23,23 @@ pub struct Settings {
   height_limit: Option<u32>,
   hidden: Option<HashSet<InscriptionId>>,
   index: Option<PathBuf>,
+  index_addresses: bool,
   index_cache_size: Option<usize>,
   index_runes: bool,
   index_sats: bool,
+  index_search: bool,
   index_spent_sats: bool,
   index_transactions: bool,
   integration_test: bool,
+  max_savepoints: Option<u32>,
   no_index_inscriptions: bool,
+  savepoint_interval: Option<u32>,
   server_password: Option<String>,
+  server_rate_limit: Option<u32>,
+  server_trusted_proxies: Option<u32>,
   server_url: Option<String>,
   server_username: Option<String>,
+  undo_log_depth: Option<u32>,
 }
 
 impl Settings {
@@ -107,11 +120,13 @@ impl Settings {
 
   pub(crate) fn or(self, source: Settings) -> Self {
     Self {
+      api_keys: self.api_keys.or(source.api_keys),
       bitcoin_data_dir: self.bitcoin_data_dir.or(source.bitcoin_data_dir),
       bitcoin_rpc_password: self.bitcoin_rpc_password.or(source.bitcoin_rpc_password),
       bitcoin_rpc_url: self.bitcoin_rpc_url.or(source.bitcoin_rpc_url),
       bitcoin_rpc_username: self.bitcoin_rpc_username.or(source.bitcoin_rpc_username),
       chain: self.chain.or(source.chain),
+      chain_tip_distance: self.chain_tip_distance.or(source.chain_tip_distance),
       commit_interval: self.commit_interval.or(source.commit_interval),
       config: self.config.or(source.config),
       config_dir: self.config_dir.or(source.config_dir),
@@ -131,21 +146,31 @@ impl Settings {
           .collect(),
       ),
       index: self.index.or(source.index),
+      index_addresses: self.index_addresses || source.index_addresses,
       index_cache_size: self.index_cache_size.or(source.index_cache_size),
       index_runes: self.index_runes || source.index_runes,
       index_sats: self.index_sats || source.index_sats,
+      index_search: self.index_search || source.index_search,
       index_spent_sats: self.index_spent_sats || source.index_spent_sats,
       index_transactions: self.index_transactions || source.index_transactions,
       integration_test: self.integration_test || source.integration_test,
+      max_savepoints: self.max_savepoints.or(source.max_savepoints),
       no_index_inscriptions: self.no_index_inscriptions || source.no_index_inscriptions,
+      savepoint_interval: self.savepoint_interval.or(source.savepoint_interval),
       server_password: self.server_password.or(source.server_password),
+      server_rate_limit: self.server_rate_limit.or(source.server_rate_limit),
+      server_trusted_proxies: self
+        .server_trusted_proxies
+        .or(source.server_trusted_proxies),
       server_url: self.server_url.or(source.server_url),
       server_username: self.server_username.or(source.server_username),
+      undo_log_depth: self.undo_log_depth.or(source.undo_log_depth),
     }
   }
 
   pub(crate) fn from_options(options: Options) -> Self {
     Self {
+      api_keys: None,
       bitcoin_data_dir: options.bitcoin_data_dir,
       bitcoin_rpc_password: options.bitcoin_rpc_password,
       bitcoin_rpc_url: options.bitcoin_rpc_url,
@@ -156,6 +181,7 @@ impl Settings {
         .or(options.regtest.then_some(Chain::Regtest))
         .or(options.testnet.then_some(Chain::Testnet))
         .or(options.chain_argument),
+      chain_tip_distance: options.chain_tip_distance,
       commit_interval: options.commit_interval,
       config: options.config,
       config_dir: options.config_dir,
@@ -165,16 +191,23 @@ impl Settings {
       height_limit: options.height_limit,
       hidden: None,
       index: options.index,
+      index_addresses: options.index_addresses,
       index_cache_size: options.index_cache_size,
       index_runes: options.index_runes,
       index_sats: options.index_sats,
+      index_search: options.index_search,
       index_spent_sats: options.index_spent_sats,
       index_transactions: options.index_transactions,
       integration_test: options.integration_test,
+      max_savepoints: options.max_savepoints,
       no_index_inscriptions: options.no_index_inscriptions,
+      savepoint_interval: options.savepoint_interval,
       server_password: options.server_password,
+      server_rate_limit: options.server_rate_limit,
+      server_trusted_proxies: options.server_trusted_proxies,
       server_url: None,
       server_username: options.server_username,
+      undo_log_depth: options.undo_log_depth,
     }
   }
 
@@ -229,11 +262,13 @@ impl Settings {
     };
 
     Ok(Self {
+      api_keys: None,
       bitcoin_data_dir: get_path("BITCOIN_DATA_DIR"),
       bitcoin_rpc_password: get_string("BITCOIN_RPC_PASSWORD"),
       bitcoin_rpc_url: get_string("BITCOIN_RPC_URL"),
       bitcoin_rpc_username: get_string("BITCOIN_RPC_USERNAME"),
       chain: get_chain("CHAIN")?,
+      chain_tip_distance: get_u32("CHAIN_TIP_DISTANCE")?,
       commit_interval: get_usize("COMMIT_INTERVAL")?,
       config: get_path("CONFIG"),
       config_dir: get_path("CONFIG_DIR"),
@@ -243,26 +278,35 @@ impl Settings {
       height_limit: get_u32("HEIGHT_LIMIT")?,
       hidden: inscriptions("HIDDEN")?,
       index: get_path("INDEX"),
+      index_addresses: get_bool("INDEX_ADDRESSES"),
       index_cache_size: get_usize("INDEX_CACHE_SIZE")?,
       index_runes: get_bool("INDEX_RUNES"),
       index_sats: get_bool("INDEX_SATS"),
+      index_search: get_bool("INDEX_SEARCH"),
       index_spent_sats: get_bool("INDEX_SPENT_SATS"),
       index_transactions: get_bool("INDEX_TRANSACTIONS"),
       integration_test: get_bool("INTEGRATION_TEST"),
+      max_savepoints: get_u32("MAX_SAVEPOINTS")?,
       no_index_inscriptions: get_bool("NO_INDEX_INSCRIPTIONS"),
+      savepoint_interval: get_u32("SAVEPOINT_INTERVAL")?,
       server_password: get_string("SERVER_PASSWORD"),
+      server_rate_limit: get_u32("SERVER_RATE_LIMIT")?,
+      server_trusted_proxies: get_u32("SERVER_TRUSTED_PROXIES")?,
       server_url: get_string("SERVER_URL"),
       server_username: get_string("SERVER_USERNAME"),
+      undo_log_depth: get_u32("UNDO_LOG_DEPTH")?,
     })
   }
 
   pub(crate) fn for_env(dir: &Path, rpc_url: &str, server_url: &str) -> Self {
     Self {
+      api_keys: None,
       bitcoin_data_dir: Some(dir.into()),
       bitcoin_rpc_password: None,
       bitcoin_rpc_url: Some(rpc_url.into()),
       bitcoin_rpc_username: None,
       chain: Some(Chain::Regtest),
+      chain_tip_distance: None,
       commit_interval: None,
       config: None,
       config_dir: None,
@@ -272,16 +316,23 @@ impl Settings {
       height_limit: None,
       hidden: None,
       index: None,
+      index_addresses: true,
       index_cache_size: None,
       index_runes: true,
       index_sats: true,
+      index_search: true,
       index_spent_sats: false,
       index_transactions: false,
       integration_test: false,
+      max_savepoints: None,
       no_index_inscriptions: false,
+      savepoint_interval: None,
       server_password: None,
+      server_rate_limit: None,
+      server_trusted_proxies: None,
       server_url: Some(server_url.into()),
       server_username: None,
+      undo_log_depth: None,
     }
   }
 
@@ -318,7 +369,52 @@ impl Settings {
       None => data_dir.join("index.redb"),
     };
 
+    let max_savepoints = self.max_savepoints.unwrap_or(2);
+
+    ensure!(max_savepoints > 0, "max savepoints must be greater than 0");
+
+    let savepoint_interval = self.savepoint_interval.unwrap_or(10);
+
+    ensure!(
+      savepoint_interval > 0,
+      "savepoint interval must be greater than 0"
+    );
+
+    if let Some(api_keys) = &self.api_keys {
+      let mut keys = HashSet::new();
+      let mut names = HashSet::new();
+
+      for api_key in api_keys {
+        ensure!(
+          !api_key.key.is_empty(),
+          "API key `{}` is empty",
+          api_key.name
+        );
+        ensure!(
+          keys.insert(&api_key.key),
+          "API key `{}` is not unique",
+          api_key.name
+        );
+        ensure!(
+          names.insert(&api_key.name),
+          "API key name `{}` is not unique",
+          api_key.name
+        );
+        ensure!(
+          api_key.rate_limit != Some(0),
+          "API key `{}` rate limit must be greater than 0",
+          api_key.name
+        );
+      }
+    }
+
+    ensure!(
+      self.server_rate_limit != Some(0),
+      "server rate limit must be greater than 0"
+    );
+
     Ok(Self {
+      api_keys: self.api_keys,
       bitcoin_data_dir: Some(bitcoin_data_dir),
       bitcoin_rpc_password: self.bitcoin_rpc_password,
       bitcoin_rpc_url: Some(
@@ -329,6 +425,7 @@ impl Settings {
       ),
       bitcoin_rpc_username: self.bitcoin_rpc_username,
       chain: Some(chain),
+      chain_tip_distance: Some(self.chain_tip_distance.unwrap_or(21)),
       commit_interval: Some(self.commit_interval.unwrap_or(5000)),
       config: None,
       config_dir: None,
@@ -344,6 +441,7 @@ impl Settings {
       height_limit: self.height_limit,
       hidden: self.hidden,
       index: Some(index),
+      index_addresses: self.index_addresses,
       index_cache_size: Some(match self.index_cache_size {
         Some(index_cache_size) => index_cache_size,
         None => {
@@ -354,13 +452,19 @@ impl Settings {
       }),
       index_runes: self.index_runes,
       index_sats: self.index_sats,
+      index_search: self.index_search,
       index_spent_sats: self.index_spent_sats,
       index_transactions: self.index_transactions,
       integration_test: self.integration_test,
+      max_savepoints: Some(max_savepoints),
       no_index_inscriptions: self.no_index_inscriptions,
+      savepoint_interval: Some(savepoint_interval),
       server_password: self.server_password,
+      server_rate_limit: self.server_rate_limit,
+      server_trusted_proxies: self.server_trusted_proxies,
       server_url: self.server_url,
       server_username: self.server_username,
+      undo_log_depth: Some(self.undo_log_depth.unwrap_or(100)),
     })
   }
 
@@ -372,6 +476,10 @@ impl Settings {
     )
   }
 
+  pub(crate) fn api_keys(&self) -> &[ApiKey] {
+    self.api_keys.as_deref().unwrap_or_default()
+  }
+
   pub(crate) fn bitcoin_credentials(&self) -> Result<Auth> {
     if let Some((user, pass)) = &self
       .bitcoin_rpc_username
@@ -449,6 +557,10 @@ impl Settings {
     self.chain.unwrap()
   }
 
+  pub(crate) fn chain_tip_distance(&self) -> u32 {
+    self.chain_tip_distance.unwrap()
+  }
+
   pub(crate) fn commit_interval(&self) -> usize {
     self.commit_interval.unwrap()
   }
@@ -506,6 +618,10 @@ impl Settings {
     self.index.as_ref().unwrap()
   }
 
+  pub(crate) fn index_addresses(&self) -> bool {
+    self.index_addresses
+  }
+
   pub(crate) fn index_inscriptions(&self) -> bool {
     !self.no_index_inscriptions
   }
@@ -522,6 +638,10 @@ impl Settings {
     self.index_sats
   }
 
+  pub(crate) fn index_search(&self) -> bool {
+    self.index_search
+  }
+
   pub(crate) fn index_spent_sats(&self) -> bool {
     self.index_spent_sats
   }
@@ -534,6 +654,18 @@ impl Settings {
     self.integration_test
   }
 
+  pub(crate) fn max_savepoints(&self) -> u32 {
+    self.max_savepoints.unwrap()
+  }
+
+  pub(crate) fn savepoint_interval(&self) -> u32 {
+    self.savepoint_interval.unwrap()
+  }
+
+  pub(crate) fn undo_log_depth(&self) -> u32 {
+    self.undo_log_depth.unwrap()
+  }
+
   pub(crate) fn is_hidden(&self, inscription_id: InscriptionId) -> bool {
     self
       .hidden
@@ -550,6 +682,14 @@ impl Settings {
     }
   }
 
+  pub(crate) fn server_rate_limit(&self) -> Option<u32> {
+    self.server_rate_limit
+  }
+
+  pub(crate) fn server_trusted_proxies(&self) -> u32 {
+    self.server_trusted_proxies.unwrap_or_default()
+  }
+
   pub(crate) fn server_url(&self) -> Option<&str> {
     self.server_url.as_deref()
   }
@@ -885,6 +1025,46 @@ mod tests {
     assert_eq!(arguments.options.commit_interval, Some(500));
   }
 
+  #[test]
+  fn savepoint_defaults() {
+    let settings = parse(&[]);
+    assert_eq!(settings.chain_tip_distance(), 21);
+    assert_eq!(settings.max_savepoints(), 2);
+    assert_eq!(settings.savepoint_interval(), 10);
+    assert_eq!(settings.undo_log_depth(), 100);
+  }
+
+  #[test]
+  fn setting_savepoint_policy() {
+    let settings = parse(&[
+      "--chain-tip-distance=100",
+      "--max-savepoints=5",
+      "--savepoint-interval=3",
+    ]);
+    assert_eq!(settings.chain_tip_distance(), 100);
+    assert_eq!(settings.max_savepoints(), 5);
+    assert_eq!(settings.savepoint_interval(), 3);
+  }
+
+  #[test]
+  fn savepoint_policy_must_be_positive() {
+    assert_eq!(
+      Settings::from_options(Options::try_parse_from(["ord", "--max-savepoints=0"]).unwrap())
+        .or_defaults()
+        .unwrap_err()
+        .to_string(),
+      "max savepoints must be greater than 0",
+    );
+
+    assert_eq!(
+      Settings::from_options(Options::try_parse_from(["ord", "--savepoint-interval=0"]).unwrap())
+        .or_defaults()
+        .unwrap_err()
+        .to_string(),
+      "savepoint interval must be greater than 0",
+    );
+  }
+
   #[test]
   fn index_runes() {
     assert!(parse(&["--chain=signet", "--index-runes"]).index_runes());
@@ -982,6 +1162,7 @@ mod tests {
       ("BITCOIN_RPC_URL", "url"),
       ("BITCOIN_RPC_USERNAME", "bitcoin username"),
       ("CHAIN", "signet"),
+      ("CHAIN_TIP_DISTANCE", "5"),
       ("COMMIT_INTERVAL", "1"),
       ("CONFIG", "config"),
       ("CONFIG_DIR", "config dir"),
@@ -991,16 +1172,23 @@ mod tests {
       ("HEIGHT_LIMIT", "3"),
       ("HIDDEN", "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0 703e5f7c49d82aab99e605af306b9a30e991e57d42f982908a962a81ac439832i0"),
       ("INDEX", "index"),
+      ("INDEX_ADDRESSES", "1"),
       ("INDEX_CACHE_SIZE", "4"),
       ("INDEX_RUNES", "1"),
       ("INDEX_SATS", "1"),
+      ("INDEX_SEARCH", "1"),
       ("INDEX_SPENT_SATS", "1"),
       ("INDEX_TRANSACTIONS", "1"),
       ("INTEGRATION_TEST", "1"),
+      ("MAX_SAVEPOINTS", "6"),
       ("NO_INDEX_INSCRIPTIONS", "1"),
+      ("SAVEPOINT_INTERVAL", "7"),
       ("SERVER_PASSWORD", "server password"),
+      ("SERVER_RATE_LIMIT", "9"),
+      ("SERVER_TRUSTED_PROXIES", "10"),
       ("SERVER_URL", "server url"),
       ("SERVER_USERNAME", "server username"),
+      ("UNDO_LOG_DEPTH", "8"),
     ]
     .into_iter()
     .map(|(key, value)| (key.into(), value.into()))
@@ -1009,11 +1197,13 @@ mod tests {
     pretty_assert_eq!(
       Settings::from_env(env).unwrap(),
       Settings {
+        api_keys: None,
         bitcoin_data_dir: Some("/bitcoin/data/dir".into()),
         bitcoin_rpc_password: Some("bitcoin password".into()),
         bitcoin_rpc_url: Some("url".into()),
         bitcoin_rpc_username: Some("bitcoin username".into()),
         chain: Some(Chain::Signet),
+        chain_tip_distance: Some(5),
         commit_interval: Some(1),
         config: Some("config".into()),
         config_dir: Some("config dir".into()),
@@ -1034,16 +1224,23 @@ mod tests {
           .collect()
         ),
         index: Some("index".into()),
+        index_addresses: true,
         index_cache_size: Some(4),
         index_runes: true,
         index_sats: true,
+        index_search: true,
         index_spent_sats: true,
         index_transactions: true,
         integration_test: true,
+        max_savepoints: Some(6),
         no_index_inscriptions: true,
+        savepoint_interval: Some(7),
         server_password: Some("server password".into()),
+        server_rate_limit: Some(9),
+        server_trusted_proxies: Some(10),
         server_url: Some("server url".into()),
         server_username: Some("server username".into()),
+        undo_log_depth: Some(8),
       }
     );
   }
@@ -1059,6 +1256,7 @@ mod tests {
           "--bitcoin-rpc-url=url",
           "--bitcoin-rpc-username=bitcoin username",
           "--chain=signet",
+          "--chain-tip-distance=5",
           "--commit-interval=1",
           "--config=config",
           "--config-dir=config dir",
@@ -1066,25 +1264,34 @@ mod tests {
           "--datadir=/data/dir",
           "--first-inscription-height=2",
           "--height-limit=3",
+          "--index-addresses",
           "--index-cache-size=4",
           "--index-runes",
           "--index-sats",
+          "--index-search",
           "--index-spent-sats",
           "--index-transactions",
           "--index=index",
           "--integration-test",
+          "--max-savepoints=6",
           "--no-index-inscriptions",
+          "--savepoint-interval=7",
           "--server-password=server password",
+          "--server-rate-limit=9",
+          "--server-trusted-proxies=10",
           "--server-username=server username",
+          "--undo-log-depth=8",
         ])
         .unwrap()
       ),
       Settings {
+        api_keys: None,
         bitcoin_data_dir: Some("/bitcoin/data/dir".into()),
         bitcoin_rpc_password: Some("bitcoin password".into()),
         bitcoin_rpc_url: Some("url".into()),
         bitcoin_rpc_username: Some("bitcoin username".into()),
         chain: Some(Chain::Signet),
+        chain_tip_distance: Some(5),
         commit_interval: Some(1),
         config: Some("config".into()),
         config_dir: Some("config dir".into()),
@@ -1094,16 +1301,23 @@ mod tests {
         height_limit: Some(3),
         hidden: None,
         index: Some("index".into()),
+        index_addresses: true,
         index_cache_size: Some(4),
         index_runes: true,
         index_sats: true,
+        index_search: true,
         index_spent_sats: true,
         index_transactions: true,
         integration_test: true,
+        max_savepoints: Some(6),
         no_index_inscriptions: true,
+        savepoint_interval: Some(7),
         server_password: Some("server password".into()),
+        server_rate_limit: Some(9),
+        server_trusted_proxies: Some(10),
         server_url: None,
         server_username: Some("server username".into()),
+        undo_log_depth: Some(8),
       }
     );
   }
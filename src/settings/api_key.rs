@@ -0,0 +1,12 @@
+use super::*;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ApiKey {
+  #[serde(default)]
+  pub(crate) admin: bool,
+  pub(crate) key: String,
+  pub(crate) name: String,
+  pub(crate) quota: Option<u64>,
+  pub(crate) rate_limit: Option<u32>,
+}
@@ -65,8 +65,11 @@ impl Subcommand {
       Self::List(list) => list.run(settings),
       Self::Parse(parse) => parse.run(),
       Self::Runes => runes::run(settings),
-      Self::Server(server) => {
-        let index = Arc::new(Index::open(&settings)?);
+      Self::Server(mut server) => {
+        let index = Arc::new(Index::open_with_event_sender(
+          &settings,
+          server.event_sender()?,
+        )?);
         let handle = axum_server::Handle::new();
         LISTENERS.lock().unwrap().push(handle.clone());
         server.run(settings, index, handle)
@@ -1,25 +1,39 @@
 use super::*;
 
-mod export;
+pub mod export;
+mod import;
 pub mod info;
-mod update;
+pub mod rollback;
+pub mod update;
+pub mod verify;
 
 #[derive(Debug, Parser)]
 pub(crate) enum IndexSubcommand {
-  #[command(about = "Write inscription numbers and ids to a tab-separated file")]
+  #[command(
+    about = "Write inscription numbers and ids to a tab-separated file, or all tables to a snapshot"
+  )]
   Export(export::Export),
+  #[command(about = "Load index from snapshot")]
+  Import(import::Import),
   #[command(about = "Print index statistics")]
   Info(info::Info),
+  #[command(about = "Revert blocks using the undo log")]
+  Rollback(rollback::Rollback),
   #[command(about = "Update the index", alias = "run")]
   Update,
+  #[command(about = "Check index for inconsistencies")]
+  Verify,
 }
 
 impl IndexSubcommand {
   pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
     match self {
       Self::Export(export) => export.run(settings),
+      Self::Import(import) => import.run(settings),
       Self::Info(info) => info.run(settings),
+      Self::Rollback(rollback) => rollback.run(settings),
       Self::Update => update::run(settings),
+      Self::Verify => verify::run(settings),
     }
   }
 }
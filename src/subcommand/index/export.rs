@@ -1,11 +1,124 @@
-use super::*;
+use {
+  super::*,
+  std::io::{BufWriter, Write},
+};
 
 #[derive(Debug, Parser)]
+#[clap(group(
+  ArgGroup::new("output")
+    .required(true)
+    .args(&["inscriptions", "snapshot", "tsv"]))
+)]
 pub(crate) struct Export {
-  #[arg(long, help = "Include addresses in export")]
+  #[arg(
+    long,
+    value_enum,
+    default_value_t,
+    requires = "inscriptions",
+    help = "Write inscription records as <FORMAT>."
+  )]
+  format: Format,
+  #[arg(
+    long,
+    requires = "inscriptions",
+    help = "Only export inscriptions created at or after block <FROM_HEIGHT>."
+  )]
+  from_height: Option<u32>,
+  #[arg(long, requires = "tsv", help = "Include addresses in export")]
   include_addresses: bool,
+  #[arg(long, help = "Write one record per inscription to <INSCRIPTIONS>")]
+  inscriptions: Option<PathBuf>,
+  #[arg(long, help = "Write snapshot of all index tables to <SNAPSHOT>")]
+  snapshot: Option<PathBuf>,
+  #[arg(
+    long,
+    requires = "inscriptions",
+    help = "Only export inscriptions created at or before block <TO_HEIGHT>."
+  )]
+  to_height: Option<u32>,
   #[arg(long, help = "Write export to <TSV>")]
-  tsv: String,
+  tsv: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+enum Format {
+  Csv,
+  #[default]
+  Ndjson,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct InscriptionRecord {
+  pub charms: Vec<Charm>,
+  pub content_length: Option<usize>,
+  pub content_type: Option<String>,
+  pub delegate: Option<InscriptionId>,
+  pub fee: u64,
+  pub height: u32,
+  pub id: InscriptionId,
+  pub metaprotocol: Option<String>,
+  pub number: i32,
+  pub parents: Vec<InscriptionId>,
+  pub sat: Option<Sat>,
+  pub satpoint: SatPoint,
+  pub sequence_number: u32,
+  pub timestamp: u32,
+}
+
+impl InscriptionRecord {
+  const CSV_HEADER: &'static str = "charms,content_length,content_type,delegate,fee,height,id,\
+    metaprotocol,number,parents,sat,satpoint,sequence_number,timestamp";
+
+  fn write_csv(&self, writer: &mut impl Write) -> Result {
+    fn field<T: ToString>(value: Option<T>) -> String {
+      value.map(|value| value.to_string()).unwrap_or_default()
+    }
+
+    fn list<T: ToString>(values: &[T]) -> String {
+      values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(";")
+    }
+
+    fn escape(field: String) -> String {
+      if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+      } else {
+        field
+      }
+    }
+
+    let fields = [
+      list(&self.charms),
+      field(self.content_length),
+      field(self.content_type.as_ref()),
+      field(self.delegate),
+      self.fee.to_string(),
+      self.height.to_string(),
+      self.id.to_string(),
+      field(self.metaprotocol.as_ref()),
+      self.number.to_string(),
+      list(&self.parents),
+      field(self.sat),
+      self.satpoint.to_string(),
+      self.sequence_number.to_string(),
+      self.timestamp.to_string(),
+    ];
+
+    writeln!(
+      writer,
+      "{}",
+      fields
+        .into_iter()
+        .map(escape)
+        .collect::<Vec<String>>()
+        .join(",")
+    )?;
+
+    Ok(())
+  }
 }
 
 impl Export {
@@ -13,8 +126,79 @@ impl Export {
     let index = Index::open(&settings)?;
 
     index.update()?;
-    index.export(&self.tsv, self.include_addresses)?;
+
+    if let Some(snapshot) = self.snapshot {
+      return Ok(Some(Box::new(index.export_snapshot(&snapshot)?)));
+    }
+
+    if let Some(tsv) = self.tsv {
+      index.export(&tsv, self.include_addresses)?;
+    }
+
+    if let Some(path) = self.inscriptions {
+      let mut writer = BufWriter::new(fs::File::create(&path)?);
+
+      if let Format::Csv = self.format {
+        writeln!(writer, "{}", InscriptionRecord::CSV_HEADER)?;
+      }
+
+      let heights = self.from_height.unwrap_or(0)..=self.to_height.unwrap_or(u32::MAX);
+
+      index.export_inscriptions(heights, |record| {
+        match self.format {
+          Format::Csv => record.write_csv(&mut writer)?,
+          Format::Ndjson => {
+            serde_json::to_writer(&mut writer, &record)?;
+            writeln!(writer)?;
+          }
+        }
+
+        Ok(())
+      })?;
+
+      writer.flush()?;
+    }
 
     Ok(None)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn csv_fields_are_escaped() {
+    let mut buffer = Vec::new();
+
+    InscriptionRecord {
+      charms: vec![Charm::Cursed, Charm::Reinscription],
+      content_length: None,
+      content_type: Some("text/plain, \"quoted\"".into()),
+      delegate: None,
+      fee: 1,
+      height: 2,
+      id: inscription_id(1),
+      metaprotocol: Some("foo\nbar".into()),
+      number: -3,
+      parents: vec![inscription_id(2), inscription_id(3)],
+      sat: Some(Sat(4)),
+      satpoint: satpoint(1, 0),
+      sequence_number: 5,
+      timestamp: 6,
+    }
+    .write_csv(&mut buffer)
+    .unwrap();
+
+    assert_eq!(
+      String::from_utf8(buffer).unwrap(),
+      format!(
+        "cursed;reinscription,,\"text/plain, \"\"quoted\"\"\",,1,2,{},\"foo\nbar\",-3,{};{},4,{},5,6\n",
+        inscription_id(1),
+        inscription_id(2),
+        inscription_id(3),
+        satpoint(1, 0),
+      )
+    );
+  }
+}
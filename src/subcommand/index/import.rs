@@ -0,0 +1,16 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Import {
+  #[arg(help = "Load index from <SNAPSHOT>.")]
+  snapshot: PathBuf,
+}
+
+impl Import {
+  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+    Ok(Some(Box::new(Index::import_snapshot(
+      &settings,
+      &self.snapshot,
+    )?)))
+  }
+}
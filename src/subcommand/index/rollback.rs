@@ -0,0 +1,26 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Rollback {
+  #[arg(long, help = "Roll back index to <HEIGHT>.")]
+  height: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub height: u32,
+  pub reverted: u32,
+}
+
+impl Rollback {
+  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+    let index = Index::open(&settings)?;
+
+    let reverted = index.rollback(self.height)?;
+
+    Ok(Some(Box::new(Output {
+      height: self.height,
+      reverted,
+    })))
+  }
+}
@@ -1,9 +1,29 @@
 use super::*;
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub migrations: Vec<Migrated>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Migrated {
+  pub description: String,
+  pub version: u64,
+}
+
 pub(crate) fn run(settings: Settings) -> SubcommandResult {
   let index = Index::open(&settings)?;
 
   index.update()?;
 
-  Ok(None)
+  Ok(Some(Box::new(Output {
+    migrations: index
+      .migrations()
+      .iter()
+      .map(|migration| Migrated {
+        description: migration.description.into(),
+        version: migration.version,
+      })
+      .collect(),
+  })))
 }
@@ -0,0 +1,30 @@
+use super::*;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub warnings: Vec<String>,
+}
+
+pub(crate) fn run(settings: Settings) -> SubcommandResult {
+  let index = Index::open(&settings)?;
+
+  index.update()?;
+
+  let verification = index.verify()?;
+
+  if !verification.errors.is_empty() {
+    bail!(
+      "index verification failed:\n{}",
+      verification
+        .errors
+        .iter()
+        .map(|error| format!("- {error}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+    );
+  }
+
+  Ok(Some(Box::new(Output {
+    warnings: verification.warnings,
+  })))
+}
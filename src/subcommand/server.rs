@@ -2,26 +2,41 @@ use {
   self::{
     accept_encoding::AcceptEncoding,
     accept_json::AcceptJson,
+    conditional_request::ConditionalRequest,
     error::{OptionExt, ServerError, ServerResult},
+    event_sink::EventSink,
+    event_stream::EventFilter,
+    metrics::RequestMetrics,
+    rate_limiter::{Caller, RateLimiter},
   },
   super::*,
+  crate::index::{
+    event::Event,
+    inscription_filter::{InscriptionFilter, Order},
+    search,
+  },
   crate::templates::{
-    BlockHtml, BlocksHtml, ChildrenHtml, ClockSvg, CollectionsHtml, HomeHtml, InputHtml,
-    InscriptionHtml, InscriptionsBlockHtml, InscriptionsHtml, OutputHtml, PageContent, PageHtml,
-    ParentsHtml, PreviewAudioHtml, PreviewCodeHtml, PreviewFontHtml, PreviewImageHtml,
+    AddressHtml, BlockHtml, BlocksHtml, ChildrenHtml, ClockSvg, CollectionsHtml, HomeHtml,
+    InputHtml, InscriptionHtml, InscriptionsBlockHtml, InscriptionsHtml, OutputHtml, PageContent,
+    PageHtml, ParentsHtml, PreviewAudioHtml, PreviewCodeHtml, PreviewFontHtml, PreviewImageHtml,
     PreviewMarkdownHtml, PreviewModelHtml, PreviewPdfHtml, PreviewTextHtml, PreviewUnknownHtml,
-    PreviewVideoHtml, RangeHtml, RareTxt, RuneHtml, RunesHtml, SatHtml, TransactionHtml,
+    PreviewVideoHtml, RangeHtml, RareTxt, RuneHtml, RunesHtml, SatHtml, SearchHtml,
+    TransactionHtml, UsageHtml,
   },
   axum::{
     body,
     extract::{Extension, Json, Path, Query},
-    http::{header, HeaderValue, StatusCode, Uri},
-    response::{IntoResponse, Redirect, Response},
-    routing::{get, post},
+    handler::Handler,
+    http::{header, HeaderValue, Method, StatusCode, Uri},
+    middleware,
+    response::{
+      sse::{KeepAlive, Sse},
+      IntoResponse, Redirect, Response,
+    },
+    routing::{on, MethodFilter, MethodRouter},
     Router,
   },
   axum_server::Handle,
-  brotli::Decompressor,
   rust_embed::RustEmbed,
   rustls_acme::{
     acme::{LETS_ENCRYPT_PRODUCTION_DIRECTORY, LETS_ENCRYPT_STAGING_DIRECTORY},
@@ -29,11 +44,14 @@ use {
     caches::DirCache,
     AcmeConfig,
   },
-  std::{cmp::Ordering, str, sync::Arc},
+  std::{cmp::Ordering, net::SocketAddr, str, sync::Arc},
   tokio_stream::StreamExt,
   tower_http::{
-    compression::CompressionLayer,
-    cors::{Any, CorsLayer},
+    compression::{
+      predicate::{DefaultPredicate, NotForContentType, Predicate},
+      CompressionLayer,
+    },
+    cors::{self, Any, CorsLayer},
     set_header::SetResponseHeaderLayer,
     validate_request::ValidateRequestHeaderLayer,
   },
@@ -43,10 +61,19 @@ pub(crate) use server_config::ServerConfig;
 
 mod accept_encoding;
 mod accept_json;
+mod codec;
+mod conditional_request;
 mod error;
+pub(crate) mod event_sink;
+mod event_stream;
+mod metrics;
+mod openapi;
 pub mod query;
+mod rate_limiter;
 mod server_config;
 
+const EVENT_STREAM_CAPACITY: usize = 1024;
+
 enum SpawnConfig {
   Https(AxumAcceptor),
   Http,
@@ -58,6 +85,140 @@ struct Search {
   query: String,
 }
 
+#[derive(Default, Deserialize)]
+struct InscriptionsQuery {
+  charm: Option<String>,
+  content_type: Option<String>,
+  max_height: Option<String>,
+  metaprotocol: Option<String>,
+  min_height: Option<String>,
+  order: Option<String>,
+  parent: Option<String>,
+}
+
+impl InscriptionsQuery {
+  fn filter(&self) -> ServerResult<Option<InscriptionFilter>> {
+    let filter = InscriptionFilter {
+      charm: parse_query_param("charm", &self.charm)?,
+      content_type: query_param(&self.content_type).map(str::to_owned),
+      max_height: parse_query_param("max_height", &self.max_height)?,
+      metaprotocol: query_param(&self.metaprotocol).map(str::to_owned),
+      min_height: parse_query_param("min_height", &self.min_height)?,
+      order: parse_query_param("order", &self.order)?.unwrap_or_default(),
+      parent: parse_query_param("parent", &self.parent)?,
+    };
+
+    Ok(
+      (filter.charm.is_some()
+        || filter.content_type.is_some()
+        || filter.max_height.is_some()
+        || filter.metaprotocol.is_some()
+        || filter.min_height.is_some()
+        || filter.order != Order::Newest
+        || filter.parent.is_some())
+      .then_some(filter),
+    )
+  }
+
+  fn query_string(&self) -> String {
+    let params = [
+      ("content_type", &self.content_type),
+      ("charm", &self.charm),
+      ("metaprotocol", &self.metaprotocol),
+      ("parent", &self.parent),
+      ("min_height", &self.min_height),
+      ("max_height", &self.max_height),
+      ("order", &self.order),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| {
+      query_param(value).map(|value| format!("{name}={}", urlencoding::encode(value)))
+    })
+    .collect::<Vec<String>>();
+
+    if params.is_empty() {
+      String::new()
+    } else {
+      format!("?{}", params.join("&"))
+    }
+  }
+}
+
+#[derive(Default, Deserialize)]
+struct SearchQuery {
+  charm: Option<String>,
+  content_type: Option<String>,
+  from_height: Option<String>,
+  page: Option<String>,
+  parent: Option<String>,
+  q: Option<String>,
+  query: Option<String>,
+  to_height: Option<String>,
+}
+
+impl SearchQuery {
+  fn filter(&self) -> ServerResult<search::Filter> {
+    Ok(search::Filter {
+      charm: parse_query_param("charm", &self.charm)?,
+      content_type: query_param(&self.content_type).map(str::to_owned),
+      from_height: parse_query_param("from_height", &self.from_height)?,
+      parent: parse_query_param("parent", &self.parent)?,
+      to_height: parse_query_param("to_height", &self.to_height)?,
+    })
+  }
+
+  fn url(&self, page: u32) -> String {
+    let mut url = format!(
+      "/search?q={}",
+      urlencoding::encode(query_param(&self.q).unwrap_or_default())
+    );
+
+    for (name, value) in [
+      ("content_type", &self.content_type),
+      ("charm", &self.charm),
+      ("from_height", &self.from_height),
+      ("to_height", &self.to_height),
+      ("parent", &self.parent),
+    ] {
+      if let Some(value) = query_param(value) {
+        url.push_str(&format!("&{name}={}", urlencoding::encode(value)));
+      }
+    }
+
+    url.push_str(&format!("&page={page}"));
+
+    url
+  }
+}
+
+pub(super) type Route = (Method, &'static str, MethodRouter<Arc<ServerConfig>>);
+
+fn route<H, T>(method: Method, path: &'static str, handler: H) -> Route
+where
+  H: Handler<T, Arc<ServerConfig>>,
+  T: 'static,
+{
+  let filter = MethodFilter::try_from(method.clone()).unwrap();
+  (method, path, on(filter, handler))
+}
+
+fn query_param(value: &Option<String>) -> Option<&str> {
+  value
+    .as_deref()
+    .map(str::trim)
+    .filter(|value| !value.is_empty())
+}
+
+fn parse_query_param<T: FromStr>(name: &str, value: &Option<String>) -> ServerResult<Option<T>> {
+  query_param(value)
+    .map(|value| {
+      value
+        .parse()
+        .map_err(|_| ServerError::BadRequest(format!("invalid {name}: {value}")))
+    })
+    .transpose()
+}
+
 #[derive(RustEmbed)]
 #[folder = "static"]
 struct StaticAssets;
@@ -98,11 +259,27 @@ pub struct Server {
   pub(crate) csp_origin: Option<String>,
   #[arg(
     long,
-    help = "Decompress encoded content. Currently only supports brotli. Be careful using this on production instances. A decompressed inscription may be arbitrarily large, making decompression a DoS vector."
+    help = "Decompress encoded content. Supports brotli, gzip, and deflate. Content is re-encoded with an encoding the client accepts, if any. Be careful using this on production instances, since decompression is CPU and memory intensive."
   )]
   pub(crate) decompress: bool,
   #[arg(long, help = "Disable JSON API.")]
   pub(crate) disable_json_api: bool,
+  #[arg(
+    long,
+    help = "Append index events to <EVENT_LOG> as newline-delimited JSON."
+  )]
+  pub(crate) event_log: Option<PathBuf>,
+  #[arg(
+    long,
+    help = "Stream live index events as Server-Sent Events from `/events`."
+  )]
+  pub(crate) event_stream: bool,
+  #[arg(
+    long,
+    requires = "event_log",
+    help = "POST each index event in <EVENT_LOG> to <EVENT_WEBHOOK>, retrying until delivered. Delivery progress is saved to <EVENT_LOG>.cursor."
+  )]
+  pub(crate) event_webhook: Option<Url>,
   #[arg(
     long,
     help = "Listen on <HTTP_PORT> for incoming HTTP requests. [default: 80]"
@@ -114,6 +291,13 @@ pub struct Server {
     help = "Listen on <HTTPS_PORT> for incoming HTTPS requests. [default: 443]"
   )]
   pub(crate) https_port: Option<u16>,
+  #[arg(
+    long,
+    default_value_t = 32 * 1024 * 1024,
+    value_name = "BYTES",
+    help = "Refuse to decompress content larger than <BYTES> when decompressed."
+  )]
+  pub(crate) max_decompressed_size: usize,
   #[arg(long, help = "Store ACME TLS certificates in <ACME_CACHE>.")]
   pub(crate) acme_cache: Option<PathBuf>,
   #[arg(long, help = "Provide ACME contact <ACME_CONTACT>.")]
@@ -137,6 +321,8 @@ pub struct Server {
     help = "Poll Bitcoin Core every <POLLING_INTERVAL>."
   )]
   pub(crate) polling_interval: humantime::Duration,
+  #[arg(skip)]
+  pub(crate) event_broadcast: Option<tokio::sync::broadcast::Sender<Event>>,
 }
 
 impl Server {
@@ -173,97 +359,32 @@ impl Server {
         content_proxy: self.content_proxy.clone(),
         csp_origin: self.csp_origin.clone(),
         decompress: self.decompress,
+        max_decompressed_size: self.max_decompressed_size,
         domain: acme_domains.first().cloned(),
+        event_broadcast: self.event_broadcast.clone(),
         index_sats: index.has_sat_index(),
         json_api_enabled: !self.disable_json_api,
       });
 
-      let router = Router::new()
-        .route("/", get(Self::home))
-        .route("/block/:query", get(Self::block))
-        .route("/blockcount", get(Self::block_count))
-        .route("/blockhash", get(Self::block_hash))
-        .route("/blockhash/:height", get(Self::block_hash_from_height))
-        .route("/blockheight", get(Self::block_height))
-        .route("/blocks", get(Self::blocks))
-        .route("/blocktime", get(Self::block_time))
-        .route("/bounties", get(Self::bounties))
-        .route("/children/:inscription_id", get(Self::children))
-        .route(
-          "/children/:inscription_id/:page",
-          get(Self::children_paginated),
-        )
-        .route("/clock", get(Self::clock))
-        .route("/collections", get(Self::collections))
-        .route("/collections/:page", get(Self::collections_paginated))
-        .route("/content/:inscription_id", get(Self::content))
-        .route("/faq", get(Self::faq))
-        .route("/favicon.ico", get(Self::favicon))
-        .route("/feed.xml", get(Self::feed))
-        .route("/input/:block/:transaction/:input", get(Self::input))
-        .route("/inscription/:inscription_query", get(Self::inscription))
-        .route("/inscriptions", get(Self::inscriptions))
-        .route("/inscriptions", post(Self::inscriptions_json))
-        .route("/inscriptions/:page", get(Self::inscriptions_paginated))
-        .route(
-          "/inscriptions/block/:height",
-          get(Self::inscriptions_in_block),
-        )
-        .route(
-          "/inscriptions/block/:height/:page",
-          get(Self::inscriptions_in_block_paginated),
-        )
-        .route("/install.sh", get(Self::install_script))
-        .route("/ordinal/:sat", get(Self::ordinal))
-        .route("/output/:output", get(Self::output))
-        .route("/outputs", post(Self::outputs))
-        .route("/parents/:inscription_id", get(Self::parents))
-        .route(
-          "/parents/:inscription_id/:page",
-          get(Self::parents_paginated),
-        )
-        .route("/preview/:inscription_id", get(Self::preview))
-        .route("/r/blockhash", get(Self::block_hash_json))
-        .route(
-          "/r/blockhash/:height",
-          get(Self::block_hash_from_height_json),
-        )
-        .route("/r/blockheight", get(Self::block_height))
-        .route("/r/blocktime", get(Self::block_time))
-        .route("/r/blockinfo/:query", get(Self::block_info))
-        .route(
-          "/r/inscription/:inscription_id",
-          get(Self::inscription_recursive),
-        )
-        .route("/r/children/:inscription_id", get(Self::children_recursive))
-        .route(
-          "/r/children/:inscription_id/:page",
-          get(Self::children_recursive_paginated),
-        )
-        .route("/r/metadata/:inscription_id", get(Self::metadata))
-        .route("/r/sat/:sat_number", get(Self::sat_inscriptions))
-        .route(
-          "/r/sat/:sat_number/:page",
-          get(Self::sat_inscriptions_paginated),
-        )
-        .route(
-          "/r/sat/:sat_number/at/:index",
-          get(Self::sat_inscription_at_index),
-        )
-        .route("/range/:start/:end", get(Self::range))
-        .route("/rare.txt", get(Self::rare_txt))
-        .route("/rune/:rune", get(Self::rune))
-        .route("/runes", get(Self::runes))
-        .route("/runes/:page", get(Self::runes_paginated))
-        .route("/runes/balances", get(Self::runes_balances))
-        .route("/sat/:sat", get(Self::sat))
-        .route("/search", get(Self::search_by_query))
-        .route("/search/*query", get(Self::search_by_path))
-        .route("/static/*path", get(Self::static_asset))
-        .route("/status", get(Self::status))
-        .route("/tx/:txid", get(Self::transaction))
-        .route("/update", get(Self::update))
-        .fallback(Self::fallback)
+      let router = Self::routes()
+        .into_iter()
+        .fold(Router::new(), |router, (_method, path, method_router)| {
+          router.route(path, method_router)
+        })
+        .route_layer(middleware::from_fn(RequestMetrics::middleware))
+        .fallback(Self::fallback);
+
+      let rate_limiter = Arc::new(RateLimiter::new(&settings));
+
+      let router = if rate_limiter.is_enabled() {
+        router.layer(middleware::from_fn(RateLimiter::middleware))
+      } else {
+        router
+      };
+
+      let router = router
+        .layer(Extension(rate_limiter))
+        .layer(Extension(Arc::new(RequestMetrics::default())))
         .layer(Extension(index))
         .layer(Extension(server_config.clone()))
         .layer(Extension(settings.clone()))
@@ -278,9 +399,29 @@ impl Server {
         .layer(
           CorsLayer::new()
             .allow_methods([http::Method::GET])
-            .allow_origin(Any),
+            .allow_origin(Any)
+            // the CORS layer replaces any `Vary` header set by handlers, and
+            // responses may be compressed or transcoded per `Accept-Encoding`
+            .vary(
+              cors::preflight_request_headers()
+                .chain([header::ACCEPT_ENCODING])
+                .collect::<Vec<header::HeaderName>>(),
+            ),
+        )
+        .layer(
+          CompressionLayer::new().compress_when(
+            DefaultPredicate::new()
+              .and(NotForContentType::const_new("text/event-stream"))
+              .and(NotForContentType::const_new("audio/"))
+              .and(NotForContentType::const_new("video/"))
+              // byte ranges refer to the uncompressed body
+              .and(
+                |status: StatusCode, _version, _headers: &HeaderMap, _extensions: &http::Extensions| {
+                  status != StatusCode::PARTIAL_CONTENT
+                },
+              ),
+          ),
         )
-        .layer(CompressionLayer::new())
         .with_state(server_config);
 
       let router = if let Some((username, password)) = settings.credentials() {
@@ -342,6 +483,159 @@ impl Server {
     })
   }
 
+  /// Routes served by the server, which are also used to check that every
+  /// JSON route is documented in the OpenAPI document.
+  pub(super) fn routes() -> Vec<Route> {
+    vec![
+      route(Method::GET, "/", Self::home),
+      route(Method::GET, "/address/:address", Self::address),
+      route(Method::GET, "/block/:query", Self::block),
+      route(Method::GET, "/blockcount", Self::block_count),
+      route(Method::GET, "/blockhash", Self::block_hash),
+      route(
+        Method::GET,
+        "/blockhash/:height",
+        Self::block_hash_from_height,
+      ),
+      route(Method::GET, "/blockheight", Self::block_height),
+      route(Method::GET, "/blocks", Self::blocks),
+      route(Method::GET, "/blocktime", Self::block_time),
+      route(Method::GET, "/bounties", Self::bounties),
+      route(Method::GET, "/children/:inscription_id", Self::children),
+      route(
+        Method::GET,
+        "/children/:inscription_id/:page",
+        Self::children_paginated,
+      ),
+      route(Method::GET, "/clock", Self::clock),
+      route(Method::GET, "/collections", Self::collections),
+      route(
+        Method::GET,
+        "/collections/:page",
+        Self::collections_paginated,
+      ),
+      route(Method::GET, "/content/:inscription_id", Self::content),
+      route(Method::GET, "/events", Self::events),
+      route(Method::GET, "/faq", Self::faq),
+      route(Method::GET, "/favicon.ico", Self::favicon),
+      route(Method::GET, "/feed.xml", Self::feed),
+      route(
+        Method::GET,
+        "/input/:block/:transaction/:input",
+        Self::input,
+      ),
+      route(
+        Method::GET,
+        "/inscription/:inscription_query",
+        Self::inscription,
+      ),
+      route(Method::GET, "/inscriptions", Self::inscriptions),
+      route(Method::POST, "/inscriptions", Self::inscriptions_json),
+      route(
+        Method::GET,
+        "/inscriptions/:page",
+        Self::inscriptions_paginated,
+      ),
+      route(
+        Method::GET,
+        "/inscriptions/block/:height",
+        Self::inscriptions_in_block,
+      ),
+      route(
+        Method::GET,
+        "/inscriptions/block/:height/:page",
+        Self::inscriptions_in_block_paginated,
+      ),
+      route(Method::GET, "/install.sh", Self::install_script),
+      route(Method::GET, "/metrics", Self::metrics),
+      route(Method::GET, "/openapi.json", Self::openapi),
+      route(Method::GET, "/ordinal/:sat", Self::ordinal),
+      route(Method::GET, "/output/:output", Self::output),
+      route(Method::POST, "/outputs", Self::outputs),
+      route(Method::GET, "/parents/:inscription_id", Self::parents),
+      route(
+        Method::GET,
+        "/parents/:inscription_id/:page",
+        Self::parents_paginated,
+      ),
+      route(Method::GET, "/preview/:inscription_id", Self::preview),
+      route(Method::GET, "/r/blockhash", Self::block_hash_json),
+      route(
+        Method::GET,
+        "/r/blockhash/:height",
+        Self::block_hash_from_height_json,
+      ),
+      route(Method::GET, "/r/blockheight", Self::block_height),
+      route(Method::GET, "/r/blocktime", Self::block_time),
+      route(Method::GET, "/r/blockinfo/:query", Self::block_info),
+      route(
+        Method::GET,
+        "/r/inscription/:inscription_id",
+        Self::inscription_recursive,
+      ),
+      route(
+        Method::GET,
+        "/r/children/:inscription_id",
+        Self::children_recursive,
+      ),
+      route(
+        Method::GET,
+        "/r/children/:inscription_id/:page",
+        Self::children_recursive_paginated,
+      ),
+      route(Method::GET, "/r/metadata/:inscription_id", Self::metadata),
+      route(Method::GET, "/r/output/:outpoint", Self::output_recursive),
+      route(Method::GET, "/r/rune/:rune", Self::rune_recursive),
+      route(
+        Method::GET,
+        "/r/rune/:rune/balance/:outpoint",
+        Self::rune_balance_recursive,
+      ),
+      route(
+        Method::GET,
+        "/r/rune/:rune/balances",
+        Self::rune_balances_recursive,
+      ),
+      route(
+        Method::GET,
+        "/r/rune/:rune/balances/:page",
+        Self::rune_balances_recursive_paginated,
+      ),
+      route(Method::GET, "/r/sat/:sat_number", Self::sat_inscriptions),
+      route(
+        Method::GET,
+        "/r/sat/:sat_number/:page",
+        Self::sat_inscriptions_paginated,
+      ),
+      route(
+        Method::GET,
+        "/r/sat/:sat_number/at/:index",
+        Self::sat_inscription_at_index,
+      ),
+      route(Method::GET, "/r/sat/:sat_number/info", Self::sat_recursive),
+      route(Method::GET, "/r/tx/:txid", Self::transaction_recursive),
+      route(
+        Method::GET,
+        "/r/undelegated-content/:inscription_id",
+        Self::undelegated_content,
+      ),
+      route(Method::GET, "/range/:start/:end", Self::range),
+      route(Method::GET, "/rare.txt", Self::rare_txt),
+      route(Method::GET, "/rune/:rune", Self::rune),
+      route(Method::GET, "/runes", Self::runes),
+      route(Method::GET, "/runes/:page", Self::runes_paginated),
+      route(Method::GET, "/runes/balances", Self::runes_balances),
+      route(Method::GET, "/sat/:sat", Self::sat),
+      route(Method::GET, "/search", Self::search_by_query),
+      route(Method::GET, "/search/*query", Self::search_by_path),
+      route(Method::GET, "/static/*path", Self::static_asset),
+      route(Method::GET, "/status", Self::status),
+      route(Method::GET, "/tx/:txid", Self::transaction),
+      route(Method::GET, "/update", Self::update),
+      route(Method::GET, "/usage", Self::usage),
+    ]
+  }
+
   fn spawn(
     &self,
     settings: &Settings,
@@ -382,7 +676,7 @@ impl Server {
           axum_server::Server::bind(addr)
             .handle(handle)
             .acceptor(acceptor)
-            .serve(router.into_make_service())
+            .serve(router.into_make_service_with_connect_info::<SocketAddr>())
             .await
         }
         SpawnConfig::Redirect(destination) => {
@@ -399,7 +693,7 @@ impl Server {
         SpawnConfig::Http => {
           axum_server::Server::bind(addr)
             .handle(handle)
-            .serve(router.into_make_service())
+            .serve(router.into_make_service_with_connect_info::<SocketAddr>())
             .await
         }
       }
@@ -413,6 +707,24 @@ impl Server {
     }
   }
 
+  pub(crate) fn event_sender(&mut self) -> Result<Option<tokio::sync::mpsc::Sender<Event>>> {
+    if self.event_stream {
+      self.event_broadcast = Some(tokio::sync::broadcast::channel(EVENT_STREAM_CAPACITY).0);
+    }
+
+    if self.event_log.is_none() && self.event_broadcast.is_none() {
+      return Ok(None);
+    }
+
+    EventSink {
+      broadcast: self.event_broadcast.clone(),
+      log: self.event_log.clone(),
+      webhook: self.event_webhook.clone(),
+    }
+    .spawn()
+    .map(Some)
+  }
+
   fn acme_domains(&self) -> Result<Vec<String>> {
     if !self.acme_domain.is_empty() {
       Ok(self.acme_domain.clone())
@@ -574,6 +886,67 @@ impl Server {
     Redirect::to(&format!("/sat/{sat}"))
   }
 
+  async fn address(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(DeserializeFromStr(address)): Path<DeserializeFromStr<Address<NetworkUnchecked>>>,
+    AcceptJson(accept_json): AcceptJson,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      if !index.has_address_index() {
+        return Err(ServerError::NotFound(
+          "this server has no address index".to_string(),
+        ));
+      }
+
+      let address = address
+        .require_network(server_config.chain.network())
+        .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+      let mut outputs = Vec::new();
+      let mut inscriptions = Vec::new();
+      let mut runes_balances = BTreeMap::<SpacedRune, Pile>::new();
+      let mut sat_balance = 0;
+
+      for (outpoint, txout) in index.get_address_info(&address)? {
+        outputs.push(outpoint);
+
+        inscriptions.extend(index.get_inscriptions_on_output(outpoint)?);
+
+        for (spaced_rune, pile) in index.get_rune_balances_for_outpoint(outpoint)? {
+          runes_balances
+            .entry(spaced_rune)
+            .and_modify(|balance| balance.amount += pile.amount)
+            .or_insert(pile);
+        }
+
+        sat_balance += txout.value;
+      }
+
+      let runes_balances = runes_balances.into_iter().collect();
+
+      Ok(if accept_json {
+        Json(api::AddressInfo {
+          inscriptions,
+          outputs,
+          runes_balances,
+          sat_balance,
+        })
+        .into_response()
+      } else {
+        AddressHtml {
+          address,
+          inscriptions,
+          outputs,
+          runes_balances,
+          sat_balance,
+        }
+        .page(server_config)
+        .into_response()
+      })
+    })
+  }
+
   async fn output(
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
@@ -654,19 +1027,7 @@ impl Server {
         ));
       }
 
-      let rune = match rune_query {
-        query::Rune::Spaced(spaced_rune) => spaced_rune.rune,
-        query::Rune::Id(rune_id) => index
-          .get_rune_by_id(rune_id)?
-          .ok_or_not_found(|| format!("rune {rune_id}"))?,
-        query::Rune::Number(number) => index
-          .get_rune_by_number(usize::try_from(number).unwrap())?
-          .ok_or_not_found(|| format!("rune number {number}"))?,
-      };
-
-      let (id, entry, parent) = index
-        .rune(rune)?
-        .ok_or_not_found(|| format!("rune {rune}"))?;
+      let (id, entry, parent) = Self::rune_by_query(&index, rune_query)?;
 
       let block_height = index.block_height()?.unwrap_or(Height(0));
 
@@ -693,6 +1054,23 @@ impl Server {
     })
   }
 
+  fn rune_by_query(
+    index: &Index,
+    rune_query: query::Rune,
+  ) -> ServerResult<(RuneId, RuneEntry, Option<InscriptionId>)> {
+    let rune = match rune_query {
+      query::Rune::Spaced(spaced_rune) => spaced_rune.rune,
+      query::Rune::Id(rune_id) => index
+        .get_rune_by_id(rune_id)?
+        .ok_or_not_found(|| format!("rune {rune_id}"))?,
+      query::Rune::Number(number) => index
+        .get_rune_by_number(usize::try_from(number).unwrap())?
+        .ok_or_not_found(|| format!("rune number {number}"))?,
+    };
+
+    index.rune(rune)?.ok_or_not_found(|| format!("rune {rune}"))
+  }
+
   async fn runes(
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
@@ -904,6 +1282,22 @@ impl Server {
     })
   }
 
+  async fn events(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Query(filter): Query<EventFilter>,
+  ) -> ServerResult {
+    let event_broadcast = server_config
+      .event_broadcast
+      .as_ref()
+      .ok_or_else(|| ServerError::NotFound("this server does not stream events".into()))?;
+
+    Ok(
+      Sse::new(event_stream::stream(event_broadcast.subscribe(), filter))
+        .keep_alive(KeepAlive::default())
+        .into_response(),
+    )
+  }
+
   async fn update(
     Extension(settings): Extension<Arc<Settings>>,
     Extension(index): Extension<Arc<Index>>,
@@ -938,54 +1332,56 @@ impl Server {
     Path(inscription_id): Path<InscriptionId>,
   ) -> ServerResult {
     task::block_in_place(|| {
-      let inscription = index
-        .get_inscription_by_id(inscription_id)?
-        .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+      Ok(Json(Self::inscription_recursive_info(&index, inscription_id)?).into_response())
+    })
+  }
 
-      let entry = index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+  fn inscription_recursive_info(
+    index: &Index,
+    inscription_id: InscriptionId,
+  ) -> ServerResult<api::InscriptionRecursive> {
+    let inscription = index
+      .get_inscription_by_id(inscription_id)?
+      .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
 
-      let satpoint = index
-        .get_inscription_satpoint_by_id(inscription_id)
-        .ok()
-        .flatten()
-        .unwrap();
+    let entry = index
+      .get_inscription_entry(inscription_id)
+      .unwrap()
+      .unwrap();
 
-      let output = if satpoint.outpoint == unbound_outpoint() {
-        None
-      } else {
-        Some(
-          index
-            .get_transaction(satpoint.outpoint.txid)?
-            .ok_or_not_found(|| format!("inscription {inscription_id} current transaction"))?
-            .output
-            .into_iter()
-            .nth(satpoint.outpoint.vout.try_into().unwrap())
-            .ok_or_not_found(|| {
-              format!("inscription {inscription_id} current transaction output")
-            })?,
-        )
-      };
+    let satpoint = index
+      .get_inscription_satpoint_by_id(inscription_id)
+      .ok()
+      .flatten()
+      .unwrap();
 
-      Ok(
-        Json(api::InscriptionRecursive {
-          charms: Charm::charms(entry.charms),
-          content_type: inscription.content_type().map(|s| s.to_string()),
-          content_length: inscription.content_length(),
-          fee: entry.fee,
-          height: entry.height,
-          id: inscription_id,
-          number: entry.inscription_number,
-          output: satpoint.outpoint,
-          value: output.as_ref().map(|o| o.value),
-          sat: entry.sat,
-          satpoint,
-          timestamp: timestamp(entry.timestamp.into()).timestamp(),
-        })
-        .into_response(),
+    let output = if satpoint.outpoint == unbound_outpoint() {
+      None
+    } else {
+      Some(
+        index
+          .get_transaction(satpoint.outpoint.txid)?
+          .ok_or_not_found(|| format!("inscription {inscription_id} current transaction"))?
+          .output
+          .into_iter()
+          .nth(satpoint.outpoint.vout.try_into().unwrap())
+          .ok_or_not_found(|| format!("inscription {inscription_id} current transaction output"))?,
       )
+    };
+
+    Ok(api::InscriptionRecursive {
+      charms: Charm::charms(entry.charms),
+      content_type: inscription.content_type().map(|s| s.to_string()),
+      content_length: inscription.content_length(),
+      fee: entry.fee,
+      height: entry.height,
+      id: inscription_id,
+      number: entry.inscription_number,
+      output: satpoint.outpoint,
+      value: output.as_ref().map(|o| o.value),
+      sat: entry.sat,
+      satpoint,
+      timestamp: timestamp(entry.timestamp.into()).timestamp(),
     })
   }
 
@@ -1003,11 +1399,99 @@ impl Server {
     })
   }
 
+  async fn metrics(
+    Extension(index): Extension<Arc<Index>>,
+    Extension(request_metrics): Extension<Arc<RequestMetrics>>,
+    caller: Option<Extension<Caller>>,
+  ) -> ServerResult {
+    if !caller.is_some_and(|Extension(caller)| caller.is_admin()) {
+      return Err(ServerError::Forbidden("admin API key required".into()));
+    }
+
+    task::block_in_place(|| {
+      Ok(
+        (
+          [(header::CONTENT_TYPE, metrics::CONTENT_TYPE)],
+          metrics::render(&index, &request_metrics)?,
+        )
+          .into_response(),
+      )
+    })
+  }
+
+  async fn openapi() -> Json<serde_json::Value> {
+    Json(openapi::document())
+  }
+
+  async fn usage(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(rate_limiter): Extension<Arc<RateLimiter>>,
+    caller: Option<Extension<Caller>>,
+    AcceptJson(accept_json): AcceptJson,
+  ) -> ServerResult {
+    if !caller.is_some_and(|Extension(caller)| caller.is_admin()) {
+      return Err(ServerError::Forbidden("admin API key required".into()));
+    }
+
+    Ok(if accept_json {
+      Json(rate_limiter.usage()).into_response()
+    } else {
+      rate_limiter.usage().page(server_config).into_response()
+    })
+  }
+
   async fn search_by_query(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Query(search): Query<Search>,
-  ) -> ServerResult<Redirect> {
-    Self::search(index, search.query).await
+    AcceptJson(accept_json): AcceptJson,
+    Query(search): Query<SearchQuery>,
+  ) -> ServerResult {
+    if let Some(query) = search.query {
+      return Ok(Self::search(index, query).await?.into_response());
+    }
+
+    task::block_in_place(|| {
+      if !index.has_search_index() {
+        return Err(ServerError::NotFound(
+          "this server has no search index".into(),
+        ));
+      }
+
+      let Some(query) = query_param(&search.q) else {
+        return Err(ServerError::BadRequest("empty search query".into()));
+      };
+
+      let filter = search.filter()?;
+
+      let page_index = parse_query_param::<u32>("page", &search.page)?.unwrap_or_default();
+
+      let (inscriptions, more) = index.search(
+        query,
+        &filter,
+        100,
+        page_index.try_into().unwrap_or(usize::MAX),
+      )?;
+
+      Ok(if accept_json {
+        Json(api::Inscriptions {
+          ids: inscriptions,
+          more,
+          page_index,
+        })
+        .into_response()
+      } else {
+        SearchHtml {
+          inscriptions,
+          next: more.then(|| search.url(page_index + 1)),
+          prev: page_index
+            .checked_sub(1)
+            .map(|page_index| search.url(page_index)),
+          query: query.into(),
+        }
+        .page(server_config)
+        .into_response()
+      })
+    })
   }
 
   async fn search_by_path(
@@ -1045,6 +1529,8 @@ impl Server {
         let rune = index.get_rune_by_id(id)?.ok_or_not_found(|| "rune ID")?;
 
         Ok(Redirect::to(&format!("/rune/{rune}")))
+      } else if re::ADDRESS.is_match(query) {
+        Ok(Redirect::to(&format!("/address/{query}")))
       } else {
         Ok(Redirect::to(&format!("/sat/{query}")))
       }
@@ -1323,6 +1809,7 @@ impl Server {
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Path(inscription_id): Path<InscriptionId>,
     accept_encoding: AcceptEncoding,
+    conditional_request: ConditionalRequest,
   ) -> ServerResult {
     task::block_in_place(|| {
       if settings.is_hidden(inscription_id) {
@@ -1340,26 +1827,90 @@ impl Server {
         };
       };
 
+      let mut etag = inscription_id.to_string();
+
       if let Some(delegate) = inscription.delegate() {
         inscription = index
           .get_inscription_by_id(delegate)?
-          .ok_or_not_found(|| format!("delegate {inscription_id}"))?
+          .ok_or_not_found(|| format!("delegate {inscription_id}"))?;
+
+        etag = format!("{etag}:{delegate}");
       }
 
-      Ok(
-        Self::content_response(inscription, accept_encoding, &server_config)?
-          .ok_or_not_found(|| format!("inscription {inscription_id} content"))?
-          .into_response(),
+      Self::conditional_content_response(
+        inscription_id,
+        inscription,
+        etag,
+        accept_encoding,
+        conditional_request,
+        &server_config,
       )
     })
   }
 
-  fn content_response(
+  async fn undelegated_content(
+    Extension(index): Extension<Arc<Index>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Path(inscription_id): Path<InscriptionId>,
+    accept_encoding: AcceptEncoding,
+    conditional_request: ConditionalRequest,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      if settings.is_hidden(inscription_id) {
+        return Ok(PreviewUnknownHtml.into_response());
+      }
+
+      let inscription = index
+        .get_inscription_by_id(inscription_id)?
+        .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+      Self::conditional_content_response(
+        inscription_id,
+        inscription,
+        inscription_id.to_string(),
+        accept_encoding,
+        conditional_request,
+        &server_config,
+      )
+    })
+  }
+
+  fn conditional_content_response(
+    inscription_id: InscriptionId,
     inscription: Inscription,
+    mut etag: String,
     accept_encoding: AcceptEncoding,
+    conditional_request: ConditionalRequest,
     server_config: &ServerConfig,
-  ) -> ServerResult<Option<(HeaderMap, Vec<u8>)>> {
-    let mut headers = HeaderMap::new();
+  ) -> ServerResult {
+    let content_encoding = inscription.content_encoding();
+
+    let (headers, body) = Self::content_response(inscription, accept_encoding, server_config)?
+      .ok_or_not_found(|| format!("inscription {inscription_id} content"))?;
+
+    // decompressed or re-encoded content is a different representation, and
+    // so needs a different strong etag
+    if let Some(content_encoding) = content_encoding {
+      match headers.get(header::CONTENT_ENCODING) {
+        Some(encoding) if *encoding == content_encoding => {}
+        Some(encoding) => {
+          etag.push(':');
+          etag.push_str(encoding.to_str().unwrap_or_default());
+        }
+        None => etag.push_str(":identity"),
+      }
+    }
+
+    Ok(conditional_request.respond(&format!("\"{etag}\""), headers, body))
+  }
+
+  fn content_response(
+    inscription: Inscription,
+    accept_encoding: AcceptEncoding,
+    server_config: &ServerConfig,
+  ) -> ServerResult<Option<(HeaderMap, Vec<u8>)>> {
+    let mut headers = HeaderMap::new();
 
     match &server_config.csp_origin {
       None => {
@@ -1397,18 +1948,43 @@ impl Server {
     if let Some(content_encoding) = inscription.content_encoding() {
       if accept_encoding.is_acceptable(&content_encoding) {
         headers.insert(header::CONTENT_ENCODING, content_encoding);
-      } else if server_config.decompress && content_encoding == "br" {
+      } else if server_config.decompress {
         let Some(body) = inscription.into_body() else {
           return Ok(None);
         };
 
-        let mut decompressed = Vec::new();
+        let encoding = content_encoding.to_str().unwrap_or_default();
+
+        let decoded = match codec::decode(encoding, &body, server_config.max_decompressed_size) {
+          codec::Decoded::Body(decoded) => decoded,
+          codec::Decoded::Corrupt(err) => {
+            return Err(ServerError::UnprocessableContent(format!(
+              "inscription content could not be decoded with `{encoding}`: {err}"
+            )))
+          }
+          codec::Decoded::TooLarge => {
+            return Err(ServerError::UnprocessableContent(format!(
+              "decoded inscription content is larger than {} bytes",
+              server_config.max_decompressed_size
+            )))
+          }
+          codec::Decoded::Unsupported => {
+            return Err(ServerError::NotAcceptable {
+              accept_encoding,
+              content_encoding,
+            })
+          }
+        };
 
-        Decompressor::new(body.as_slice(), 4096)
-          .read_to_end(&mut decompressed)
-          .map_err(|err| ServerError::Internal(err.into()))?;
+        if let Some(encoding) = codec::ENCODINGS
+          .into_iter()
+          .find(|encoding| accept_encoding.is_acceptable(&HeaderValue::from_static(encoding)))
+        {
+          headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+          return Ok(Some((headers, codec::encode(encoding, &decoded)?)));
+        }
 
-        return Ok(Some((headers, decompressed)));
+        return Ok(Some((headers, decoded)));
       } else {
         return Err(ServerError::NotAcceptable {
           accept_encoding,
@@ -1682,12 +2258,14 @@ impl Server {
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
     accept_json: AcceptJson,
+    query: Query<InscriptionsQuery>,
   ) -> ServerResult {
     Self::inscriptions_paginated(
       Extension(server_config),
       Extension(index),
       Path(0),
       accept_json,
+      query,
     )
     .await
   }
@@ -1697,9 +2275,26 @@ impl Server {
     Extension(index): Extension<Arc<Index>>,
     Path(page_index): Path<u32>,
     AcceptJson(accept_json): AcceptJson,
+    Query(query): Query<InscriptionsQuery>,
   ) -> ServerResult {
     task::block_in_place(|| {
-      let (inscriptions, more) = index.get_inscriptions_paginated(100, page_index)?;
+      let (inscriptions, more) = match query.filter()? {
+        Some(filter) => {
+          if filter.requires_filter_index() && !index.has_inscription_filter_index() {
+            return Err(ServerError::NotFound(
+              "this index must be rebuilt to filter inscriptions by charm, content type, or metaprotocol"
+                .into(),
+            ));
+          }
+
+          index.get_inscriptions_filtered(
+            &filter,
+            100,
+            page_index.try_into().unwrap_or(usize::MAX),
+          )?
+        }
+        None => index.get_inscriptions_paginated(100, page_index)?,
+      };
 
       let prev = page_index.checked_sub(1);
 
@@ -1717,6 +2312,7 @@ impl Server {
           inscriptions,
           next,
           prev,
+          query: query.query_string(),
         }
         .page(server_config)
         .into_response()
@@ -1869,6 +2465,190 @@ impl Server {
     })
   }
 
+  async fn sat_recursive(
+    Extension(index): Extension<Arc<Index>>,
+    Path(DeserializeFromStr(sat)): Path<DeserializeFromStr<Sat>>,
+  ) -> ServerResult<Json<api::SatRecursive>> {
+    task::block_in_place(|| {
+      if !index.has_sat_index() {
+        return Err(ServerError::NotFound(
+          "this server has no sat index".to_string(),
+        ));
+      }
+
+      let inscriptions = index
+        .get_inscription_ids_by_sat(sat)?
+        .into_iter()
+        .map(|inscription_id| Self::inscription_recursive_info(&index, inscription_id))
+        .collect::<ServerResult<Vec<api::InscriptionRecursive>>>()?;
+
+      let satpoint = match index.rare_sat_satpoint(sat)? {
+        Some(satpoint) => Some(satpoint),
+        None => inscriptions.first().map(|inscription| inscription.satpoint),
+      };
+
+      Ok(Json(api::SatRecursive {
+        block: sat.height().0,
+        charms: Charm::charms(sat.charms()),
+        inscriptions,
+        name: sat.name(),
+        number: sat.0,
+        rarity: sat.rarity(),
+        satpoint,
+      }))
+    })
+  }
+
+  async fn output_recursive(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(outpoint): Path<OutPoint>,
+  ) -> ServerResult<Json<api::OutputRecursive>> {
+    task::block_in_place(|| {
+      // only serve outputs of transactions confirmed in indexed blocks, so
+      // that responses do not depend on the mempool
+      let height = index
+        .get_transaction_height(outpoint.txid)?
+        .ok_or_not_found(|| format!("output {outpoint}"))?;
+
+      let (output_info, txout) = index
+        .get_output_info(outpoint)?
+        .ok_or_not_found(|| format!("output {outpoint}"))?;
+
+      Ok(Json(api::OutputRecursive {
+        address: server_config
+          .chain
+          .address_from_script(&txout.script_pubkey)
+          .ok()
+          .map(|address| uncheck(&address)),
+        height,
+        inscriptions: output_info.inscriptions,
+        runes: output_info.runes,
+        sat_ranges: output_info.sat_ranges,
+        script_pubkey: output_info.script_pubkey,
+        value: txout.value,
+      }))
+    })
+  }
+
+  async fn rune_recursive(
+    Extension(index): Extension<Arc<Index>>,
+    Path(DeserializeFromStr(rune_query)): Path<DeserializeFromStr<query::Rune>>,
+  ) -> ServerResult<Json<api::RuneRecursive>> {
+    task::block_in_place(|| {
+      if !index.has_rune_index() {
+        return Err(ServerError::NotFound(
+          "this server has no rune index".to_string(),
+        ));
+      }
+
+      let (id, entry, _parent) = Self::rune_by_query(&index, rune_query)?;
+
+      let block_height = index.block_height()?.unwrap_or(Height(0));
+
+      Ok(Json(api::RuneRecursive {
+        block: entry.block,
+        burned: entry.burned,
+        divisibility: entry.divisibility,
+        etching: entry.etching,
+        id,
+        mintable: entry.mintable((block_height.n() + 1).into()).is_ok(),
+        mints: entry.mints,
+        number: entry.number,
+        premine: entry.premine,
+        spaced_rune: entry.spaced_rune,
+        supply: entry.supply(),
+        symbol: entry.symbol,
+        terms: entry.terms,
+        timestamp: entry.timestamp,
+        turbo: entry.turbo,
+      }))
+    })
+  }
+
+  async fn rune_balance_recursive(
+    Extension(index): Extension<Arc<Index>>,
+    Path((DeserializeFromStr(rune_query), outpoint)): Path<(
+      DeserializeFromStr<query::Rune>,
+      OutPoint,
+    )>,
+  ) -> ServerResult<Json<Pile>> {
+    task::block_in_place(|| {
+      if !index.has_rune_index() {
+        return Err(ServerError::NotFound(
+          "this server has no rune index".to_string(),
+        ));
+      }
+
+      let (_id, entry, _parent) = Self::rune_by_query(&index, rune_query)?;
+
+      let amount = index
+        .get_rune_balances_for_outpoint(outpoint)?
+        .into_iter()
+        .find(|(spaced_rune, _pile)| *spaced_rune == entry.spaced_rune)
+        .map(|(_spaced_rune, pile)| pile.amount)
+        .unwrap_or_default();
+
+      Ok(Json(entry.pile(amount)))
+    })
+  }
+
+  async fn rune_balances_recursive(
+    Extension(index): Extension<Arc<Index>>,
+    Path(rune_query): Path<DeserializeFromStr<query::Rune>>,
+  ) -> ServerResult<Json<api::RuneBalancesRecursive>> {
+    Self::rune_balances_recursive_paginated(Extension(index), Path((rune_query, 0))).await
+  }
+
+  async fn rune_balances_recursive_paginated(
+    Extension(index): Extension<Arc<Index>>,
+    Path((DeserializeFromStr(rune_query), page)): Path<(DeserializeFromStr<query::Rune>, usize)>,
+  ) -> ServerResult<Json<api::RuneBalancesRecursive>> {
+    task::block_in_place(|| {
+      if !index.has_rune_index() {
+        return Err(ServerError::NotFound(
+          "this server has no rune index".to_string(),
+        ));
+      }
+
+      let (id, entry, _parent) = Self::rune_by_query(&index, rune_query)?;
+
+      let (balances, more) = index.get_rune_balances_paginated(id, 100, page)?;
+
+      Ok(Json(api::RuneBalancesRecursive {
+        balances: balances
+          .into_iter()
+          .map(|(outpoint, amount)| (outpoint, entry.pile(amount)))
+          .collect(),
+        more,
+        page,
+      }))
+    })
+  }
+
+  async fn transaction_recursive(
+    Extension(index): Extension<Arc<Index>>,
+    Path(txid): Path<Txid>,
+  ) -> ServerResult<Json<api::TransactionRecursive>> {
+    task::block_in_place(|| {
+      // only serve transactions confirmed in indexed blocks, so that
+      // inscriptions seeded from them render the same way everywhere
+      let height = index
+        .get_transaction_height(txid)?
+        .ok_or_not_found(|| format!("transaction {txid}"))?;
+
+      let transaction = index
+        .get_transaction(txid)?
+        .ok_or_not_found(|| format!("transaction {txid}"))?;
+
+      Ok(Json(api::TransactionRecursive {
+        height,
+        hex: consensus::encode::serialize_hex(&transaction),
+        transaction,
+      }))
+    })
+  }
+
   async fn redirect_http_to_https(
     Extension(mut destination): Extension<String>,
     uri: Uri,
@@ -2007,7 +2787,7 @@ mod tests {
 
       let arguments = Arguments::try_parse_from(args).unwrap();
 
-      let Subcommand::Server(server) = arguments.subcommand else {
+      let Subcommand::Server(mut server) = arguments.subcommand else {
         panic!("unexpected subcommand: {:?}", arguments.subcommand);
       };
 
@@ -2016,7 +2796,8 @@ mod tests {
         .or_defaults()
         .unwrap();
 
-      let index = Arc::new(Index::open(&settings).unwrap());
+      let index =
+        Arc::new(Index::open_with_event_sender(&settings, server.event_sender().unwrap()).unwrap());
       let ord_server_handle = Handle::new();
 
       {
@@ -2060,6 +2841,10 @@ mod tests {
       self.server_flag("--https")
     }
 
+    fn index_addresses(self) -> Self {
+      self.ord_flag("--index-addresses")
+    }
+
     fn index_runes(self) -> Self {
       self.ord_flag("--index-runes")
     }
@@ -2068,6 +2853,10 @@ mod tests {
       self.ord_flag("--index-sats")
     }
 
+    fn index_search(self) -> Self {
+      self.ord_flag("--index-search")
+    }
+
     fn redirect_http_to_https(self) -> Self {
       self.server_flag("--redirect-http-to-https")
     }
@@ -2491,6 +3280,14 @@ mod tests {
     TestServer::new().assert_redirect("/search/abc", "/sat/abc");
   }
 
+  #[test]
+  fn search_for_address_returns_address() {
+    TestServer::new().assert_redirect(
+      "/search/bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+      "/address/bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+    );
+  }
+
   #[test]
   fn search_for_blockhash_returns_block() {
     TestServer::new().assert_redirect(
@@ -2575,6 +3372,131 @@ mod tests {
     );
   }
 
+  #[test]
+  fn full_text_search_requires_search_index() {
+    let server = TestServer::new();
+
+    server.assert_response(
+      "/search?q=hello",
+      StatusCode::NOT_FOUND,
+      "this server has no search index",
+    );
+  }
+
+  #[test]
+  fn full_text_search() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_search()
+      .build();
+
+    server.mine_blocks(1);
+
+    let parent_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        1,
+        0,
+        0,
+        inscription("text/plain", "Hello, World!").to_witness(),
+      )],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let parent = InscriptionId {
+      txid: parent_txid,
+      index: 0,
+    };
+
+    let child_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (
+          2,
+          0,
+          0,
+          Inscription {
+            content_type: Some("text/markdown".into()),
+            body: Some("# hello mars".into()),
+            parents: vec![parent.value()],
+            ..default()
+          }
+          .to_witness(),
+        ),
+        (2, 1, 0, Default::default()),
+      ],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let child = InscriptionId {
+      txid: child_txid,
+      index: 0,
+    };
+
+    server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 0, 0, inscription("image/png", "hello").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    #[track_caller]
+    fn case(server: &TestServer, query: &str, expected: &[InscriptionId]) {
+      assert_eq!(
+        server.get_json::<api::Inscriptions>(format!("/search?{query}")),
+        api::Inscriptions {
+          ids: expected.into(),
+          more: false,
+          page_index: 0,
+        },
+      );
+    }
+
+    case(&server, "q=hello", &[child, parent]);
+    case(&server, "q=HELLO+world", &[parent]);
+    case(&server, "q=mars", &[child]);
+    case(&server, "q=venus", &[]);
+    case(&server, "q=hello&content_type=text/markdown", &[child]);
+    case(&server, "q=hello&parent=", &[child, parent]);
+    case(&server, &format!("q=hello&parent={parent}"), &[child]);
+    case(&server, "q=hello&from_height=3", &[child]);
+    case(&server, "q=hello&to_height=2", &[parent]);
+    case(&server, "q=hello&charm=cursed", &[]);
+
+    assert_eq!(
+      server.get_json::<api::Inscriptions>("/search?q=hello&page=1"),
+      api::Inscriptions {
+        ids: Vec::new(),
+        more: false,
+        page_index: 1,
+      },
+    );
+
+    server.assert_response_regex(
+      "/search?q=hello",
+      StatusCode::OK,
+      format!(
+        ".*<title>Search: hello</title>.*
+<div class=thumbnails>
+  <a href=/inscription/{child}>.*</a>
+  <a href=/inscription/{parent}>.*</a>
+</div>.*"
+      ),
+    );
+
+    server.assert_response("/search?q=+", StatusCode::BAD_REQUEST, "empty search query");
+
+    server.assert_response(
+      "/search?q=hello&from_height=foo",
+      StatusCode::BAD_REQUEST,
+      "invalid from_height: foo",
+    );
+
+    server.assert_redirect("/search?query=0", "/inscription/0");
+  }
+
   #[test]
   fn html_runes_balances_not_found() {
     TestServer::builder()
@@ -2661,7 +3583,7 @@ mod tests {
   }
 
   #[test]
-  fn runes_can_be_queried_by_rune_number() {
+  fn rune_recursive_endpoints() {
     let server = TestServer::builder()
       .chain(Chain::Regtest)
       .index_runes()
@@ -2669,38 +3591,162 @@ mod tests {
 
     server.mine_blocks(1);
 
-    server.assert_response_regex("/rune/0", StatusCode::NOT_FOUND, ".*");
+    server.assert_response(
+      "/r/rune/0",
+      StatusCode::NOT_FOUND,
+      "rune number 0 not found",
+    );
 
-    for i in 0..10 {
-      let rune = Rune(RUNE + i);
-      server.etch(
-        Runestone {
-          edicts: vec![Edict {
-            id: RuneId::default(),
-            amount: u128::MAX,
-            output: 0,
-          }],
-          etching: Some(Etching {
-            rune: Some(rune),
+    let rune = Rune(RUNE);
+
+    let (txid, id) = server.etch(
+      Runestone {
+        edicts: vec![Edict {
+          id: RuneId::default(),
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          divisibility: Some(1),
+          premine: Some(1000),
+          rune: Some(rune),
+          symbol: Some('%'),
+          terms: Some(Terms {
+            amount: Some(100),
+            cap: Some(10),
             ..default()
           }),
           ..default()
-        },
-        1,
-        None,
-      );
-
-      server.mine_blocks(1);
-    }
-
-    server.assert_response_regex(
-      "/rune/0",
-      StatusCode::OK,
-      ".*<title>Rune AAAAAAAAAAAAA</title>.*",
+        }),
+        ..default()
+      },
+      1,
+      None,
     );
 
-    for i in 1..6 {
-      server.assert_response_regex(
+    server.mine_blocks(1);
+
+    let expected = api::RuneRecursive {
+      block: id.block,
+      burned: 0,
+      divisibility: 1,
+      etching: txid,
+      id,
+      mintable: true,
+      mints: 0,
+      number: 0,
+      premine: 1000,
+      spaced_rune: SpacedRune { rune, spacers: 0 },
+      supply: 1000,
+      symbol: Some('%'),
+      terms: Some(Terms {
+        amount: Some(100),
+        cap: Some(10),
+        ..default()
+      }),
+      timestamp: id.block,
+      turbo: false,
+    };
+
+    assert_eq!(server.get_json::<api::RuneRecursive>("/r/rune/0"), expected);
+    assert_eq!(
+      server.get_json::<api::RuneRecursive>(format!("/r/rune/{id}")),
+      expected
+    );
+    assert_eq!(
+      server.get_json::<api::RuneRecursive>(format!("/r/rune/{rune}")),
+      expected
+    );
+
+    let outpoint = OutPoint { txid, vout: 0 };
+
+    let pile = Pile {
+      amount: 1000,
+      divisibility: 1,
+      symbol: Some('%'),
+    };
+
+    assert_eq!(
+      server.get_json::<Pile>(format!("/r/rune/{rune}/balance/{outpoint}")),
+      pile,
+    );
+
+    assert_eq!(
+      server.get_json::<Pile>(format!("/r/rune/{rune}/balance/{}", OutPoint::null())),
+      Pile { amount: 0, ..pile },
+    );
+
+    assert_eq!(
+      server.get_json::<api::RuneBalancesRecursive>(format!("/r/rune/{rune}/balances")),
+      api::RuneBalancesRecursive {
+        balances: vec![(outpoint, pile)],
+        more: false,
+        page: 0,
+      }
+    );
+
+    assert_eq!(
+      server.get_json::<api::RuneBalancesRecursive>(format!("/r/rune/{rune}/balances/1")),
+      api::RuneBalancesRecursive {
+        balances: Vec::new(),
+        more: false,
+        page: 1,
+      }
+    );
+  }
+
+  #[test]
+  fn rune_recursive_endpoints_require_rune_index() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.assert_response(
+      "/r/rune/0",
+      StatusCode::NOT_FOUND,
+      "this server has no rune index",
+    );
+  }
+
+  #[test]
+  fn runes_can_be_queried_by_rune_number() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_runes()
+      .build();
+
+    server.mine_blocks(1);
+
+    server.assert_response_regex("/rune/0", StatusCode::NOT_FOUND, ".*");
+
+    for i in 0..10 {
+      let rune = Rune(RUNE + i);
+      server.etch(
+        Runestone {
+          edicts: vec![Edict {
+            id: RuneId::default(),
+            amount: u128::MAX,
+            output: 0,
+          }],
+          etching: Some(Etching {
+            rune: Some(rune),
+            ..default()
+          }),
+          ..default()
+        },
+        1,
+        None,
+      );
+
+      server.mine_blocks(1);
+    }
+
+    server.assert_response_regex(
+      "/rune/0",
+      StatusCode::OK,
+      ".*<title>Rune AAAAAAAAAAAAA</title>.*",
+    );
+
+    for i in 1..6 {
+      server.assert_response_regex(
         format!("/rune/{}", i),
         StatusCode::OK,
         ".*<title>Rune AAAAAAAAAAAA.*</title>.*",
@@ -3217,6 +4263,181 @@ mod tests {
     );
   }
 
+  #[test]
+  fn event_stream_requires_flag() {
+    TestServer::new().assert_response(
+      "/events",
+      StatusCode::NOT_FOUND,
+      "this server does not stream events",
+    );
+  }
+
+  #[test]
+  fn event_stream() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .server_flag("--event-stream")
+      .build();
+
+    let response = server.get("/events?content_type=text/plain");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_TYPE).unwrap(),
+      "text/event-stream"
+    );
+
+    server.mine_blocks(1);
+
+    server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("image/png", "foo").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0, inscription("text/plain", "bar").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let mut lines = io::BufRead::lines(io::BufReader::new(response)).map(Result::unwrap);
+
+    let mut events = Vec::new();
+
+    while let Some(line) = lines.next() {
+      if let Some(name) = line.strip_prefix("event:") {
+        let data = lines.next().unwrap();
+        let event = serde_json::from_str::<Event>(data.strip_prefix("data:").unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&event).unwrap()["type"], name);
+        if let Event::BlockCommitted { .. } = event {
+          continue;
+        }
+        events.push(event);
+        if events.len() == 4 {
+          break;
+        }
+      }
+    }
+
+    let block_indexed = |block_height| Event::BlockIndexed {
+      block_hash: server
+        .index
+        .block_hash(Some(block_height))
+        .unwrap()
+        .unwrap(),
+      block_height,
+    };
+
+    pretty_assert_eq!(
+      events,
+      [
+        block_indexed(1),
+        block_indexed(2),
+        Event::InscriptionCreated {
+          block_hash: server.index.block_hash(Some(3)).unwrap().unwrap(),
+          block_height: 3,
+          charms: 0,
+          content_type: Some("text/plain".into()),
+          inscription_id: InscriptionId { txid, index: 0 },
+          location: Some(SatPoint {
+            outpoint: OutPoint { txid, vout: 0 },
+            offset: 0,
+          }),
+          parent_inscription_ids: Vec::new(),
+          sequence_number: 1,
+        },
+        block_indexed(3),
+      ]
+    );
+  }
+
+  #[test]
+  fn address_page_requires_address_index() {
+    TestServer::builder()
+      .chain(Chain::Regtest)
+      .build()
+      .assert_response(
+        format!("/address/{}", default_address(Chain::Regtest)),
+        StatusCode::NOT_FOUND,
+        "this server has no address index",
+      );
+  }
+
+  #[test]
+  fn address_page_rejects_address_for_wrong_network() {
+    TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_addresses()
+      .build()
+      .assert_response_regex(
+        "/address/bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        StatusCode::BAD_REQUEST,
+        ".*",
+      );
+  }
+
+  #[test]
+  fn address_page_shows_outputs_inscriptions_and_sat_balance() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_addresses()
+      .build();
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        1,
+        0,
+        0,
+        inscription("text/plain;charset=utf-8", "hello").to_witness(),
+      )],
+      outputs: 2,
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let address = default_address(Chain::Regtest);
+
+    pretty_assert_eq!(
+      server.get_json::<api::AddressInfo>(format!("/address/{address}")),
+      api::AddressInfo {
+        inscriptions: vec![InscriptionId { txid, index: 0 }],
+        outputs: vec![OutPoint { txid, vout: 0 }, OutPoint { txid, vout: 1 }],
+        runes_balances: Vec::new(),
+        sat_balance: 50 * COIN_VALUE,
+      }
+    );
+
+    server.assert_response_regex(
+      format!("/address/{address}"),
+      StatusCode::OK,
+      format!(
+        ".*<title>Address {address}</title>.*
+<h1>Address {address}</h1>
+<dl>
+  <dt>sat balance</dt><dd>5000000000</dd>
+  <dt>inscriptions</dt>
+  <dd class=thumbnails>
+    <a href=/inscription/{txid}i0>.*</a>
+  </dd>
+  <dt>outputs</dt>
+  <dd>
+    <ul class=monospace>
+      <li><a href=/output/{txid}:0 class=monospace>{txid}:0</a></li>
+      <li><a href=/output/{txid}:1 class=monospace>{txid}:1</a></li>
+    </ul>
+  </dd>
+</dl>
+.*"
+      ),
+    );
+  }
+
   #[test]
   fn http_to_https_redirect_with_path() {
     TestServer::builder()
@@ -3880,7 +5101,9 @@ mod tests {
 
   #[test]
   fn detect_unrecoverable_reorg() {
-    let test_server = TestServer::new();
+    let test_server = TestServer::builder()
+      .ord_option("--undo-log-depth", "0")
+      .build();
 
     test_server.mine_blocks(21);
 
@@ -4720,57 +5943,405 @@ mod tests {
   }
 
   #[test]
-  fn error_content_responses_have_max_age_zero_cache_control_headers() {
+  fn content_responses_have_etag_and_accept_ranges_headers() {
     let server = TestServer::builder().chain(Chain::Regtest).build();
-    let response =
-      server.get("/content/6ac5cacb768794f4fd7a78bf00f2074891fce68bd65c4ff36e77177237aacacai0");
+    server.mine_blocks(1);
 
-    assert_eq!(response.status(), 404);
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/foo", "hello").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    let response = server.get(format!("/content/{inscription_id}"));
+
+    assert_eq!(response.status(), StatusCode::OK);
     assert_eq!(
-      response.headers().get(header::CACHE_CONTROL).unwrap(),
-      "no-store"
+      response.headers().get(header::ETAG).unwrap(),
+      &format!("\"{inscription_id}\"")
+    );
+    assert_eq!(
+      response.headers().get(header::ACCEPT_RANGES).unwrap(),
+      "bytes"
     );
   }
 
   #[test]
-  fn inscriptions_page_with_no_prev_or_next() {
-    TestServer::builder()
-      .chain(Chain::Regtest)
-      .index_sats()
-      .build()
-      .assert_response_regex("/inscriptions", StatusCode::OK, ".*prev\nnext.*");
-  }
-
-  #[test]
-  fn inscriptions_page_with_no_next() {
-    let server = TestServer::builder()
-      .chain(Chain::Regtest)
-      .index_sats()
-      .build();
+  fn content_if_none_match() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+    server.mine_blocks(1);
 
-    for i in 0..101 {
-      server.mine_blocks(1);
-      server.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(i + 1, 0, 0, inscription("text/foo", "hello").to_witness())],
-        ..default()
-      });
-    }
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/foo", "hello").to_witness())],
+      ..default()
+    });
 
     server.mine_blocks(1);
 
-    server.assert_response_regex(
-      "/inscriptions/1",
-      StatusCode::OK,
-      ".*<a class=prev href=/inscriptions/0>prev</a>\nnext.*",
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    let client = reqwest::blocking::Client::new();
+
+    let response = client
+      .get(server.join_url(&format!("/content/{inscription_id}")))
+      .header(header::IF_NONE_MATCH, format!("\"{inscription_id}\""))
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(
+      response.headers().get(header::ETAG).unwrap(),
+      &format!("\"{inscription_id}\"")
     );
+    assert_eq!(response.text().unwrap(), "");
+
+    let response = client
+      .get(server.join_url(&format!("/content/{inscription_id}")))
+      .header(header::IF_NONE_MATCH, "\"foo\"")
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "hello");
   }
 
   #[test]
-  fn inscriptions_page_with_no_prev() {
-    let server = TestServer::builder()
-      .chain(Chain::Regtest)
-      .index_sats()
-      .build();
+  fn content_range_requests() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/foo", "hello").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    let client = reqwest::blocking::Client::new();
+
+    let get = |range: &str, if_range: Option<&str>| {
+      let mut request = client
+        .get(server.join_url(&format!("/content/{inscription_id}")))
+        .header(header::RANGE, range);
+
+      if let Some(if_range) = if_range {
+        request = request.header(header::IF_RANGE, if_range);
+      }
+
+      request.send().unwrap()
+    };
+
+    let response = get("bytes=1-3", None);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+      response.headers().get(header::CONTENT_RANGE).unwrap(),
+      "bytes 1-3/5"
+    );
+    assert_eq!(response.text().unwrap(), "ell");
+
+    let response = get("bytes=-2", None);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.text().unwrap(), "lo");
+
+    let response = get("bytes=5-", None);
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+      response.headers().get(header::CONTENT_RANGE).unwrap(),
+      "bytes */5"
+    );
+
+    let response = get("bytes=0-1,3-4", None);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "hello");
+
+    let response = get("bytes=1-3", Some(&format!("\"{inscription_id}\"")));
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.text().unwrap(), "ell");
+
+    let response = get("bytes=1-3", Some("\"foo\""));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "hello");
+  }
+
+  #[test]
+  fn content_range_responses_are_not_compressed() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+    server.mine_blocks(1);
+
+    let body = "0123456789".repeat(10);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", &body).to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let client = reqwest::blocking::Client::new();
+
+    let get = |range: Option<&str>| {
+      let mut request = client
+        .get(server.join_url(&format!("/content/{}", InscriptionId { txid, index: 0 })))
+        .header(header::ACCEPT_ENCODING, "gzip");
+
+      if let Some(range) = range {
+        request = request.header(header::RANGE, range);
+      }
+
+      request.send().unwrap()
+    };
+
+    let response = get(Some("bytes=10-59"));
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers().get(header::CONTENT_ENCODING), None);
+    assert_eq!(
+      response.headers().get(header::CONTENT_RANGE).unwrap(),
+      "bytes 10-59/100"
+    );
+    assert_eq!(response.text().unwrap(), body[10..60]);
+
+    let response = get(None);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_ENCODING).unwrap(),
+      "gzip"
+    );
+  }
+
+  #[test]
+  fn content_responses_are_compressed_unless_media() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+    server.mine_blocks(2);
+
+    let body = "<html><body>hello</body></html>".repeat(10);
+
+    let html = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/html", &body).to_witness())],
+      ..default()
+    });
+
+    let video = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0, inscription("video/webm", &body).to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let get = |txid| {
+      reqwest::blocking::Client::new()
+        .get(server.join_url(&format!("/content/{}", InscriptionId { txid, index: 0 })))
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .send()
+        .unwrap()
+    };
+
+    let response = get(html);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_ENCODING).unwrap(),
+      "gzip"
+    );
+    assert!(response
+      .headers()
+      .get_all(header::VARY)
+      .iter()
+      .any(|value| value == "accept-encoding"));
+    assert_eq!(
+      response.headers().get(header::ACCEPT_RANGES).unwrap(),
+      "bytes"
+    );
+    assert_eq!(
+      codec::decode("gzip", &response.bytes().unwrap(), body.len()),
+      codec::Decoded::Body(body.clone().into_bytes()),
+    );
+
+    let response = get(video);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(header::CONTENT_ENCODING), None);
+    assert!(response
+      .headers()
+      .get_all(header::VARY)
+      .iter()
+      .any(|value| value == "accept-encoding"));
+    assert_eq!(response.text().unwrap(), body);
+  }
+
+  #[test]
+  fn error_content_responses_have_max_age_zero_cache_control_headers() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+    let response =
+      server.get("/content/6ac5cacb768794f4fd7a78bf00f2074891fce68bd65c4ff36e77177237aacacai0");
+
+    assert_eq!(response.status(), 404);
+    assert_eq!(
+      response.headers().get(header::CACHE_CONTROL).unwrap(),
+      "no-store"
+    );
+  }
+
+  #[test]
+  fn inscriptions_can_be_filtered() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.mine_blocks(2);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        1,
+        0,
+        0,
+        Inscription {
+          content_type: Some("text/plain".into()),
+          body: Some("foo".into()),
+          metaprotocol: Some("foo".into()),
+          ..default()
+        }
+        .to_witness(),
+      )],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let parent = InscriptionId { txid, index: 0 };
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (2, 0, 0, Witness::default()),
+        (3, 0, 0, inscription("image/png", "cursed").to_witness()),
+      ],
+      outputs: 2,
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let cursed = InscriptionId { txid, index: 0 };
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (
+          4,
+          0,
+          0,
+          Inscription {
+            content_type: Some("image/png".into()),
+            body: Some("child".into()),
+            parents: vec![parent.value()],
+            ..default()
+          }
+          .to_witness(),
+        ),
+        (3, 1, 0, Default::default()),
+      ],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let child = InscriptionId { txid, index: 0 };
+
+    #[track_caller]
+    fn case(server: &TestServer, query: &str, expected: &[InscriptionId]) {
+      assert_eq!(
+        server.get_json::<api::Inscriptions>(format!("/inscriptions?{query}")),
+        api::Inscriptions {
+          ids: expected.into(),
+          more: false,
+          page_index: 0,
+        },
+      );
+    }
+
+    case(&server, "content_type=image/png", &[child, cursed]);
+    case(
+      &server,
+      "content_type=image/png&order=oldest",
+      &[cursed, child],
+    );
+    case(&server, "content_type=text/html", &[]);
+    case(&server, "charm=cursed", &[cursed]);
+    case(&server, "charm=cursed&content_type=text/plain", &[]);
+    case(&server, "metaprotocol=foo", &[parent]);
+    case(&server, &format!("parent={parent}"), &[child]);
+    case(&server, "min_height=4&max_height=4", &[cursed]);
+    case(&server, "max_height=3", &[parent]);
+    case(&server, "min_height=4", &[child, cursed]);
+    case(&server, "order=oldest", &[parent, cursed, child]);
+    case(
+      &server,
+      "order=newest&content_type=",
+      &[child, cursed, parent],
+    );
+
+    server.assert_response_regex(
+      "/inscriptions?content_type=image/png",
+      StatusCode::OK,
+      format!(
+        ".*<div class=thumbnails>
+  <a href=/inscription/{child}>.*</a>
+  <a href=/inscription/{cursed}>.*</a>
+</div>.*"
+      ),
+    );
+
+    server.assert_response(
+      "/inscriptions?charm=foo",
+      StatusCode::BAD_REQUEST,
+      "invalid charm: foo",
+    );
+
+    server.assert_response(
+      "/inscriptions/0?order=foo",
+      StatusCode::BAD_REQUEST,
+      "invalid order: foo",
+    );
+  }
+
+  #[test]
+  fn inscriptions_page_with_no_prev_or_next() {
+    TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_sats()
+      .build()
+      .assert_response_regex("/inscriptions", StatusCode::OK, ".*prev\nnext.*");
+  }
+
+  #[test]
+  fn inscriptions_page_with_no_next() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_sats()
+      .build();
+
+    for i in 0..101 {
+      server.mine_blocks(1);
+      server.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 0, 0, inscription("text/foo", "hello").to_witness())],
+        ..default()
+      });
+    }
+
+    server.mine_blocks(1);
+
+    server.assert_response_regex(
+      "/inscriptions/1",
+      StatusCode::OK,
+      ".*<a class=prev href=\"/inscriptions/0\">prev</a>\nnext.*",
+    );
+  }
+
+  #[test]
+  fn inscriptions_page_with_no_prev() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_sats()
+      .build();
 
     for i in 0..101 {
       server.mine_blocks(1);
@@ -4785,7 +6356,7 @@ mod tests {
     server.assert_response_regex(
       "/inscriptions/0",
       StatusCode::OK,
-      ".*prev\n<a class=next href=/inscriptions/1>next</a>.*",
+      ".*prev\n<a class=next href=\"/inscriptions/1\">next</a>.*",
     );
   }
 
@@ -4915,6 +6486,149 @@ next
     );
   }
 
+  #[test]
+  fn encoded_content_is_decompressed_and_reencoded() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .server_flag("--decompress")
+      .server_option("--max-decompressed-size", "1000")
+      .build();
+
+    server.mine_blocks(1);
+
+    let mut ids = Vec::new();
+
+    for (i, (encoding, body)) in [
+      ("gzip", codec::encode("gzip", &[0; 1000]).unwrap()),
+      ("deflate", codec::encode("deflate", &[0; 1000]).unwrap()),
+      ("gzip", codec::encode("gzip", &[0; 1001]).unwrap()),
+      ("gzip", b"this is not gzip".to_vec()),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+      let txid = server.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(
+          i + 1,
+          0,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            content_encoding: Some(encoding.into()),
+            body: Some(body),
+            ..default()
+          }
+          .to_witness(),
+        )],
+        ..default()
+      });
+
+      server.mine_blocks(1);
+
+      ids.push(InscriptionId { txid, index: 0 });
+    }
+
+    let get = |id: InscriptionId, accept_encoding: Option<&str>| {
+      let mut request = reqwest::blocking::Client::builder()
+        .brotli(false)
+        .build()
+        .unwrap()
+        .get(server.join_url(&format!("/content/{id}")));
+
+      if let Some(accept_encoding) = accept_encoding {
+        request = request.header(header::ACCEPT_ENCODING, accept_encoding);
+      }
+
+      request.send().unwrap()
+    };
+
+    let response = get(ids[0], Some("gzip"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_ENCODING).unwrap(),
+      "gzip"
+    );
+    assert_eq!(
+      response.headers().get(header::ETAG).unwrap(),
+      &format!("\"{}\"", ids[0])
+    );
+
+    for id in &ids[..2] {
+      let response = get(*id, None);
+      assert_eq!(response.status(), StatusCode::OK);
+      assert_eq!(response.headers().get(header::CONTENT_ENCODING), None);
+      assert_eq!(
+        response.headers().get(header::ETAG).unwrap(),
+        &format!("\"{id}:identity\"")
+      );
+      assert_eq!(*response.bytes().unwrap(), [0; 1000]);
+
+      let response = get(*id, Some("br"));
+      assert_eq!(response.status(), StatusCode::OK);
+      assert_eq!(
+        response.headers().get(header::CONTENT_ENCODING).unwrap(),
+        "br"
+      );
+      assert_eq!(
+        response.headers().get(header::ETAG).unwrap(),
+        &format!("\"{id}:br\"")
+      );
+      assert_eq!(
+        codec::decode("br", &response.bytes().unwrap(), 1000),
+        codec::Decoded::Body(vec![0; 1000]),
+      );
+    }
+
+    let response = get(ids[2], None);
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+      response.text().unwrap(),
+      "decoded inscription content is larger than 1000 bytes"
+    );
+
+    let response = get(ids[3], None);
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+      response.text().unwrap(),
+      "inscription content could not be decoded with `gzip`: invalid gzip header"
+    );
+  }
+
+  #[test]
+  fn encoded_content_is_not_decompressed_by_default() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        1,
+        0,
+        0,
+        Inscription {
+          content_type: Some("text/plain".into()),
+          content_encoding: Some("gzip".into()),
+          body: Some(codec::encode("gzip", b"hello").unwrap()),
+          ..default()
+        }
+        .to_witness(),
+      )],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let response = reqwest::blocking::Client::builder()
+      .brotli(false)
+      .build()
+      .unwrap()
+      .get(server.join_url(&format!("/content/{}", InscriptionId { txid, index: 0 })))
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+  }
+
   #[test]
   fn inscription_links_to_parent() {
     let server = TestServer::builder().chain(Chain::Regtest).build();
@@ -5849,36 +7563,214 @@ next
 
     assert_eq!(
       server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/0")
-        .id,
-      Some(ids[0])
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/0")
+        .id,
+      Some(ids[0])
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-111")
+        .id,
+      Some(ids[0])
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/110")
+        .id,
+      Some(ids[110])
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-1")
+        .id,
+      Some(ids[110])
+    );
+
+    assert!(server
+      .get_json::<api::SatInscription>("/r/sat/5000000000/at/111")
+      .id
+      .is_none());
+  }
+
+  #[test]
+  fn sat_info_recursive_endpoint() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_sats()
+      .build();
+
+    assert_eq!(
+      server.get_json::<api::SatRecursive>("/r/sat/0/info"),
+      api::SatRecursive {
+        block: 0,
+        charms: vec![Charm::Coin, Charm::Mythic],
+        inscriptions: Vec::new(),
+        name: "nvtdijuwxlp".into(),
+        number: 0,
+        rarity: Rarity::Mythic,
+        satpoint: Some(SatPoint {
+          outpoint: Chain::Regtest.genesis_coinbase_outpoint(),
+          offset: 0,
+        }),
+      }
+    );
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let id = InscriptionId { txid, index: 0 };
+
+    let sat = server.get_json::<api::SatRecursive>("/r/sat/5000000000/info");
+
+    assert_eq!(sat.number, 5000000000);
+    assert_eq!(sat.rarity, Rarity::Uncommon);
+    assert_eq!(
+      sat.satpoint,
+      Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      })
+    );
+    assert_eq!(
+      sat.inscriptions,
+      vec![server.get_json::<api::InscriptionRecursive>(format!("/r/inscription/{id}"))]
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatRecursive>("/r/sat/nvtcsezkbth/info")
+        .number,
+      5000000000,
+    );
+
+    assert_eq!(
+      server.get_json::<api::SatRecursive>("/r/sat/5000000001/info"),
+      api::SatRecursive {
+        block: 1,
+        charms: Vec::new(),
+        inscriptions: Vec::new(),
+        name: "nvtcsezkbtg".into(),
+        number: 5000000001,
+        rarity: Rarity::Common,
+        satpoint: None,
+      }
+    );
+  }
+
+  #[test]
+  fn sat_info_recursive_endpoint_requires_sat_index() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.assert_response(
+      "/r/sat/0/info",
+      StatusCode::NOT_FOUND,
+      "this server has no sat index",
+    );
+  }
+
+  #[test]
+  fn output_recursive_endpoint() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_sats()
+      .build();
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    let outpoint = OutPoint { txid, vout: 0 };
+
+    server.assert_response(
+      format!("/r/output/{outpoint}"),
+      StatusCode::NOT_FOUND,
+      &format!("output {outpoint} not found"),
+    );
+
+    server.mine_blocks(1);
+
+    let output = server.get_json::<api::OutputRecursive>(format!("/r/output/{outpoint}"));
+
+    assert_eq!(
+      output,
+      api::OutputRecursive {
+        address: Some(
+          "bcrt1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqdku202"
+            .parse()
+            .unwrap()
+        ),
+        height: 2,
+        inscriptions: vec![InscriptionId { txid, index: 0 }],
+        runes: Vec::new(),
+        sat_ranges: Some(vec![(5000000000, 10000000000)]),
+        script_pubkey: server.core.tx(2, 1).output[0].script_pubkey.to_asm_string(),
+        value: 50 * COIN_VALUE,
+      }
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::OutputRecursive>(format!(
+          "/r/output/{}",
+          Chain::Regtest.genesis_coinbase_outpoint()
+        ))
+        .height,
+      0,
     );
+  }
 
-    assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-111")
-        .id,
-      Some(ids[0])
+  #[test]
+  fn transaction_recursive_endpoint() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      ..default()
+    });
+
+    server.assert_response(
+      format!("/r/tx/{txid}"),
+      StatusCode::NOT_FOUND,
+      &format!("transaction {txid} not found"),
     );
 
+    server.mine_blocks(1);
+
+    let transaction = server.core.tx(2, 1);
+
     assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/110")
-        .id,
-      Some(ids[110])
+      server.get_json::<api::TransactionRecursive>(format!("/r/tx/{txid}")),
+      api::TransactionRecursive {
+        height: 2,
+        hex: consensus::encode::serialize_hex(&transaction),
+        transaction,
+      }
     );
 
+    let genesis = Chain::Regtest.genesis_block().coinbase().unwrap().clone();
+
     assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-1")
-        .id,
-      Some(ids[110])
+      server.get_json::<api::TransactionRecursive>(format!("/r/tx/{}", genesis.txid())),
+      api::TransactionRecursive {
+        height: 0,
+        hex: consensus::encode::serialize_hex(&genesis),
+        transaction: genesis,
+      }
     );
-
-    assert!(server
-      .get_json::<api::SatInscription>("/r/sat/5000000000/at/111")
-      .id
-      .is_none());
   }
 
   #[test]
@@ -6073,9 +7965,85 @@ next
 
     server.assert_response(format!("/content/{id}"), StatusCode::OK, "foo");
 
+    assert_eq!(
+      server
+        .get(format!("/content/{id}"))
+        .headers()
+        .get(header::ETAG)
+        .unwrap(),
+      &format!("\"{id}:{delegate}\"")
+    );
+
     server.assert_response(format!("/preview/{id}"), StatusCode::OK, "foo");
   }
 
+  #[test]
+  fn undelegated_content() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.mine_blocks(1);
+
+    let delegate = Inscription {
+      content_type: Some("text/plain".into()),
+      body: Some("foo".into()),
+      ..default()
+    };
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, delegate.to_witness())],
+      ..default()
+    });
+
+    let delegate = InscriptionId { txid, index: 0 };
+
+    server.mine_blocks(1);
+
+    let inscription = Inscription {
+      content_type: Some("text/plain".into()),
+      body: Some("bar".into()),
+      delegate: Some(delegate.value()),
+      ..default()
+    };
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0, inscription.to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let id = InscriptionId { txid, index: 0 };
+
+    server.assert_response(format!("/content/{id}"), StatusCode::OK, "foo");
+
+    server.assert_response(
+      format!("/r/undelegated-content/{id}"),
+      StatusCode::OK,
+      "bar",
+    );
+
+    server.assert_response(
+      format!("/r/undelegated-content/{delegate}"),
+      StatusCode::OK,
+      "foo",
+    );
+
+    assert_eq!(
+      server
+        .get(format!("/r/undelegated-content/{id}"))
+        .headers()
+        .get(header::ETAG)
+        .unwrap(),
+      &format!("\"{id}\"")
+    );
+
+    server.assert_response(
+      format!("/r/undelegated-content/{}", inscription_id(1)),
+      StatusCode::NOT_FOUND,
+      &format!("inscription {} not found", inscription_id(1)),
+    );
+  }
+
   #[test]
   fn proxy() {
     let server = TestServer::builder().chain(Chain::Regtest).build();
@@ -6233,6 +8201,370 @@ next
       StatusCode::OK,
       PreviewUnknownHtml.to_string(),
     );
+    server.assert_response_regex(
+      format!("/r/undelegated-content/{inscription}"),
+      StatusCode::OK,
+      PreviewUnknownHtml.to_string(),
+    );
+  }
+
+  #[test]
+  fn api_keys_and_rate_limits() {
+    let server = TestServer::builder()
+      .config(
+        "
+server_rate_limit: 3
+api_keys:
+- key: foo
+  name: alice
+  admin: true
+- key: bar
+  name: bob
+  quota: 1
+",
+      )
+      .build();
+
+    let get = |path: &str, key: Option<&str>| {
+      let mut request = reqwest::blocking::Client::new()
+        .get(server.join_url(path))
+        .header(header::ACCEPT, "application/json");
+
+      if let Some(key) = key {
+        request = request.header("x-api-key", key);
+      }
+
+      request.send().unwrap()
+    };
+
+    // one request was made while waiting for the server to start
+    assert_eq!(get("/blockcount", None).status(), StatusCode::OK);
+    assert_eq!(get("/blockcount", None).status(), StatusCode::OK);
+
+    let response = get("/blockcount", None);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+    assert_eq!(response.text().unwrap(), "rate limit exceeded");
+
+    let response = get("/blockcount", Some("baz"));
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.text().unwrap(), "invalid API key");
+
+    let response = get("/usage", Some("bar"));
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(response.text().unwrap(), "admin API key required");
+
+    let response = get("/blockcount", Some("bar"));
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.text().unwrap(), "daily quota exceeded");
+
+    assert_eq!(get("/blockcount", Some("foo")).status(), StatusCode::OK);
+
+    let usage = reqwest::blocking::Client::new()
+      .get(server.join_url("/usage"))
+      .header(header::ACCEPT, "application/json")
+      .bearer_auth("foo")
+      .send()
+      .unwrap()
+      .json::<UsageHtml>()
+      .unwrap();
+
+    pretty_assert_eq!(
+      usage,
+      UsageHtml {
+        anonymous_clients: 1,
+        anonymous_rate_limit: Some(3),
+        anonymous_requests: 4,
+        anonymous_throttled: 1,
+        keys: vec![
+          crate::templates::KeyUsage {
+            admin: true,
+            name: "alice".into(),
+            quota: None,
+            rate_limit: None,
+            requests: 2,
+            requests_today: 2,
+            throttled: 0,
+          },
+          crate::templates::KeyUsage {
+            admin: false,
+            name: "bob".into(),
+            quota: Some(1),
+            rate_limit: None,
+            requests: 2,
+            requests_today: 1,
+            throttled: 1,
+          },
+        ],
+      }
+    );
+
+    let response = reqwest::blocking::Client::new()
+      .get(server.join_url("/usage"))
+      .header("x-api-key", "foo")
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_regex_match!(
+      response.text().unwrap(),
+      ".*<h1>Usage</h1>.*<h3>alice</h3>.*<h3>bob</h3>.*"
+    );
+  }
+
+  #[test]
+  fn metrics() {
+    let server = TestServer::builder()
+      .config("api_keys:\n- key: foo\n  name: alice\n  admin: true\n")
+      .build();
+
+    server.mine_blocks(2);
+
+    server.assert_response("/blockcount", StatusCode::OK, "3");
+
+    let response = reqwest::blocking::Client::new()
+      .get(server.join_url("/metrics"))
+      .header(header::AUTHORIZATION, "Bearer foo")
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_TYPE).unwrap(),
+      metrics::CONTENT_TYPE,
+    );
+
+    let text = response.text().unwrap();
+
+    for line in [
+      "ord_index_block_count 3",
+      "ord_bitcoind_up 1",
+      "ord_bitcoind_block_count 3",
+      "ord_index_lag_blocks 0",
+      "ord_index_blocks_indexed_total 3",
+      "ord_index_reorgs_total{kind=\"recoverable\"} 0",
+      "ord_index_unrecoverably_reorged 0",
+      "ord_rpc_errors_total{request=\"block\"} 0",
+      "ord_http_requests_total{route=\"/blockcount\",status=\"200\"} 1",
+      "ord_http_request_duration_seconds_count{route=\"/blockcount\"} 1",
+    ] {
+      assert!(
+        text.lines().any(|l| l == line),
+        "missing line `{line}` in:\n{text}"
+      );
+    }
+
+    assert_regex_match!(
+      text,
+      r#"(?s).*\nord_index_table_stored_bytes\{table="HEIGHT_TO_BLOCK_HEADER"\} [1-9][0-9]*\n.*"#
+    );
+  }
+
+  #[test]
+  fn openapi_schemas_match_responses() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_addresses()
+      .index_runes()
+      .index_sats()
+      .index_search()
+      .config("api_keys:\n- key: foo\n  name: alice\n  admin: true\n")
+      .build();
+
+    let rune = Rune(RUNE);
+
+    let (etching, _id) = server.etch(
+      Runestone {
+        edicts: vec![Edict {
+          id: RuneId::default(),
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          premine: Some(1000),
+          rune: Some(rune),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+      None,
+    );
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        3,
+        0,
+        0,
+        Inscription {
+          metadata: Some(vec![0xa0]),
+          ..inscription("text/plain", "hello")
+        }
+        .to_witness(),
+      )],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let id = InscriptionId { txid, index: 0 };
+    let outpoint = OutPoint { txid, vout: 0 };
+    let rune_outpoint = OutPoint {
+      txid: etching,
+      vout: 0,
+    };
+
+    let inscription = server.get_json::<api::Inscription>(format!("/inscription/{id}"));
+    let address = inscription.address.unwrap();
+    let height = inscription.height;
+    let sat = inscription.sat.unwrap();
+
+    let examples = [
+      ("get", "/address/:address", format!("/address/{address}")),
+      ("get", "/block/:query", "/block/0".into()),
+      ("get", "/blocks", "/blocks".into()),
+      (
+        "get",
+        "/inscription/:inscription_query",
+        format!("/inscription/{id}"),
+      ),
+      ("get", "/inscriptions", "/inscriptions".into()),
+      ("post", "/inscriptions", "/inscriptions".into()),
+      ("get", "/inscriptions/:page", "/inscriptions/0".into()),
+      (
+        "get",
+        "/inscriptions/block/:height",
+        format!("/inscriptions/block/{height}"),
+      ),
+      (
+        "get",
+        "/inscriptions/block/:height/:page",
+        format!("/inscriptions/block/{height}/0"),
+      ),
+      ("get", "/output/:output", format!("/output/{outpoint}")),
+      ("post", "/outputs", "/outputs".into()),
+      ("get", "/r/blockhash", "/r/blockhash".into()),
+      ("get", "/r/blockhash/:height", "/r/blockhash/0".into()),
+      ("get", "/r/blockinfo/:query", "/r/blockinfo/0".into()),
+      (
+        "get",
+        "/r/inscription/:inscription_id",
+        format!("/r/inscription/{id}"),
+      ),
+      (
+        "get",
+        "/r/children/:inscription_id",
+        format!("/r/children/{id}"),
+      ),
+      (
+        "get",
+        "/r/children/:inscription_id/:page",
+        format!("/r/children/{id}/0"),
+      ),
+      (
+        "get",
+        "/r/metadata/:inscription_id",
+        format!("/r/metadata/{id}"),
+      ),
+      (
+        "get",
+        "/r/output/:outpoint",
+        format!("/r/output/{outpoint}"),
+      ),
+      ("get", "/r/rune/:rune", format!("/r/rune/{rune}")),
+      (
+        "get",
+        "/r/rune/:rune/balance/:outpoint",
+        format!("/r/rune/{rune}/balance/{rune_outpoint}"),
+      ),
+      (
+        "get",
+        "/r/rune/:rune/balances",
+        format!("/r/rune/{rune}/balances"),
+      ),
+      (
+        "get",
+        "/r/rune/:rune/balances/:page",
+        format!("/r/rune/{rune}/balances/0"),
+      ),
+      ("get", "/r/sat/:sat_number", format!("/r/sat/{sat}")),
+      ("get", "/r/sat/:sat_number/:page", format!("/r/sat/{sat}/0")),
+      (
+        "get",
+        "/r/sat/:sat_number/at/:index",
+        format!("/r/sat/{sat}/at/0"),
+      ),
+      (
+        "get",
+        "/r/sat/:sat_number/info",
+        format!("/r/sat/{sat}/info"),
+      ),
+      ("get", "/r/tx/:txid", format!("/r/tx/{txid}")),
+      ("get", "/rune/:rune", format!("/rune/{rune}")),
+      ("get", "/runes", "/runes".into()),
+      ("get", "/runes/:page", "/runes/0".into()),
+      ("get", "/runes/balances", "/runes/balances".into()),
+      ("get", "/sat/:sat", format!("/sat/{sat}")),
+      ("get", "/search", "/search?q=hello".into()),
+      ("get", "/status", "/status".into()),
+      ("get", "/tx/:txid", format!("/tx/{txid}")),
+      ("get", "/usage", "/usage".into()),
+    ];
+
+    assert_eq!(
+      examples
+        .iter()
+        .map(|(method, path, _)| (*method, *path))
+        .collect::<Vec<(&str, &str)>>(),
+      openapi::ENDPOINTS
+        .iter()
+        .map(|endpoint| (endpoint.method, endpoint.path))
+        .collect::<Vec<(&str, &str)>>(),
+    );
+
+    let document = server.get_json::<serde_json::Value>("/openapi.json");
+
+    let client = reqwest::blocking::Client::new();
+
+    for (endpoint, (method, _path, example)) in openapi::ENDPOINTS.iter().zip(examples) {
+      let request = if method == "post" {
+        client
+          .post(server.join_url(&example))
+          .json(&if example == "/outputs" {
+            vec![outpoint.to_string()]
+          } else {
+            vec![id.to_string()]
+          })
+      } else {
+        client.get(server.join_url(&example))
+      };
+
+      let response = request
+        .header(header::ACCEPT, "application/json")
+        .header("x-api-key", "foo")
+        .send()
+        .unwrap();
+
+      assert_eq!(response.status(), StatusCode::OK, "{method} {example}");
+
+      let value = response.json::<serde_json::Value>().unwrap();
+
+      let schema = &document["paths"][endpoint.openapi_path()][method]["responses"]["200"]
+        ["content"]["application/json"]["schema"];
+
+      if let Err(err) = openapi::validate(&document, schema, &value, "") {
+        panic!("{method} {example} does not match schema: {err}\n{value:#}");
+      }
+    }
+  }
+
+  #[test]
+  fn metrics_requires_admin_api_key() {
+    TestServer::new().assert_response("/metrics", StatusCode::FORBIDDEN, "admin API key required");
+  }
+
+  #[test]
+  fn usage_requires_admin_api_key() {
+    TestServer::new().assert_response("/usage", StatusCode::FORBIDDEN, "admin API key required");
   }
 
   #[test]
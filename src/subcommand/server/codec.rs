@@ -0,0 +1,137 @@
+use {
+  super::*,
+  flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+  },
+  std::io::Write,
+};
+
+/// Content encodings the server can decode and encode, in order of preference.
+pub(super) const ENCODINGS: [&str; 3] = ["br", "gzip", "deflate"];
+
+/// The outcome of decoding a content-encoded body.
+#[derive(Debug, PartialEq)]
+pub(super) enum Decoded {
+  Body(Vec<u8>),
+  Corrupt(String),
+  TooLarge,
+  Unsupported,
+}
+
+/// Decode `body` with `encoding`, without decoding more than `limit` bytes.
+pub(super) fn decode(encoding: &str, body: &[u8], limit: usize) -> Decoded {
+  let decoder: Box<dyn Read + '_> = match encoding {
+    "br" => Box::new(brotli::Decompressor::new(body, 4096)),
+    "gzip" => Box::new(GzDecoder::new(body)),
+    "deflate" => Box::new(ZlibDecoder::new(body)),
+    _ => return Decoded::Unsupported,
+  };
+
+  let mut decoded = Vec::new();
+
+  if let Err(err) = decoder
+    .take(u64::try_from(limit).unwrap_or(u64::MAX).saturating_add(1))
+    .read_to_end(&mut decoded)
+  {
+    return Decoded::Corrupt(err.to_string());
+  }
+
+  if decoded.len() > limit {
+    return Decoded::TooLarge;
+  }
+
+  Decoded::Body(decoded)
+}
+
+/// Encode `body` with `encoding`, which must be one of `ENCODINGS`.
+pub(super) fn encode(encoding: &str, body: &[u8]) -> Result<Vec<u8>> {
+  let mut encoded = Vec::new();
+
+  match encoding {
+    "br" => {
+      let mut encoder = brotli::CompressorWriter::new(&mut encoded, 4096, 5, 22);
+      encoder.write_all(body)?;
+      encoder.into_inner();
+    }
+    "gzip" => {
+      let mut encoder = GzEncoder::new(&mut encoded, Compression::default());
+      encoder.write_all(body)?;
+      encoder.finish()?;
+    }
+    "deflate" => {
+      let mut encoder = ZlibEncoder::new(&mut encoded, Compression::default());
+      encoder.write_all(body)?;
+      encoder.finish()?;
+    }
+    _ => bail!("unsupported content encoding `{encoding}`"),
+  }
+
+  Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    let body = b"hello, world! hello, world! hello, world!";
+
+    for encoding in ENCODINGS {
+      let encoded = encode(encoding, body).unwrap();
+      assert_ne!(encoded, body);
+      assert_eq!(
+        decode(encoding, &encoded, body.len()),
+        Decoded::Body(body.into()),
+        "{encoding}"
+      );
+    }
+  }
+
+  #[test]
+  fn decode_respects_limit() {
+    let body = [0; 1000];
+
+    for encoding in ENCODINGS {
+      let encoded = encode(encoding, &body).unwrap();
+      assert_eq!(
+        decode(encoding, &encoded, 999),
+        Decoded::TooLarge,
+        "{encoding}"
+      );
+      assert_eq!(
+        decode(encoding, &encoded, 1000),
+        Decoded::Body(body.into()),
+        "{encoding}"
+      );
+    }
+  }
+
+  #[test]
+  fn decode_corrupt_body() {
+    for encoding in ENCODINGS {
+      assert!(
+        matches!(
+          decode(encoding, b"not compressed", 1000),
+          Decoded::Corrupt(_)
+        ),
+        "{encoding}"
+      );
+    }
+  }
+
+  #[test]
+  fn decode_unsupported_encoding() {
+    assert_eq!(decode("compress", b"foo", 100), Decoded::Unsupported);
+  }
+
+  #[test]
+  fn encode_unsupported_encoding() {
+    assert_eq!(
+      encode("compress", b"foo").unwrap_err().to_string(),
+      "unsupported content encoding `compress`"
+    );
+  }
+}
@@ -0,0 +1,163 @@
+use super::*;
+
+#[derive(Default, Debug)]
+pub(crate) struct ConditionalRequest {
+  if_none_match: Option<String>,
+  if_range: Option<String>,
+  range: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl<S> axum::extract::FromRequestParts<S> for ConditionalRequest
+where
+  S: Send + Sync,
+{
+  type Rejection = (StatusCode, &'static str);
+
+  async fn from_request_parts(
+    parts: &mut http::request::Parts,
+    _state: &S,
+  ) -> Result<Self, Self::Rejection> {
+    let header = |name| {
+      parts
+        .headers
+        .get(name)
+        .and_then(|value: &HeaderValue| value.to_str().ok())
+        .map(str::to_owned)
+    };
+
+    Ok(Self {
+      if_none_match: header(header::IF_NONE_MATCH),
+      if_range: header(header::IF_RANGE),
+      range: header(header::RANGE),
+    })
+  }
+}
+
+impl ConditionalRequest {
+  /// Respond with `body`, or `304 Not Modified` if the client already has it,
+  /// or `206 Partial Content` if a single satisfiable byte range was requested.
+  pub(crate) fn respond(&self, etag: &str, mut headers: HeaderMap, body: Vec<u8>) -> Response {
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    if let Ok(value) = HeaderValue::from_str(etag) {
+      headers.insert(header::ETAG, value);
+    }
+
+    if let Some(if_none_match) = &self.if_none_match {
+      if if_none_match.split(',').any(|candidate| {
+        let candidate = candidate.trim();
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+      }) {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+      }
+    }
+
+    let Some(range) = &self.range else {
+      return (headers, body).into_response();
+    };
+
+    if self
+      .if_range
+      .as_ref()
+      .is_some_and(|if_range| if_range.trim() != etag)
+    {
+      return (headers, body).into_response();
+    }
+
+    let len = body.len();
+
+    match Self::parse_range(range, len) {
+      Some(Some((start, end))) => {
+        headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!("bytes {start}-{end}/{len}")).unwrap(),
+        );
+
+        (
+          StatusCode::PARTIAL_CONTENT,
+          headers,
+          body[start..=end].to_vec(),
+        )
+          .into_response()
+      }
+      Some(None) => {
+        headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!("bytes */{len}")).unwrap(),
+        );
+
+        (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+      }
+      None => (headers, body).into_response(),
+    }
+  }
+
+  /// Parse a single byte range, returning `None` if the header should be
+  /// ignored and `Some(None)` if the range cannot be satisfied.
+  fn parse_range(range: &str, len: usize) -> Option<Option<(usize, usize)>> {
+    let range = range.trim().strip_prefix("bytes=")?;
+
+    if range.contains(',') {
+      return None;
+    }
+
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+      let suffix = end.parse::<usize>().ok()?;
+
+      if suffix == 0 || len == 0 {
+        return Some(None);
+      }
+
+      return Some(Some((len.saturating_sub(suffix), len - 1)));
+    }
+
+    let start = start.parse::<usize>().ok()?;
+
+    let end = if end.is_empty() {
+      usize::MAX
+    } else {
+      end.parse::<usize>().ok()?
+    };
+
+    if end < start {
+      return None;
+    }
+
+    if start >= len {
+      return Some(None);
+    }
+
+    Some(Some((start, end.min(len - 1))))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_range() {
+    #[track_caller]
+    fn case(range: &str, expected: Option<Option<(usize, usize)>>) {
+      assert_eq!(ConditionalRequest::parse_range(range, 10), expected);
+    }
+
+    case("bytes=0-0", Some(Some((0, 0))));
+    case("bytes=0-9", Some(Some((0, 9))));
+    case("bytes=2-5", Some(Some((2, 5))));
+    case("bytes=5-", Some(Some((5, 9))));
+    case("bytes=5-100", Some(Some((5, 9))));
+    case("bytes=-3", Some(Some((7, 9))));
+    case("bytes=-100", Some(Some((0, 9))));
+    case("bytes=10-", Some(None));
+    case("bytes=-0", Some(None));
+    case("bytes=5-2", None);
+    case("bytes=0-1,3-4", None);
+    case("items=0-1", None);
+    case("bytes=a-b", None);
+  }
+}
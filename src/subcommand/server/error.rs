@@ -3,12 +3,14 @@ use {super::*, std::fmt::Write};
 #[derive(Debug)]
 pub(super) enum ServerError {
   BadRequest(String),
+  Forbidden(String),
   Internal(Error),
   NotAcceptable {
     accept_encoding: AcceptEncoding,
     content_encoding: HeaderValue,
   },
   NotFound(String),
+  UnprocessableContent(String),
 }
 
 pub(super) type ServerResult<T = Response> = Result<T, ServerError>;
@@ -17,6 +19,7 @@ impl IntoResponse for ServerError {
   fn into_response(self) -> Response {
     match self {
       Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+      Self::Forbidden(message) => (StatusCode::FORBIDDEN, message).into_response(),
       Self::Internal(error) => {
         eprintln!("error serving request: {error}");
         (
@@ -50,6 +53,9 @@ impl IntoResponse for ServerError {
         message,
       )
         .into_response(),
+      Self::UnprocessableContent(message) => {
+        (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+      }
     }
   }
 }
@@ -0,0 +1,308 @@
+use {
+  super::*,
+  crate::index::event::Event,
+  std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Seek, SeekFrom, Write},
+    path::Path,
+  },
+  tokio::sync::{broadcast, mpsc},
+};
+
+/// Forwards index events to the live event stream, appends them to a
+/// newline-delimited JSON log, and, if a webhook is configured, delivers each
+/// line of that log to the webhook in order. The log doubles as the webhook's
+/// delivery queue, and the byte offset of the last delivered line is persisted
+/// next to it, so that delivery resumes where it left off after a restart.
+pub(crate) struct EventSink {
+  pub(crate) broadcast: Option<broadcast::Sender<Event>>,
+  pub(crate) log: Option<PathBuf>,
+  pub(crate) webhook: Option<Url>,
+}
+
+impl EventSink {
+  const CHANNEL_CAPACITY: usize = 1024;
+  const MAX_BACKOFF: Duration = Duration::from_secs(60);
+  const MIN_BACKOFF: Duration = Duration::from_secs(1);
+  const POLL_INTERVAL: Duration = Duration::from_millis(250);
+  const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+  pub(crate) fn spawn(self) -> Result<mpsc::Sender<Event>> {
+    let mut file = self
+      .log
+      .as_ref()
+      .map(|log| {
+        OpenOptions::new()
+          .create(true)
+          .append(true)
+          .open(log)
+          .with_context(|| format!("failed to open event log `{}`", log.display()))
+      })
+      .transpose()?;
+
+    let (sender, mut receiver) = mpsc::channel(Self::CHANNEL_CAPACITY);
+
+    {
+      let broadcast = self.broadcast;
+      let log = self.log.clone();
+      thread::spawn(move || {
+        while let Some(event) = receiver.blocking_recv() {
+          if let (Some(file), Some(log)) = (&mut file, &log) {
+            if let Err(err) = Self::append(file, &event) {
+              log::error!("Failed to write event to `{}`: {err}", log.display());
+            }
+          }
+
+          if let Some(broadcast) = &broadcast {
+            broadcast.send(event).ok();
+          }
+        }
+      });
+    }
+
+    if let (Some(log), Some(webhook)) = (self.log, self.webhook) {
+      thread::spawn(move || {
+        if let Err(err) = Self::deliver(&log, &Self::cursor_path(&log), &webhook) {
+          log::error!("Event webhook delivery stopped: {err}");
+        }
+      });
+    }
+
+    Ok(sender)
+  }
+
+  fn append(file: &mut File, event: &Event) -> Result {
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+  }
+
+  fn cursor_path(log: &Path) -> PathBuf {
+    let mut path = log.to_owned().into_os_string();
+    path.push(".cursor");
+    path.into()
+  }
+
+  fn load_cursor(path: &Path) -> Result<u64> {
+    match fs::read_to_string(path) {
+      Ok(cursor) => cursor
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid event cursor in `{}`", path.display())),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(0),
+      Err(err) => Err(err.into()),
+    }
+  }
+
+  fn store_cursor(path: &Path, cursor: u64) -> Result {
+    let mut tmp = path.to_owned().into_os_string();
+    tmp.push(".tmp");
+    fs::write(&tmp, cursor.to_string())?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+  }
+
+  fn deliver(log: &Path, cursor_path: &Path, webhook: &Url) -> Result {
+    let client = reqwest::blocking::Client::builder()
+      .timeout(Self::REQUEST_TIMEOUT)
+      .build()?;
+
+    let mut cursor = Self::load_cursor(cursor_path)?;
+
+    let mut reader = BufReader::new(File::open(log)?);
+    reader.seek(SeekFrom::Start(cursor))?;
+
+    let mut line = String::new();
+
+    loop {
+      if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+        return Ok(());
+      }
+
+      line.clear();
+
+      let n = reader.read_line(&mut line)?;
+
+      if !line.ends_with('\n') {
+        reader.seek(SeekFrom::Start(cursor))?;
+        thread::sleep(Self::POLL_INTERVAL);
+        continue;
+      }
+
+      let mut backoff = Self::MIN_BACKOFF;
+
+      while let Err(err) = client
+        .post(webhook.clone())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(line.trim_end().to_owned())
+        .send()
+        .and_then(|response| response.error_for_status())
+      {
+        log::warn!("Failed to deliver event to webhook, retrying in {backoff:?}: {err}");
+
+        thread::sleep(backoff);
+
+        if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+          return Ok(());
+        }
+
+        backoff = (backoff * 2).min(Self::MAX_BACKOFF);
+      }
+
+      cursor += u64::try_from(n).unwrap();
+
+      Self::store_cursor(cursor_path, cursor)?;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, std::net::TcpListener, tempfile::TempDir};
+
+  fn event(block_height: u32) -> Event {
+    Event::RuneEtched {
+      block_hash: BlockHash::all_zeros(),
+      block_height,
+      rune_id: RuneId { block: 1, tx: 2 },
+      txid: txid(1),
+    }
+  }
+
+  fn wait_for(mut condition: impl FnMut() -> bool) {
+    for _ in 0..200 {
+      if condition() {
+        return;
+      }
+      thread::sleep(Duration::from_millis(50));
+    }
+    panic!("timed out waiting for condition");
+  }
+
+  #[test]
+  fn events_are_appended_to_log_as_ndjson() {
+    let tempdir = TempDir::new().unwrap();
+    let log = tempdir.path().join("events.ndjson");
+
+    fs::write(&log, "").unwrap();
+
+    let sender = EventSink {
+      broadcast: None,
+      log: Some(log.clone()),
+      webhook: None,
+    }
+    .spawn()
+    .unwrap();
+
+    sender.blocking_send(event(1)).unwrap();
+    sender.blocking_send(event(2)).unwrap();
+
+    wait_for(|| fs::read_to_string(&log).unwrap().lines().count() == 2);
+
+    assert_eq!(
+      fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Event>(line).unwrap())
+        .collect::<Vec<Event>>(),
+      [event(1), event(2)],
+    );
+
+    assert_eq!(
+      fs::read_to_string(&log).unwrap().lines().next().unwrap(),
+      format!(
+        r#"{{"type":"rune_etched","block_hash":"{}","block_height":1,"rune_id":"1:2","txid":"{}"}}"#,
+        BlockHash::all_zeros(),
+        txid(1),
+      ),
+    );
+  }
+
+  #[test]
+  fn webhook_delivery_retries_and_persists_cursor() {
+    let tempdir = TempDir::new().unwrap();
+    let log = tempdir.path().join("events.ndjson");
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let (request_sender, request_receiver) = std::sync::mpsc::channel();
+
+    thread::spawn(move || {
+      for (i, stream) in listener.incoming().enumerate() {
+        let mut stream = stream.unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let mut content_length = 0;
+        loop {
+          let mut header = String::new();
+          reader.read_line(&mut header).unwrap();
+          if header == "\r\n" {
+            break;
+          }
+          if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+            content_length = value.trim().parse().unwrap();
+          }
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+
+        let status = if i == 0 {
+          "500 Internal Server Error"
+        } else {
+          request_sender.send(body).unwrap();
+          "200 OK"
+        };
+
+        write!(
+          stream,
+          "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+      }
+    });
+
+    let sender = EventSink {
+      broadcast: None,
+      log: Some(log.clone()),
+      webhook: Some(format!("http://127.0.0.1:{port}/").parse().unwrap()),
+    }
+    .spawn()
+    .unwrap();
+
+    let cursor = EventSink::cursor_path(&log);
+
+    sender.blocking_send(event(1)).unwrap();
+
+    assert_eq!(
+      serde_json::from_slice::<Event>(
+        &request_receiver
+          .recv_timeout(Duration::from_secs(10))
+          .unwrap()
+      )
+      .unwrap(),
+      event(1),
+    );
+
+    wait_for(|| EventSink::load_cursor(&cursor).unwrap() == fs::metadata(&log).unwrap().len());
+  }
+
+  #[test]
+  fn missing_cursor_starts_at_beginning() {
+    let tempdir = TempDir::new().unwrap();
+    assert_eq!(
+      EventSink::load_cursor(&tempdir.path().join("events.ndjson.cursor")).unwrap(),
+      0
+    );
+  }
+
+  #[test]
+  fn cursor_round_trips() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("events.ndjson.cursor");
+    EventSink::store_cursor(&path, 1234).unwrap();
+    assert_eq!(EventSink::load_cursor(&path).unwrap(), 1234);
+  }
+}
@@ -0,0 +1,198 @@
+use {
+  super::*,
+  crate::index::event::Event,
+  axum::response::sse,
+  futures::stream::{self, Stream},
+  std::convert::Infallible,
+  tokio::sync::broadcast::{self, error::RecvError},
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EventFilter {
+  pub(crate) content_type: Option<String>,
+  pub(crate) inscription_id: Option<InscriptionId>,
+  pub(crate) parent: Option<InscriptionId>,
+  pub(crate) rune_id: Option<RuneId>,
+}
+
+impl EventFilter {
+  fn is_inscription_filter(&self) -> bool {
+    self.content_type.is_some() || self.inscription_id.is_some() || self.parent.is_some()
+  }
+
+  pub(crate) fn matches(&self, event: &Event) -> bool {
+    match event {
+      Event::BlockCommitted { .. } | Event::BlockIndexed { .. } | Event::Rollback { .. } => true,
+      Event::InscriptionCreated {
+        content_type,
+        inscription_id,
+        parent_inscription_ids,
+        ..
+      }
+      | Event::InscriptionTransferred {
+        content_type,
+        inscription_id,
+        parent_inscription_ids,
+        ..
+      } => {
+        self.rune_id.is_none()
+          && self.inscription_id.map_or(true, |id| id == *inscription_id)
+          && self
+            .parent
+            .map_or(true, |parent| parent_inscription_ids.contains(&parent))
+          && self
+            .content_type
+            .as_ref()
+            .map_or(true, |filter| content_type.as_ref() == Some(filter))
+      }
+      Event::RuneBurned { rune_id, .. }
+      | Event::RuneEtched { rune_id, .. }
+      | Event::RuneMinted { rune_id, .. }
+      | Event::RuneTransferred { rune_id, .. } => {
+        !self.is_inscription_filter() && self.rune_id.map_or(true, |id| id == *rune_id)
+      }
+    }
+  }
+}
+
+pub(crate) fn stream(
+  receiver: broadcast::Receiver<Event>,
+  filter: EventFilter,
+) -> impl Stream<Item = Result<sse::Event, Infallible>> {
+  stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+    loop {
+      let event = match receiver.recv().await {
+        Ok(event) => event,
+        Err(RecvError::Lagged(skipped)) => {
+          return Some((
+            Ok(
+              sse::Event::default()
+                .event("lagged")
+                .data(skipped.to_string()),
+            ),
+            (receiver, filter),
+          ))
+        }
+        Err(RecvError::Closed) => return None,
+      };
+
+      if !filter.matches(&event) {
+        continue;
+      }
+
+      match sse_event(&event) {
+        Ok(sse_event) => return Some((Ok(sse_event), (receiver, filter))),
+        Err(err) => log::warn!("Failed to serialize event {event:?}: {err}"),
+      }
+    }
+  })
+}
+
+fn sse_event(event: &Event) -> Result<sse::Event> {
+  let data = serde_json::to_value(event)?;
+
+  let name = data["type"]
+    .as_str()
+    .ok_or_else(|| anyhow!("event has no type"))?
+    .to_owned();
+
+  Ok(sse::Event::default().event(name).json_data(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn created(content_type: Option<&str>, parents: Vec<InscriptionId>) -> Event {
+    Event::InscriptionCreated {
+      block_hash: BlockHash::all_zeros(),
+      block_height: 1,
+      charms: 0,
+      content_type: content_type.map(str::to_owned),
+      inscription_id: inscription_id(1),
+      location: None,
+      parent_inscription_ids: parents,
+      sequence_number: 0,
+    }
+  }
+
+  fn transferred(content_type: Option<&str>, parents: Vec<InscriptionId>) -> Event {
+    Event::InscriptionTransferred {
+      block_hash: BlockHash::all_zeros(),
+      block_height: 1,
+      content_type: content_type.map(str::to_owned),
+      inscription_id: inscription_id(1),
+      new_location: satpoint(2, 0),
+      old_location: satpoint(1, 0),
+      parent_inscription_ids: parents,
+      sequence_number: 0,
+    }
+  }
+
+  fn etched(rune_id: RuneId) -> Event {
+    Event::RuneEtched {
+      block_hash: BlockHash::all_zeros(),
+      block_height: 1,
+      rune_id,
+      txid: txid(1),
+    }
+  }
+
+  #[test]
+  fn filters() {
+    let block = Event::BlockIndexed {
+      block_hash: BlockHash::all_zeros(),
+      block_height: 1,
+    };
+
+    let rune_id = RuneId { block: 1, tx: 0 };
+
+    let empty = EventFilter::default();
+    assert!(empty.matches(&block));
+    assert!(empty.matches(&created(None, Vec::new())));
+    assert!(empty.matches(&etched(rune_id)));
+
+    let by_id = EventFilter {
+      inscription_id: Some(inscription_id(1)),
+      ..default()
+    };
+    assert!(by_id.matches(&block));
+    assert!(by_id.matches(&created(None, Vec::new())));
+    assert!(!by_id.matches(&etched(rune_id)));
+
+    let by_other_id = EventFilter {
+      inscription_id: Some(inscription_id(2)),
+      ..default()
+    };
+    assert!(!by_other_id.matches(&created(None, Vec::new())));
+
+    let by_content_type = EventFilter {
+      content_type: Some("text/plain".into()),
+      ..default()
+    };
+    assert!(by_content_type.matches(&created(Some("text/plain"), Vec::new())));
+    assert!(!by_content_type.matches(&created(Some("image/png"), Vec::new())));
+    assert!(!by_content_type.matches(&created(None, Vec::new())));
+    assert!(by_content_type.matches(&transferred(Some("text/plain"), Vec::new())));
+    assert!(!by_content_type.matches(&transferred(Some("image/png"), Vec::new())));
+
+    let by_parent = EventFilter {
+      parent: Some(inscription_id(3)),
+      ..default()
+    };
+    assert!(by_parent.matches(&created(None, vec![inscription_id(3)])));
+    assert!(!by_parent.matches(&created(None, Vec::new())));
+    assert!(by_parent.matches(&transferred(None, vec![inscription_id(3)])));
+    assert!(!by_parent.matches(&transferred(None, vec![inscription_id(4)])));
+
+    let by_rune = EventFilter {
+      rune_id: Some(rune_id),
+      ..default()
+    };
+    assert!(by_rune.matches(&etched(rune_id)));
+    assert!(!by_rune.matches(&etched(RuneId { block: 2, tx: 0 })));
+    assert!(!by_rune.matches(&created(None, Vec::new())));
+    assert!(!by_rune.matches(&transferred(None, Vec::new())));
+  }
+}
@@ -0,0 +1,382 @@
+use {
+  super::*,
+  crate::index::metrics::Metrics,
+  axum::{extract::MatchedPath, http::Request, middleware::Next},
+  std::fmt::Write,
+};
+
+pub(crate) const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds, in seconds, of the request latency histogram buckets.
+const BUCKETS: [f64; 11] = [
+  0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Default)]
+struct RouteMetrics {
+  buckets: [u64; BUCKETS.len()],
+  count: u64,
+  statuses: BTreeMap<u16, u64>,
+  sum: f64,
+}
+
+/// Request counts and latencies for each route.
+#[derive(Debug, Default)]
+pub(crate) struct RequestMetrics {
+  routes: Mutex<BTreeMap<String, RouteMetrics>>,
+}
+
+impl RequestMetrics {
+  pub(crate) async fn middleware<B>(
+    Extension(request_metrics): Extension<Arc<Self>>,
+    matched_path: Option<MatchedPath>,
+    request: Request<B>,
+    next: Next<B>,
+  ) -> Response {
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    request_metrics.record(
+      matched_path
+        .as_ref()
+        .map(MatchedPath::as_str)
+        .unwrap_or_default(),
+      response.status(),
+      start.elapsed(),
+    );
+
+    response
+  }
+
+  fn record(&self, route: &str, status: StatusCode, duration: Duration) {
+    let mut routes = self.routes.lock().unwrap();
+
+    let route = routes.entry(route.into()).or_default();
+
+    let seconds = duration.as_secs_f64();
+
+    for (bucket, bound) in route.buckets.iter_mut().zip(BUCKETS) {
+      if seconds <= bound {
+        *bucket += 1;
+      }
+    }
+
+    route.count += 1;
+    route.sum += seconds;
+    *route.statuses.entry(status.as_u16()).or_default() += 1;
+  }
+
+  fn write(&self, exposition: &mut Exposition) {
+    let routes = self.routes.lock().unwrap();
+
+    exposition.header(
+      "ord_http_requests_total",
+      "counter",
+      "Requests handled, by route and response status.",
+    );
+
+    for (route, metrics) in routes.iter() {
+      for (status, count) in &metrics.statuses {
+        exposition.sample(
+          "ord_http_requests_total",
+          &[("route", route), ("status", &status.to_string())],
+          count,
+        );
+      }
+    }
+
+    exposition.header(
+      "ord_http_request_duration_seconds",
+      "histogram",
+      "Time taken to handle requests, by route.",
+    );
+
+    for (route, metrics) in routes.iter() {
+      for (count, bound) in metrics.buckets.iter().zip(BUCKETS) {
+        exposition.sample(
+          "ord_http_request_duration_seconds_bucket",
+          &[("route", route), ("le", &bound.to_string())],
+          count,
+        );
+      }
+
+      exposition.sample(
+        "ord_http_request_duration_seconds_bucket",
+        &[("route", route), ("le", "+Inf")],
+        metrics.count,
+      );
+
+      exposition.sample(
+        "ord_http_request_duration_seconds_sum",
+        &[("route", route)],
+        metrics.sum,
+      );
+
+      exposition.sample(
+        "ord_http_request_duration_seconds_count",
+        &[("route", route)],
+        metrics.count,
+      );
+    }
+  }
+}
+
+/// A document in the Prometheus text exposition format.
+#[derive(Default)]
+struct Exposition(String);
+
+impl Exposition {
+  fn header(&mut self, name: &str, kind: &str, help: &str) {
+    writeln!(self.0, "# HELP {name} {help}").unwrap();
+    writeln!(self.0, "# TYPE {name} {kind}").unwrap();
+  }
+
+  fn metric(&mut self, name: &str, kind: &str, help: &str, value: impl Display) {
+    self.header(name, kind, help);
+    self.sample(name, &[], value);
+  }
+
+  fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+    self.0.push_str(name);
+
+    if !labels.is_empty() {
+      self.0.push('{');
+
+      for (i, (label, value)) in labels.iter().enumerate() {
+        if i > 0 {
+          self.0.push(',');
+        }
+
+        write!(self.0, "{label}=\"").unwrap();
+
+        for c in value.chars() {
+          match c {
+            '\\' => self.0.push_str("\\\\"),
+            '"' => self.0.push_str("\\\""),
+            '\n' => self.0.push_str("\\n"),
+            c => self.0.push(c),
+          }
+        }
+
+        self.0.push('"');
+      }
+
+      self.0.push('}');
+    }
+
+    writeln!(self.0, " {value}").unwrap();
+  }
+}
+
+/// Render index, bitcoind, and request metrics.
+pub(crate) fn render(index: &Index, request_metrics: &RequestMetrics) -> Result<String> {
+  let mut exposition = Exposition::default();
+
+  let index_block_count = index.block_count()?;
+
+  exposition.metric(
+    "ord_index_block_count",
+    "gauge",
+    "Number of blocks in the index.",
+    index_block_count,
+  );
+
+  match index.client.get_block_count() {
+    Ok(bitcoind_block_count) => {
+      let bitcoind_block_count = bitcoind_block_count + 1;
+
+      exposition.metric(
+        "ord_bitcoind_up",
+        "gauge",
+        "Whether bitcoind responded to the last request.",
+        1,
+      );
+
+      exposition.metric(
+        "ord_bitcoind_block_count",
+        "gauge",
+        "Number of blocks in the bitcoind best chain.",
+        bitcoind_block_count,
+      );
+
+      exposition.metric(
+        "ord_index_lag_blocks",
+        "gauge",
+        "Number of blocks bitcoind has that have not yet been indexed.",
+        bitcoind_block_count.saturating_sub(index_block_count.into()),
+      );
+    }
+    Err(err) => {
+      log::warn!("failed to get bitcoind block count: {err}");
+
+      exposition.metric(
+        "ord_bitcoind_up",
+        "gauge",
+        "Whether bitcoind responded to the last request.",
+        0,
+      );
+    }
+  }
+
+  let metrics = index.metrics();
+
+  exposition.metric(
+    "ord_index_blocks_indexed_total",
+    "counter",
+    "Blocks indexed since the server started.",
+    Metrics::get(&metrics.blocks_indexed),
+  );
+
+  exposition.metric(
+    "ord_index_indexing_seconds_total",
+    "counter",
+    "Time spent indexing blocks since the server started.",
+    metrics.indexing_time().as_secs_f64(),
+  );
+
+  exposition.metric(
+    "ord_index_blocks_per_second",
+    "gauge",
+    "Blocks indexed per second of time spent indexing since the server started.",
+    if metrics.indexing_time().is_zero() {
+      0.0
+    } else {
+      Metrics::get(&metrics.blocks_indexed) as f64 / metrics.indexing_time().as_secs_f64()
+    },
+  );
+
+  exposition.metric(
+    "ord_index_outputs_traversed_total",
+    "counter",
+    "Outputs traversed since the server started.",
+    Metrics::get(&metrics.outputs_traversed),
+  );
+
+  exposition.header(
+    "ord_index_reorgs_total",
+    "counter",
+    "Reorgs detected since the server started.",
+  );
+
+  exposition.sample(
+    "ord_index_reorgs_total",
+    &[("kind", "recoverable")],
+    Metrics::get(&metrics.recoverable_reorgs),
+  );
+
+  exposition.sample(
+    "ord_index_reorgs_total",
+    &[("kind", "unrecoverable")],
+    Metrics::get(&metrics.unrecoverable_reorgs),
+  );
+
+  exposition.metric(
+    "ord_index_unrecoverably_reorged",
+    "gauge",
+    "Whether the index has been unrecoverably reorged.",
+    u8::from(index.is_unrecoverably_reorged()),
+  );
+
+  exposition.header(
+    "ord_rpc_errors_total",
+    "counter",
+    "Failed bitcoind requests made while indexing, by request.",
+  );
+
+  exposition.sample(
+    "ord_rpc_errors_total",
+    &[("request", "block")],
+    Metrics::get(&metrics.block_fetch_errors),
+  );
+
+  exposition.sample(
+    "ord_rpc_errors_total",
+    &[("request", "transaction")],
+    Metrics::get(&metrics.transaction_fetch_errors),
+  );
+
+  exposition.metric(
+    "ord_index_file_bytes",
+    "gauge",
+    "Size of the index file.",
+    index.index_file_size()?,
+  );
+
+  let tables = index.table_info()?;
+
+  exposition.header(
+    "ord_index_table_stored_bytes",
+    "gauge",
+    "Bytes of user data stored in each index table.",
+  );
+
+  for (name, table) in &tables {
+    exposition.sample(
+      "ord_index_table_stored_bytes",
+      &[("table", name)],
+      table.stored_bytes,
+    );
+  }
+
+  exposition.header(
+    "ord_index_table_total_bytes",
+    "gauge",
+    "Bytes used by each index table, including metadata and fragmentation.",
+  );
+
+  for (name, table) in &tables {
+    exposition.sample(
+      "ord_index_table_total_bytes",
+      &[("table", name)],
+      table.total_bytes,
+    );
+  }
+
+  request_metrics.write(&mut exposition);
+
+  Ok(exposition.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn label_values_are_escaped() {
+    let mut exposition = Exposition::default();
+
+    exposition.sample("foo", &[("a", "b"), ("c", "\"\\\n")], 1);
+
+    assert_eq!(exposition.0, "foo{a=\"b\",c=\"\\\"\\\\\\n\"} 1\n");
+  }
+
+  #[test]
+  fn requests_are_recorded_in_histogram() {
+    let request_metrics = RequestMetrics::default();
+
+    request_metrics.record("/foo", StatusCode::OK, Duration::from_millis(20));
+    request_metrics.record("/foo", StatusCode::NOT_FOUND, Duration::from_secs(20));
+
+    let mut exposition = Exposition::default();
+
+    request_metrics.write(&mut exposition);
+
+    for line in [
+      "ord_http_requests_total{route=\"/foo\",status=\"200\"} 1",
+      "ord_http_requests_total{route=\"/foo\",status=\"404\"} 1",
+      "ord_http_request_duration_seconds_bucket{route=\"/foo\",le=\"0.01\"} 0",
+      "ord_http_request_duration_seconds_bucket{route=\"/foo\",le=\"0.025\"} 1",
+      "ord_http_request_duration_seconds_bucket{route=\"/foo\",le=\"10\"} 1",
+      "ord_http_request_duration_seconds_bucket{route=\"/foo\",le=\"+Inf\"} 2",
+      "ord_http_request_duration_seconds_sum{route=\"/foo\"} 20.02",
+      "ord_http_request_duration_seconds_count{route=\"/foo\"} 2",
+    ] {
+      assert!(
+        exposition.0.lines().any(|l| l == line),
+        "missing line `{line}` in:\n{}",
+        exposition.0
+      );
+    }
+  }
+}
@@ -0,0 +1,625 @@
+use {
+  super::*,
+  schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+  },
+  serde_json::{json, Map, Value},
+};
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+  generator.subschema_for::<T>()
+}
+
+pub(super) struct Endpoint {
+  pub(super) method: &'static str,
+  pub(super) path: &'static str,
+  query: &'static [&'static str],
+  request: Option<SchemaFn>,
+  response: SchemaFn,
+  summary: &'static str,
+}
+
+const fn get(path: &'static str, summary: &'static str, response: SchemaFn) -> Endpoint {
+  Endpoint {
+    method: "get",
+    path,
+    query: &[],
+    request: None,
+    response,
+    summary,
+  }
+}
+
+const fn post(
+  path: &'static str,
+  summary: &'static str,
+  request: SchemaFn,
+  response: SchemaFn,
+) -> Endpoint {
+  Endpoint {
+    method: "post",
+    path,
+    query: &[],
+    request: Some(request),
+    response,
+    summary,
+  }
+}
+
+impl Endpoint {
+  const fn query(self, query: &'static [&'static str]) -> Self {
+    Self { query, ..self }
+  }
+
+  fn operation(&self, generator: &mut SchemaGenerator) -> Value {
+    let mut parameters = self
+      .path
+      .split('/')
+      .filter_map(|segment| segment.strip_prefix(':'))
+      .map(|name| {
+        json!({
+          "name": name,
+          "in": "path",
+          "required": true,
+          "schema": { "type": "string" },
+        })
+      })
+      .collect::<Vec<Value>>();
+
+    parameters.extend(self.query.iter().map(|name| {
+      json!({
+        "name": name,
+        "in": "query",
+        "schema": { "type": "string" },
+      })
+    }));
+
+    let mut operation = json!({
+      "summary": self.summary,
+      "parameters": parameters,
+      "responses": {
+        "200": {
+          "description": "OK",
+          "content": {
+            "application/json": {
+              "schema": (self.response)(generator),
+            },
+          },
+        },
+      },
+    });
+
+    if !self.path.starts_with("/r/") {
+      operation["description"] = "Requires the `Accept: application/json` header.".into();
+    }
+
+    if let Some(request) = self.request {
+      operation["requestBody"] = json!({
+        "required": true,
+        "content": {
+          "application/json": {
+            "schema": request(generator),
+          },
+        },
+      });
+    }
+
+    operation
+  }
+
+  /// The path in OpenAPI syntax, with `{name}` instead of `:name`.
+  pub(super) fn openapi_path(&self) -> String {
+    self
+      .path
+      .split('/')
+      .map(|segment| match segment.strip_prefix(':') {
+        Some(name) => format!("{{{name}}}"),
+        None => segment.into(),
+      })
+      .collect::<Vec<String>>()
+      .join("/")
+  }
+}
+
+const INSCRIPTION_FILTERS: &[&str] = &[
+  "charm",
+  "content_type",
+  "max_height",
+  "metaprotocol",
+  "min_height",
+  "order",
+  "parent",
+];
+
+/// Routes which return JSON, in the same order as the router.
+pub(super) const ENDPOINTS: &[Endpoint] = &[
+  get(
+    "/address/:address",
+    "Inscriptions, outputs, and balances of an address",
+    schema::<api::AddressInfo>,
+  ),
+  get(
+    "/block/:query",
+    "Block by height or hash",
+    schema::<api::Block>,
+  ),
+  get(
+    "/blocks",
+    "Latest blocks and their featured inscriptions",
+    schema::<api::Blocks>,
+  ),
+  get(
+    "/inscription/:inscription_query",
+    "Inscription by ID or number",
+    schema::<api::Inscription>,
+  ),
+  get(
+    "/inscriptions",
+    "Latest inscriptions",
+    schema::<api::Inscriptions>,
+  )
+  .query(INSCRIPTION_FILTERS),
+  post(
+    "/inscriptions",
+    "Inscriptions by ID",
+    schema::<Vec<InscriptionId>>,
+    schema::<Vec<api::Inscription>>,
+  ),
+  get(
+    "/inscriptions/:page",
+    "Page of inscriptions",
+    schema::<api::Inscriptions>,
+  )
+  .query(INSCRIPTION_FILTERS),
+  get(
+    "/inscriptions/block/:height",
+    "Inscriptions in a block",
+    schema::<api::Inscriptions>,
+  ),
+  get(
+    "/inscriptions/block/:height/:page",
+    "Page of inscriptions in a block",
+    schema::<api::Inscriptions>,
+  ),
+  get("/output/:output", "Output", schema::<api::Output>),
+  post(
+    "/outputs",
+    "Outputs by outpoint",
+    schema::<Vec<api::schema::OutPoint>>,
+    schema::<Vec<api::Output>>,
+  ),
+  get("/r/blockhash", "Hash of the latest block", schema::<String>),
+  get(
+    "/r/blockhash/:height",
+    "Hash of the block at a height",
+    schema::<String>,
+  ),
+  get(
+    "/r/blockinfo/:query",
+    "Block details by height or hash",
+    schema::<api::BlockInfo>,
+  ),
+  get(
+    "/r/inscription/:inscription_id",
+    "Inscription details for recursive inscriptions",
+    schema::<api::InscriptionRecursive>,
+  ),
+  get(
+    "/r/children/:inscription_id",
+    "Children of an inscription",
+    schema::<api::Children>,
+  ),
+  get(
+    "/r/children/:inscription_id/:page",
+    "Page of children of an inscription",
+    schema::<api::Children>,
+  ),
+  get(
+    "/r/metadata/:inscription_id",
+    "Hex-encoded CBOR metadata of an inscription",
+    schema::<String>,
+  ),
+  get(
+    "/r/output/:outpoint",
+    "Output details for recursive inscriptions",
+    schema::<api::OutputRecursive>,
+  ),
+  get(
+    "/r/rune/:rune",
+    "Rune details for recursive inscriptions",
+    schema::<api::RuneRecursive>,
+  ),
+  get(
+    "/r/rune/:rune/balance/:outpoint",
+    "Balance of a rune in an output",
+    schema::<Pile>,
+  ),
+  get(
+    "/r/rune/:rune/balances",
+    "Outputs holding a rune",
+    schema::<api::RuneBalancesRecursive>,
+  ),
+  get(
+    "/r/rune/:rune/balances/:page",
+    "Page of outputs holding a rune",
+    schema::<api::RuneBalancesRecursive>,
+  ),
+  get(
+    "/r/sat/:sat_number",
+    "Inscriptions on a sat",
+    schema::<api::SatInscriptions>,
+  ),
+  get(
+    "/r/sat/:sat_number/:page",
+    "Page of inscriptions on a sat",
+    schema::<api::SatInscriptions>,
+  ),
+  get(
+    "/r/sat/:sat_number/at/:index",
+    "Inscription on a sat at an index",
+    schema::<api::SatInscription>,
+  ),
+  get(
+    "/r/sat/:sat_number/info",
+    "Sat details for recursive inscriptions",
+    schema::<api::SatRecursive>,
+  ),
+  get(
+    "/r/tx/:txid",
+    "Transaction and the height of the block it was confirmed in",
+    schema::<api::TransactionRecursive>,
+  ),
+  get("/rune/:rune", "Rune", schema::<api::Rune>),
+  get("/runes", "Latest runes", schema::<api::Runes>),
+  get("/runes/:page", "Page of runes", schema::<api::Runes>),
+  get(
+    "/runes/balances",
+    "Balances of every rune, by rune and outpoint",
+    schema::<BTreeMap<SpacedRune, BTreeMap<api::schema::OutPoint, u128>>>,
+  ),
+  get("/sat/:sat", "Sat", schema::<api::Sat>),
+  get(
+    "/search",
+    "Full-text inscription search",
+    schema::<api::Inscriptions>,
+  )
+  .query(&[
+    "q",
+    "charm",
+    "content_type",
+    "from_height",
+    "parent",
+    "to_height",
+    "page",
+  ]),
+  get("/status", "Server status", schema::<api::Status>),
+  get("/tx/:txid", "Transaction", schema::<api::Transaction>),
+  get(
+    "/usage",
+    "Request counts by API key, requires an admin API key",
+    schema::<UsageHtml>,
+  ),
+];
+
+pub(super) fn document() -> Value {
+  let mut generator = SchemaSettings::draft2019_09()
+    .with(|settings| settings.definitions_path = "#/components/schemas/".into())
+    .into_generator();
+
+  let mut paths = Map::new();
+
+  for endpoint in ENDPOINTS {
+    let operation = endpoint.operation(&mut generator);
+
+    paths
+      .entry(endpoint.openapi_path())
+      .or_insert_with(|| json!({}))
+      .as_object_mut()
+      .unwrap()
+      .insert(endpoint.method.into(), operation);
+  }
+
+  json!({
+    "openapi": "3.1.0",
+    "jsonSchemaDialect": "https://json-schema.org/draft/2019-09/schema",
+    "info": {
+      "title": "ord",
+      "description": "JSON API of the ord explorer.",
+      "version": env!("CARGO_PKG_VERSION"),
+    },
+    "paths": paths,
+    "components": {
+      "schemas": generator.definitions(),
+    },
+  })
+}
+
+/// Check that `value` matches `schema`, resolving references against the
+/// components of `document`. Object properties not listed in the schema are
+/// treated as errors, so that schemas which have drifted from the types they
+/// describe are caught.
+#[cfg(test)]
+pub(super) fn validate(
+  document: &Value,
+  schema: &Value,
+  value: &Value,
+  path: &str,
+) -> Result<(), String> {
+  if let Some(reference) = schema["$ref"].as_str() {
+    let name = reference
+      .strip_prefix("#/components/schemas/")
+      .ok_or_else(|| format!("{path}: unexpected reference {reference}"))?;
+
+    let schema = &document["components"]["schemas"][name];
+
+    if schema.is_null() {
+      return Err(format!("{path}: missing schema {name}"));
+    }
+
+    return validate(document, schema, value, path);
+  }
+
+  if let Some(schemas) = schema["anyOf"].as_array() {
+    if !schemas
+      .iter()
+      .any(|schema| validate(document, schema, value, path).is_ok())
+    {
+      return Err(format!("{path}: {value} matches no schema in {schemas:?}"));
+    }
+  }
+
+  if let Some(schemas) = schema["allOf"].as_array() {
+    for schema in schemas {
+      validate(document, schema, value, path)?;
+    }
+  }
+
+  let types = match &schema["type"] {
+    Value::String(instance_type) => vec![instance_type.as_str()],
+    Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+    _ => Vec::new(),
+  };
+
+  if !types.is_empty()
+    && !types.iter().any(|instance_type| match *instance_type {
+      "array" => value.is_array(),
+      "boolean" => value.is_boolean(),
+      "integer" => value.is_i64() || value.is_u64(),
+      "null" => value.is_null(),
+      "number" => value.is_number(),
+      "object" => value.is_object(),
+      "string" => value.is_string(),
+      _ => false,
+    })
+  {
+    return Err(format!("{path}: expected {types:?}, got {value}"));
+  }
+
+  if let Some(values) = schema["enum"].as_array() {
+    if !values.contains(value) {
+      return Err(format!("{path}: {value} not in {values:?}"));
+    }
+  }
+
+  if let (Some(pattern), Some(string)) = (schema["pattern"].as_str(), value.as_str()) {
+    if !Regex::new(pattern).unwrap().is_match(string) {
+      return Err(format!("{path}: {string} does not match {pattern}"));
+    }
+  }
+
+  if let Some(object) = value.as_object() {
+    if let Some(properties) = schema["properties"].as_object() {
+      for (key, value) in object {
+        let schema = properties
+          .get(key)
+          .ok_or_else(|| format!("{path}: unexpected property {key}"))?;
+
+        validate(document, schema, value, &format!("{path}.{key}"))?;
+      }
+    }
+
+    if let Some(required) = schema["required"].as_array() {
+      for key in required.iter().filter_map(Value::as_str) {
+        if !object.contains_key(key) {
+          return Err(format!("{path}: missing property {key}"));
+        }
+      }
+    }
+
+    if schema["additionalProperties"].is_object() {
+      for (key, value) in object {
+        validate(
+          document,
+          &schema["additionalProperties"],
+          value,
+          &format!("{path}.{key}"),
+        )?;
+      }
+    }
+  }
+
+  if let Some(array) = value.as_array() {
+    match &schema["items"] {
+      Value::Array(schemas) => {
+        if schemas.len() != array.len() {
+          return Err(format!("{path}: expected {} items", schemas.len()));
+        }
+
+        for (i, (schema, value)) in schemas.iter().zip(array).enumerate() {
+          validate(document, schema, value, &format!("{path}[{i}]"))?;
+        }
+      }
+      schema @ Value::Object(_) => {
+        for (i, value) in array.iter().enumerate() {
+          validate(document, schema, value, &format!("{path}[{i}]"))?;
+        }
+      }
+      _ => {}
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Routes which never return JSON, or which are not part of the API.
+  const OTHER_ROUTES: &[(&str, &str)] = &[
+    ("get", "/"),
+    ("get", "/blockcount"),
+    ("get", "/blockhash"),
+    ("get", "/blockhash/:height"),
+    ("get", "/blockheight"),
+    ("get", "/blocktime"),
+    ("get", "/bounties"),
+    ("get", "/children/:inscription_id"),
+    ("get", "/children/:inscription_id/:page"),
+    ("get", "/clock"),
+    ("get", "/collections"),
+    ("get", "/collections/:page"),
+    ("get", "/content/:inscription_id"),
+    ("get", "/events"),
+    ("get", "/faq"),
+    ("get", "/favicon.ico"),
+    ("get", "/feed.xml"),
+    ("get", "/input/:block/:transaction/:input"),
+    ("get", "/install.sh"),
+    ("get", "/metrics"),
+    ("get", "/openapi.json"),
+    ("get", "/ordinal/:sat"),
+    ("get", "/parents/:inscription_id"),
+    ("get", "/parents/:inscription_id/:page"),
+    ("get", "/preview/:inscription_id"),
+    ("get", "/r/blockheight"),
+    ("get", "/r/blocktime"),
+    ("get", "/r/undelegated-content/:inscription_id"),
+    ("get", "/range/:start/:end"),
+    ("get", "/rare.txt"),
+    ("get", "/search/*query"),
+    ("get", "/static/*path"),
+    ("get", "/update"),
+  ];
+
+  #[test]
+  fn every_json_route_is_documented() {
+    let routes = Server::routes()
+      .into_iter()
+      .map(|(method, path, _method_router)| (method.as_str().to_lowercase(), path.to_string()))
+      .collect::<BTreeSet<(String, String)>>();
+
+    assert!(routes.len() > ENDPOINTS.len());
+
+    let documented = ENDPOINTS
+      .iter()
+      .map(|endpoint| (endpoint.method.to_string(), endpoint.path.to_string()))
+      .collect::<BTreeSet<(String, String)>>();
+
+    let other = OTHER_ROUTES
+      .iter()
+      .map(|(method, path)| (method.to_string(), path.to_string()))
+      .collect::<BTreeSet<(String, String)>>();
+
+    assert_eq!(documented.len(), ENDPOINTS.len());
+    assert!(documented.is_disjoint(&other));
+
+    let undocumented = routes
+      .difference(&documented)
+      .filter(|route| !other.contains(*route))
+      .collect::<Vec<&(String, String)>>();
+
+    assert!(
+      undocumented.is_empty(),
+      "routes missing from OpenAPI document: {undocumented:?}"
+    );
+
+    let missing = documented
+      .union(&other)
+      .filter(|route| !routes.contains(*route))
+      .collect::<Vec<&(String, String)>>();
+
+    assert!(missing.is_empty(), "routes not in router: {missing:?}");
+  }
+
+  #[test]
+  fn references_resolve() {
+    let document = document();
+
+    let text = document.to_string();
+
+    for reference in Regex::new(r##""\$ref":"#/components/schemas/([^"]+)""##)
+      .unwrap()
+      .captures_iter(&text)
+    {
+      assert!(
+        document["components"]["schemas"][&reference[1]].is_object(),
+        "missing schema {}",
+        &reference[1]
+      );
+    }
+  }
+
+  #[test]
+  fn paths_use_openapi_syntax() {
+    let document = document();
+
+    assert_eq!(
+      document["paths"]["/inscriptions/block/{height}/{page}"]["get"]["parameters"][1]["name"],
+      "page"
+    );
+
+    assert!(document["paths"]["/inscriptions"]["get"].is_object());
+    assert!(document["paths"]["/inscriptions"]["post"]["requestBody"].is_object());
+  }
+
+  #[test]
+  fn validate_rejects_unexpected_properties() {
+    let document = document();
+
+    let schema = json!({ "$ref": "#/components/schemas/Children" });
+
+    assert_eq!(
+      validate(
+        &document,
+        &schema,
+        &json!({ "ids": [], "more": false, "page": 0 }),
+        "",
+      ),
+      Ok(())
+    );
+
+    assert_eq!(
+      validate(
+        &document,
+        &schema,
+        &json!({ "ids": [], "more": false, "page": 0, "foo": 1 }),
+        "",
+      ),
+      Err(": unexpected property foo".into())
+    );
+
+    assert_eq!(
+      validate(&document, &schema, &json!({ "ids": [], "more": false }), ""),
+      Err(": missing property page".into())
+    );
+
+    assert_eq!(
+      validate(
+        &document,
+        &schema,
+        &json!({ "ids": ["foo"], "more": false, "page": 0 }),
+        "",
+      ),
+      Err(format!(
+        ".ids[0]: foo does not match {}",
+        document["components"]["schemas"]["InscriptionId"]["pattern"]
+          .as_str()
+          .unwrap()
+      ))
+    );
+  }
+}
@@ -0,0 +1,630 @@
+use {
+  super::*,
+  crate::{settings::ApiKey, templates::KeyUsage},
+  axum::{extract::ConnectInfo, http::Request, middleware::Next},
+  std::net::{IpAddr, Ipv6Addr, SocketAddr},
+};
+
+const API_KEY_HEADER: &str = "x-api-key";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The client a request was made by, inserted into request extensions.
+#[derive(Clone, Debug)]
+pub(crate) enum Caller {
+  Anonymous,
+  Key(Arc<ApiKey>),
+}
+
+impl Caller {
+  pub(crate) fn is_admin(&self) -> bool {
+    matches!(self, Self::Key(api_key) if api_key.admin)
+  }
+}
+
+#[derive(Debug, PartialEq)]
+enum Rejection {
+  InvalidKey,
+  QuotaExceeded { retry_after: Duration },
+  RateLimited { retry_after: Duration },
+}
+
+impl IntoResponse for Rejection {
+  fn into_response(self) -> Response {
+    match self {
+      Self::InvalidKey => (StatusCode::UNAUTHORIZED, "invalid API key").into_response(),
+      Self::QuotaExceeded { retry_after } => (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.as_secs().to_string())],
+        "daily quota exceeded",
+      )
+        .into_response(),
+      Self::RateLimited { retry_after } => (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(
+          header::RETRY_AFTER,
+          retry_after.as_secs_f64().ceil().to_string(),
+        )],
+        "rate limit exceeded",
+      )
+        .into_response(),
+    }
+  }
+}
+
+/// A token bucket holding up to `limit` tokens, refilled at `limit` tokens per
+/// minute.
+#[derive(Debug)]
+struct Bucket {
+  tokens: f64,
+  updated: Instant,
+}
+
+impl Bucket {
+  fn new(limit: u32, now: Instant) -> Self {
+    Self {
+      tokens: limit.into(),
+      updated: now,
+    }
+  }
+
+  fn refill(&mut self, limit: u32, now: Instant) {
+    let rate = f64::from(limit) / 60.0;
+
+    self.tokens = (self.tokens + now.saturating_duration_since(self.updated).as_secs_f64() * rate)
+      .min(limit.into());
+
+    self.updated = now;
+  }
+
+  fn take(&mut self, limit: u32, now: Instant) -> Result<(), Duration> {
+    self.refill(limit, now);
+
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      Ok(())
+    } else {
+      Err(Duration::from_secs_f64(
+        (1.0 - self.tokens) * 60.0 / f64::from(limit),
+      ))
+    }
+  }
+
+  fn is_full(&mut self, limit: u32, now: Instant) -> bool {
+    self.refill(limit, now);
+    self.tokens >= f64::from(limit)
+  }
+}
+
+#[derive(Debug, Default)]
+struct KeyCounters {
+  bucket: Option<Bucket>,
+  day: u64,
+  requests: u64,
+  requests_today: u64,
+  throttled: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+  anonymous: HashMap<IpAddr, Bucket>,
+  anonymous_requests: u64,
+  anonymous_throttled: u64,
+  keys: HashMap<String, KeyCounters>,
+  pruned: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+  anonymous_rate_limit: Option<u32>,
+  counters: Mutex<Counters>,
+  keys: HashMap<String, Arc<ApiKey>>,
+  trusted_proxies: usize,
+}
+
+impl RateLimiter {
+  pub(crate) fn new(settings: &Settings) -> Self {
+    Self {
+      anonymous_rate_limit: settings.server_rate_limit(),
+      counters: Mutex::new(Counters::default()),
+      keys: settings
+        .api_keys()
+        .iter()
+        .map(|api_key| (api_key.key.clone(), Arc::new(api_key.clone())))
+        .collect(),
+      trusted_proxies: settings.server_trusted_proxies().into_usize(),
+    }
+  }
+
+  /// Whether API keys or an anonymous rate limit are configured. If not,
+  /// requests need not pass through the limiter.
+  pub(crate) fn is_enabled(&self) -> bool {
+    self.anonymous_rate_limit.is_some() || !self.keys.is_empty()
+  }
+
+  pub(crate) async fn middleware<B>(
+    Extension(rate_limiter): Extension<Arc<RateLimiter>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    mut request: Request<B>,
+    next: Next<B>,
+  ) -> Response {
+    let key = request
+      .headers()
+      .get(API_KEY_HEADER)
+      .or_else(|| request.headers().get(header::AUTHORIZATION))
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.strip_prefix("Bearer ").unwrap_or(value).trim());
+
+    let timestamp = SystemTime::now()
+      .duration_since(SystemTime::UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_secs();
+
+    let ip = rate_limiter.client_ip(address.ip(), request.headers());
+
+    match rate_limiter.check(key, ip, Instant::now(), timestamp) {
+      Ok(caller) => {
+        request.extensions_mut().insert(caller);
+        next.run(request).await
+      }
+      Err(rejection) => rejection.into_response(),
+    }
+  }
+
+  /// The address of the client which made a request to `peer`. Each trusted
+  /// proxy appends the address it received the request from to the
+  /// `Forwarded` or `X-Forwarded-For` header, so the client is the address
+  /// appended by the outermost trusted proxy. Addresses to the left of it may
+  /// have been forged by the client, and are ignored.
+  fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    if self.trusted_proxies == 0 {
+      return peer;
+    }
+
+    let forwarded = headers
+      .get_all(header::FORWARDED)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(','))
+      .filter_map(|element| {
+        element.split(';').find_map(|pair| {
+          let (name, value) = pair.trim().split_once('=')?;
+          name.eq_ignore_ascii_case("for").then(|| {
+            let value = value.trim_matches('"');
+            value
+              .strip_prefix('[')
+              .and_then(|value| value.split_once(']'))
+              .map(|(ip, _port)| ip)
+              .unwrap_or(value)
+          })
+        })
+      })
+      .collect::<Vec<&str>>();
+
+    let forwarded = if forwarded.is_empty() {
+      headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect()
+    } else {
+      forwarded
+    };
+
+    // unparseable addresses, like `unknown`, are kept so that they cannot
+    // shift a forged address into the client's position
+    let addresses = forwarded
+      .into_iter()
+      .map(|address| {
+        address.parse::<IpAddr>().ok().or_else(|| {
+          address
+            .parse::<SocketAddr>()
+            .ok()
+            .map(|address| address.ip())
+        })
+      })
+      .chain([Some(peer)])
+      .collect::<Vec<Option<IpAddr>>>();
+
+    addresses[addresses.len().saturating_sub(self.trusted_proxies + 1)].unwrap_or(peer)
+  }
+
+  /// Anonymous clients are rate limited by IPv4 address, or by IPv6 /64
+  /// network, since IPv6 clients are commonly assigned an entire /64.
+  fn bucket(ip: IpAddr) -> IpAddr {
+    match ip {
+      IpAddr::V4(_) => ip,
+      IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+        Some(ip) => IpAddr::V4(ip),
+        None => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & !u128::from(u64::MAX))),
+      },
+    }
+  }
+
+  /// Check whether a request made with `key` from `ip` at `now` may proceed.
+  /// `timestamp` is the current unix time, used to reset daily quotas.
+  fn check(
+    &self,
+    key: Option<&str>,
+    ip: IpAddr,
+    now: Instant,
+    timestamp: u64,
+  ) -> Result<Caller, Rejection> {
+    let mut counters = self.counters.lock().unwrap();
+
+    // Basic authentication credentials belong to `--server-username` and
+    // `--server-password`, not to an API key, so such requests are anonymous.
+    let key = key.filter(|key| !key.starts_with("Basic "));
+
+    let Some(key) = key else {
+      counters.anonymous_requests += 1;
+
+      if let Some(limit) = self.anonymous_rate_limit {
+        Self::prune(&mut counters, limit, now);
+
+        if let Err(retry_after) = counters
+          .anonymous
+          .entry(Self::bucket(ip))
+          .or_insert_with(|| Bucket::new(limit, now))
+          .take(limit, now)
+        {
+          counters.anonymous_throttled += 1;
+          return Err(Rejection::RateLimited { retry_after });
+        }
+      }
+
+      return Ok(Caller::Anonymous);
+    };
+
+    let api_key = self.keys.get(key).ok_or(Rejection::InvalidKey)?;
+
+    let counters = counters.keys.entry(api_key.name.clone()).or_default();
+
+    counters.requests += 1;
+
+    let day = timestamp / SECONDS_PER_DAY;
+
+    if counters.day != day {
+      counters.day = day;
+      counters.requests_today = 0;
+    }
+
+    if let Some(quota) = api_key.quota {
+      if counters.requests_today >= quota {
+        counters.throttled += 1;
+        return Err(Rejection::QuotaExceeded {
+          retry_after: Duration::from_secs(SECONDS_PER_DAY - timestamp % SECONDS_PER_DAY),
+        });
+      }
+    }
+
+    if let Some(limit) = api_key.rate_limit {
+      if let Err(retry_after) = counters
+        .bucket
+        .get_or_insert_with(|| Bucket::new(limit, now))
+        .take(limit, now)
+      {
+        counters.throttled += 1;
+        return Err(Rejection::RateLimited { retry_after });
+      }
+    }
+
+    counters.requests_today += 1;
+
+    Ok(Caller::Key(api_key.clone()))
+  }
+
+  /// Forget anonymous clients whose buckets have refilled, at most once a
+  /// minute, so that memory use is bounded by recently active clients.
+  fn prune(counters: &mut Counters, limit: u32, now: Instant) {
+    if counters
+      .pruned
+      .is_some_and(|pruned| now.saturating_duration_since(pruned) < Duration::from_secs(60))
+    {
+      return;
+    }
+
+    counters
+      .anonymous
+      .retain(|_ip, bucket| !bucket.is_full(limit, now));
+
+    counters.pruned = Some(now);
+  }
+
+  pub(crate) fn usage(&self) -> UsageHtml {
+    let counters = self.counters.lock().unwrap();
+
+    let mut keys = self
+      .keys
+      .values()
+      .map(|api_key| {
+        let key_counters = counters.keys.get(&api_key.name);
+
+        KeyUsage {
+          admin: api_key.admin,
+          name: api_key.name.clone(),
+          quota: api_key.quota,
+          rate_limit: api_key.rate_limit,
+          requests: key_counters.map(|c| c.requests).unwrap_or_default(),
+          requests_today: key_counters.map(|c| c.requests_today).unwrap_or_default(),
+          throttled: key_counters.map(|c| c.throttled).unwrap_or_default(),
+        }
+      })
+      .collect::<Vec<KeyUsage>>();
+
+    keys.sort_by(|a, b| a.name.cmp(&b.name));
+
+    UsageHtml {
+      anonymous_clients: counters.anonymous.len(),
+      anonymous_rate_limit: self.anonymous_rate_limit,
+      anonymous_requests: counters.anonymous_requests,
+      anonymous_throttled: counters.anonymous_throttled,
+      keys,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const IP: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+  fn rate_limiter(config: &str) -> RateLimiter {
+    RateLimiter::new(
+      &serde_yaml::from_str::<Settings>(config)
+        .unwrap()
+        .or_defaults()
+        .unwrap(),
+    )
+  }
+
+  #[test]
+  fn disabled_without_keys_or_rate_limit() {
+    assert!(!rate_limiter("{}").is_enabled());
+    assert!(rate_limiter("server_rate_limit: 1").is_enabled());
+    assert!(rate_limiter("api_keys: [{key: foo, name: bar}]").is_enabled());
+  }
+
+  #[test]
+  fn anonymous_requests_are_rate_limited_per_ip() {
+    let rate_limiter = rate_limiter("server_rate_limit: 2");
+
+    let now = Instant::now();
+    let other = IpAddr::V4(std::net::Ipv4Addr::new(1, 2, 3, 4));
+
+    assert!(rate_limiter.check(None, IP, now, 0).is_ok());
+    assert!(rate_limiter.check(None, IP, now, 0).is_ok());
+    assert_eq!(
+      rate_limiter.check(None, IP, now, 0).unwrap_err(),
+      Rejection::RateLimited {
+        retry_after: Duration::from_secs(30)
+      }
+    );
+    assert!(rate_limiter.check(None, other, now, 0).is_ok());
+    assert!(rate_limiter
+      .check(None, IP, now + Duration::from_secs(30), 0)
+      .is_ok());
+
+    let usage = rate_limiter.usage();
+    assert_eq!(usage.anonymous_requests, 5);
+    assert_eq!(usage.anonymous_throttled, 1);
+    assert_eq!(usage.anonymous_clients, 2);
+  }
+
+  #[test]
+  fn ipv6_clients_are_rate_limited_per_network() {
+    let rate_limiter = rate_limiter("server_rate_limit: 1");
+
+    let now = Instant::now();
+
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+    assert!(rate_limiter.check(None, ip("2001:db8::1"), now, 0).is_ok());
+    assert!(rate_limiter.check(None, ip("2001:db8::2"), now, 0).is_err());
+    assert!(rate_limiter
+      .check(None, ip("2001:db8:0:1::1"), now, 0)
+      .is_ok());
+
+    assert!(rate_limiter
+      .check(None, ip("::ffff:1.2.3.4"), now, 0)
+      .is_ok());
+    assert!(rate_limiter
+      .check(None, ip("::ffff:1.2.3.5"), now, 0)
+      .is_ok());
+    assert!(rate_limiter.check(None, ip("1.2.3.4"), now, 0).is_err());
+  }
+
+  #[test]
+  fn forwarded_addresses_are_only_used_from_trusted_proxies() {
+    let peer = "10.0.0.1".parse::<IpAddr>().unwrap();
+
+    let client_ip = |config: &str, headers: &[(&'static str, &'static str)]| {
+      let mut header_map = HeaderMap::new();
+
+      for (name, value) in headers {
+        header_map.append(*name, HeaderValue::from_static(value));
+      }
+
+      rate_limiter(config)
+        .client_ip(peer, &header_map)
+        .to_string()
+    };
+
+    assert_eq!(
+      client_ip("{}", &[("x-forwarded-for", "1.1.1.1")]),
+      "10.0.0.1"
+    );
+
+    assert_eq!(
+      client_ip(
+        "server_trusted_proxies: 1",
+        &[("x-forwarded-for", "1.1.1.1, 2.2.2.2")]
+      ),
+      "2.2.2.2"
+    );
+
+    assert_eq!(
+      client_ip(
+        "server_trusted_proxies: 2",
+        &[
+          ("x-forwarded-for", "1.1.1.1"),
+          ("x-forwarded-for", "2.2.2.2")
+        ]
+      ),
+      "1.1.1.1"
+    );
+
+    assert_eq!(
+      client_ip(
+        "server_trusted_proxies: 3",
+        &[("x-forwarded-for", "1.1.1.1")]
+      ),
+      "1.1.1.1"
+    );
+
+    assert_eq!(
+      client_ip(
+        "server_trusted_proxies: 1",
+        &[("x-forwarded-for", "1.1.1.1, unknown")]
+      ),
+      "10.0.0.1"
+    );
+
+    assert_eq!(client_ip("server_trusted_proxies: 1", &[]), "10.0.0.1");
+
+    assert_eq!(
+      client_ip(
+        "server_trusted_proxies: 1",
+        &[
+          (
+            "forwarded",
+            "for=1.1.1.1, For=\"[2001:db8::1]:4711\";proto=https"
+          ),
+          ("x-forwarded-for", "3.3.3.3"),
+        ]
+      ),
+      "2001:db8::1"
+    );
+  }
+
+  #[test]
+  fn idle_anonymous_clients_are_pruned() {
+    let rate_limiter = rate_limiter("server_rate_limit: 60");
+
+    let now = Instant::now();
+
+    rate_limiter.check(None, IP, now, 0).unwrap();
+    assert_eq!(rate_limiter.usage().anonymous_clients, 1);
+
+    rate_limiter
+      .check(
+        None,
+        IpAddr::V4(std::net::Ipv4Addr::new(1, 2, 3, 4)),
+        now + Duration::from_secs(61),
+        0,
+      )
+      .unwrap();
+    assert_eq!(rate_limiter.usage().anonymous_clients, 1);
+  }
+
+  #[test]
+  fn invalid_keys_are_rejected() {
+    let rate_limiter = rate_limiter("api_keys: [{key: foo, name: bar}]");
+
+    assert_eq!(
+      rate_limiter
+        .check(Some("baz"), IP, Instant::now(), 0)
+        .unwrap_err(),
+      Rejection::InvalidKey
+    );
+  }
+
+  #[test]
+  fn keys_bypass_anonymous_rate_limit() {
+    let rate_limiter = rate_limiter("server_rate_limit: 1\napi_keys: [{key: foo, name: bar}]");
+
+    let now = Instant::now();
+
+    for _ in 0..10 {
+      assert!(matches!(
+        rate_limiter.check(Some("foo"), IP, now, 0).unwrap(),
+        Caller::Key(api_key) if api_key.name == "bar",
+      ));
+    }
+
+    assert!(rate_limiter.check(None, IP, now, 0).is_ok());
+    assert!(rate_limiter.check(None, IP, now, 0).is_err());
+  }
+
+  #[test]
+  fn keys_are_rate_limited() {
+    let rate_limiter = rate_limiter("api_keys: [{key: foo, name: bar, rate_limit: 1}]");
+
+    let now = Instant::now();
+
+    assert!(rate_limiter.check(Some("foo"), IP, now, 0).is_ok());
+    assert_eq!(
+      rate_limiter.check(Some("foo"), IP, now, 0).unwrap_err(),
+      Rejection::RateLimited {
+        retry_after: Duration::from_secs(60)
+      }
+    );
+    assert!(rate_limiter
+      .check(Some("foo"), IP, now + Duration::from_secs(60), 0)
+      .is_ok());
+  }
+
+  #[test]
+  fn quotas_reset_daily() {
+    let rate_limiter = rate_limiter("api_keys: [{key: foo, name: bar, quota: 2}]");
+
+    let now = Instant::now();
+
+    assert!(rate_limiter.check(Some("foo"), IP, now, 100).is_ok());
+    assert!(rate_limiter.check(Some("foo"), IP, now, 100).is_ok());
+    assert_eq!(
+      rate_limiter.check(Some("foo"), IP, now, 100).unwrap_err(),
+      Rejection::QuotaExceeded {
+        retry_after: Duration::from_secs(SECONDS_PER_DAY - 100)
+      }
+    );
+    assert!(rate_limiter
+      .check(Some("foo"), IP, now, SECONDS_PER_DAY)
+      .is_ok());
+
+    let usage = rate_limiter.usage();
+    assert_eq!(usage.keys[0].requests, 4);
+    assert_eq!(usage.keys[0].requests_today, 1);
+    assert_eq!(usage.keys[0].throttled, 1);
+  }
+
+  #[test]
+  fn basic_credentials_are_not_api_keys() {
+    let rate_limiter = rate_limiter("api_keys: [{key: foo, name: bar}]");
+
+    assert!(rate_limiter
+      .check(Some("Basic Zm9vOmJhcg=="), IP, Instant::now(), 0)
+      .is_ok_and(|caller| !caller.is_admin()));
+  }
+
+  #[test]
+  fn basic_credentials_are_anonymously_rate_limited() {
+    let rate_limiter = rate_limiter("server_rate_limit: 1");
+
+    let now = Instant::now();
+
+    assert!(rate_limiter
+      .check(Some("Basic Zm9vOmJhcg=="), IP, now, 0)
+      .is_ok());
+    assert!(rate_limiter
+      .check(Some("Basic YmF6OnF1eA=="), IP, now, 0)
+      .is_err());
+    assert!(rate_limiter.check(None, IP, now, 0).is_err());
+
+    let usage = rate_limiter.usage();
+    assert_eq!(usage.anonymous_requests, 3);
+    assert_eq!(usage.anonymous_throttled, 2);
+  }
+}
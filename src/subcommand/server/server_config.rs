@@ -1,4 +1,4 @@
-use {super::*, axum::http::HeaderName};
+use {super::*, crate::index::event::Event, axum::http::HeaderName};
 
 #[derive(Default)]
 pub(crate) struct ServerConfig {
@@ -7,8 +7,10 @@ pub(crate) struct ServerConfig {
   pub(crate) csp_origin: Option<String>,
   pub(crate) decompress: bool,
   pub(crate) domain: Option<String>,
+  pub(crate) event_broadcast: Option<tokio::sync::broadcast::Sender<Event>>,
   pub(crate) index_sats: bool,
   pub(crate) json_api_enabled: bool,
+  pub(crate) max_decompressed_size: usize,
 }
 
 impl ServerConfig {
@@ -7,6 +7,8 @@ use {
 
 pub mod balance;
 mod batch_command;
+pub mod broadcast;
+pub mod bump;
 pub mod cardinals;
 pub mod create;
 pub mod dump;
@@ -21,6 +23,7 @@ pub mod resume;
 pub mod sats;
 pub mod send;
 mod shared_args;
+pub mod split;
 pub mod transactions;
 
 #[derive(Debug, Parser)]
@@ -45,6 +48,10 @@ pub(crate) enum Subcommand {
   Balance,
   #[command(about = "Create inscriptions and runes")]
   Batch(batch_command::Batch),
+  #[command(about = "Finalize and broadcast PSBT")]
+  Broadcast(broadcast::Broadcast),
+  #[command(about = "Bump fee of unconfirmed transaction")]
+  Bump(bump::Bump),
   #[command(about = "List unspent cardinal outputs in wallet")]
   Cardinals,
   #[command(about = "Create new wallet")]
@@ -71,6 +78,8 @@ pub(crate) enum Subcommand {
   Sats(sats::Sats),
   #[command(about = "Send sat or inscription")]
   Send(send::Send),
+  #[command(about = "Send runes to many outputs")]
+  Split(split::Split),
   #[command(about = "See wallet transactions")]
   Transactions(transactions::Transactions),
 }
@@ -100,6 +109,8 @@ impl WalletCommand {
     match self.subcommand {
       Subcommand::Balance => balance::run(wallet),
       Subcommand::Batch(batch) => batch.run(wallet),
+      Subcommand::Broadcast(broadcast) => broadcast.run(wallet),
+      Subcommand::Bump(bump) => bump.run(wallet),
       Subcommand::Cardinals => cardinals::run(wallet),
       Subcommand::Create(_) | Subcommand::Restore(_) => unreachable!(),
       Subcommand::Dump => dump::run(wallet),
@@ -112,6 +123,7 @@ impl WalletCommand {
       Subcommand::Resume(resume) => resume.run(wallet),
       Subcommand::Sats(sats) => sats.run(wallet),
       Subcommand::Send(send) => send.run(wallet),
+      Subcommand::Split(split) => split.run(wallet),
       Subcommand::Transactions(transactions) => transactions.run(wallet),
     }
   }
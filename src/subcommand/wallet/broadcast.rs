@@ -0,0 +1,313 @@
+use {super::*, base64::Engine, bitcoin::psbt::Psbt};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Broadcast {
+  #[arg(help = "Finalize and broadcast base64-encoded <PSBT>.")]
+  psbt: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub txid: Txid,
+}
+
+impl Broadcast {
+  pub(crate) fn run(self, wallet: Wallet) -> SubcommandResult {
+    let psbt = Psbt::deserialize(
+      &base64::engine::general_purpose::STANDARD
+        .decode(&self.psbt)
+        .context("failed to decode PSBT")?,
+    )
+    .context("failed to deserialize PSBT")?;
+
+    let result = wallet.bitcoin_client().finalize_psbt(&self.psbt, None)?;
+
+    ensure!(result.complete, "PSBT is not fully signed");
+
+    let transaction: Transaction = consensus::encode::deserialize(
+      &result
+        .hex
+        .ok_or_else(|| anyhow!("failed to extract transaction from PSBT"))?,
+    )?;
+
+    ensure!(
+      transaction.txid() == psbt.unsigned_tx.txid(),
+      "finalized transaction {} does not match PSBT transaction {}",
+      transaction.txid(),
+      psbt.unsigned_tx.txid(),
+    );
+
+    let values = transaction
+      .input
+      .iter()
+      .zip(&psbt.inputs)
+      .map(|(txin, input)| {
+        wallet
+          .utxos()
+          .get(&txin.previous_output)
+          .or(input.witness_utxo.as_ref())
+          .map(|txout| txout.value)
+      })
+      .collect::<Vec<Option<u64>>>();
+
+    Self::verify(&transaction, wallet.inscriptions(), &values)?;
+
+    Self::check_commitment_maturity(&wallet, &transaction)?;
+
+    let txid = wallet.bitcoin_client().send_raw_transaction(&transaction)?;
+
+    Ok(Some(Box::new(Output { txid })))
+  }
+
+  /// Reveals which etch a rune may only be mined once their commitment has
+  /// matured, so refuse to broadcast them before then.
+  fn check_commitment_maturity(wallet: &Wallet, transaction: &Transaction) -> Result {
+    let Some(Artifact::Runestone(Runestone {
+      etching: Some(_), ..
+    })) = Runestone::decipher(transaction)
+    else {
+      return Ok(());
+    };
+
+    for txin in &transaction.input {
+      if txin.witness.tapscript().is_none() {
+        continue;
+      }
+
+      let commit = txin.previous_output.txid;
+
+      let confirmations = wallet
+        .bitcoin_client()
+        .get_transaction(&commit, Some(true))
+        .into_option()?
+        .map(|transaction| transaction.info.confirmations)
+        .unwrap_or_default();
+
+      ensure!(
+        confirmations + 1 >= Runestone::COMMIT_CONFIRMATIONS.into(),
+        "rune commitment {commit} has {confirmations} confirmations, but reveal may only be broadcast once it has {}",
+        Runestone::COMMIT_CONFIRMATIONS - 1,
+      );
+    }
+
+    Ok(())
+  }
+
+  fn verify(
+    transaction: &Transaction,
+    inscriptions: &BTreeMap<SatPoint, Vec<InscriptionId>>,
+    values: &[Option<u64>],
+  ) -> Result {
+    if let Some(Artifact::Cenotaph(cenotaph)) = Runestone::decipher(transaction) {
+      match cenotaph.flaw {
+        Some(flaw) => bail!("transaction contains cenotaph, runes would be burned: {flaw}"),
+        None => bail!("transaction contains cenotaph, runes would be burned"),
+      }
+    }
+
+    for envelope in ParsedEnvelope::from_transaction(transaction) {
+      let inscription = envelope.payload;
+
+      ensure!(
+        !inscription.unrecognized_even_field,
+        "inscription in input {} contains unrecognized even field",
+        envelope.input,
+      );
+
+      ensure!(
+        !inscription.duplicate_field,
+        "inscription in input {} contains duplicate field",
+        envelope.input,
+      );
+
+      ensure!(
+        !inscription.incomplete_field,
+        "inscription in input {} contains incomplete field",
+        envelope.input,
+      );
+    }
+
+    let mut input_offset = 0;
+
+    for (txin, value) in transaction.input.iter().zip(values) {
+      for (satpoint, inscription_ids) in inscriptions.range(
+        SatPoint {
+          outpoint: txin.previous_output,
+          offset: 0,
+        }..=SatPoint {
+          outpoint: txin.previous_output,
+          offset: u64::MAX,
+        },
+      ) {
+        let offset = input_offset + satpoint.offset;
+
+        let mut output_offset = 0;
+
+        let output = transaction.output.iter().enumerate().find(|(_, txout)| {
+          output_offset += txout.value;
+          offset < output_offset
+        });
+
+        for inscription_id in inscription_ids {
+          match output {
+            Some((vout, txout)) => ensure!(
+              !txout.script_pubkey.is_op_return(),
+              "inscription {inscription_id} would be burned in OP_RETURN output {vout}",
+            ),
+            None => bail!("inscription {inscription_id} would be lost to fees"),
+          }
+        }
+      }
+
+      input_offset += value.ok_or_else(|| {
+        anyhow!(
+          "unable to determine value of input {}",
+          txin.previous_output
+        )
+      })?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn transaction(input: Vec<TxIn>, output: Vec<TxOut>) -> Transaction {
+    Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input,
+      output,
+    }
+  }
+
+  #[test]
+  fn inscriptions_sent_to_outputs_are_accepted() {
+    let transaction = transaction(
+      vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+      vec![tx_out(10_000, recipient()), tx_out(10_000, change(0))],
+    );
+
+    Broadcast::verify(
+      &transaction,
+      &[(satpoint(2, 5_000), vec![inscription_id(1)])].into(),
+      &[Some(10_000), Some(10_000)],
+    )
+    .unwrap();
+  }
+
+  #[test]
+  fn inscriptions_sent_to_fees_are_rejected() {
+    let transaction = transaction(
+      vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+      vec![tx_out(10_000, recipient()), tx_out(5_000, change(0))],
+    );
+
+    assert_eq!(
+      Broadcast::verify(
+        &transaction,
+        &[(satpoint(2, 5_000), vec![inscription_id(1)])].into(),
+        &[Some(10_000), Some(10_000)],
+      )
+      .unwrap_err()
+      .to_string(),
+      format!("inscription {} would be lost to fees", inscription_id(1)),
+    );
+  }
+
+  #[test]
+  fn inscriptions_sent_to_op_return_are_rejected() {
+    let transaction = transaction(
+      vec![tx_in(outpoint(1))],
+      vec![
+        TxOut {
+          script_pubkey: script::Builder::new()
+            .push_opcode(opcodes::all::OP_RETURN)
+            .into_script(),
+          value: 5_000,
+        },
+        tx_out(5_000, recipient()),
+      ],
+    );
+
+    assert_eq!(
+      Broadcast::verify(
+        &transaction,
+        &[(satpoint(1, 0), vec![inscription_id(1)])].into(),
+        &[Some(10_000)],
+      )
+      .unwrap_err()
+      .to_string(),
+      format!(
+        "inscription {} would be burned in OP_RETURN output 0",
+        inscription_id(1)
+      ),
+    );
+  }
+
+  #[test]
+  fn unknown_input_values_are_rejected() {
+    let transaction = transaction(
+      vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+      vec![tx_out(10_000, recipient())],
+    );
+
+    assert_eq!(
+      Broadcast::verify(
+        &transaction,
+        &[(satpoint(2, 0), vec![inscription_id(1)])].into(),
+        &[None, Some(10_000)],
+      )
+      .unwrap_err()
+      .to_string(),
+      format!("unable to determine value of input {}", outpoint(1)),
+    );
+  }
+
+  #[test]
+  fn cenotaphs_are_rejected() {
+    let transaction = transaction(
+      vec![tx_in(outpoint(1))],
+      vec![TxOut {
+        script_pubkey: Runestone {
+          edicts: vec![Edict {
+            id: RuneId { block: 1, tx: 1 },
+            amount: 1,
+            output: 5,
+          }],
+          ..default()
+        }
+        .encipher(),
+        value: 0,
+      }],
+    );
+
+    assert_eq!(
+      Broadcast::verify(&transaction, &BTreeMap::new(), &[Some(10_000)])
+        .unwrap_err()
+        .to_string(),
+      "transaction contains cenotaph, runes would be burned: edict output greater than transaction output count",
+    );
+  }
+
+  #[test]
+  fn malformed_inscriptions_are_rejected() {
+    let mut txin = tx_in(outpoint(1));
+
+    txin.witness = envelope(&[b"ord", &[1], b"text/plain", &[1], b"text/plain"]);
+
+    assert_eq!(
+      Broadcast::verify(
+        &transaction(vec![txin], vec![tx_out(10_000, recipient())]),
+        &BTreeMap::new(),
+        &[Some(10_000)],
+      )
+      .unwrap_err()
+      .to_string(),
+      "inscription in input 0 contains duplicate field",
+    );
+  }
+}
@@ -0,0 +1,738 @@
+use {
+  super::*,
+  bitcoin::{
+    key::{PrivateKey, TapTweak, UntweakedKeyPair},
+    secp256k1::{self, constants::SCHNORR_SIGNATURE_SIZE, Secp256k1},
+    sighash::{Prevouts, SighashCache, TapSighashType},
+    taproot::{ControlBlock, Signature, TapLeafHash, TapNodeHash, TapTweakHash},
+  },
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Bump {
+  #[arg(
+    long,
+    help = "Pay for <TXID> with a child transaction instead of replacing it."
+  )]
+  cpfp: bool,
+  #[arg(long, help = "Don't sign or broadcast transaction.")]
+  dry_run: bool,
+  #[arg(long, help = "Use fee rate of <FEE_RATE> sats/vB.")]
+  fee_rate: FeeRate,
+  #[arg(help = "Bump fee of unconfirmed transaction <TXID>.")]
+  txid: Txid,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub original: Txid,
+  pub txid: Txid,
+  pub psbt: String,
+  pub fee: u64,
+}
+
+impl Bump {
+  pub(crate) fn run(self, wallet: Wallet) -> SubcommandResult {
+    if !self.dry_run {
+      wallet.ensure_can_sign()?;
+    }
+
+    let client = wallet.bitcoin_client();
+
+    let entry = client
+      .get_mempool_entry(&self.txid)
+      .with_context(|| format!("transaction {} not found in mempool", self.txid))?;
+
+    let original = client.get_raw_transaction(&self.txid, None)?;
+
+    let inscribed_utxos = wallet
+      .inscriptions()
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<BTreeSet<OutPoint>>();
+
+    let runic_utxos = wallet.get_runic_outputs()?;
+
+    let cardinal_utxos = wallet
+      .utxos()
+      .iter()
+      .filter(|(utxo, _)| {
+        !inscribed_utxos.contains(utxo)
+          && !runic_utxos.contains(utxo)
+          && !wallet.locked_utxos().contains_key(utxo)
+          && !original
+            .input
+            .iter()
+            .any(|tx_in| tx_in.previous_output == **utxo)
+      })
+      .map(|(utxo, tx_out)| (*utxo, tx_out.clone()))
+      .collect::<BTreeMap<OutPoint, TxOut>>();
+
+    let change = wallet.get_change_address()?;
+
+    let (mut unsigned_transaction, fee) = if self.cpfp {
+      ensure!(
+        self.fee_rate.fee(entry.ancestor_size.try_into()?) > entry.fees.ancestor,
+        "fee rate of {} sat/vB is not greater than the current fee rate of transaction {} and its unconfirmed ancestors",
+        self.fee_rate.n(),
+        self.txid,
+      );
+
+      let anchor = client
+        .list_unspent(Some(0), None, None, None, None)?
+        .into_iter()
+        .filter(|utxo| utxo.txid == self.txid)
+        .map(|utxo| utxo.vout)
+        .min()
+        .ok_or_else(|| {
+          anyhow!(
+            "transaction {} has no unspent wallet outputs to spend with child transaction",
+            self.txid
+          )
+        })?;
+
+      Self::child(
+        OutPoint {
+          txid: self.txid,
+          vout: anchor,
+        },
+        original.output[anchor.into_usize()].clone(),
+        entry.ancestor_size.try_into()?,
+        entry.fees.ancestor,
+        &cardinal_utxos,
+        change,
+        self.fee_rate,
+      )?
+    } else {
+      ensure!(
+        entry.bip125_replaceable,
+        "transaction {} does not signal replaceability",
+        self.txid,
+      );
+
+      ensure!(
+        entry.spent_by.is_empty(),
+        "transaction {} has unconfirmed descendants which replacing it would evict, use `--cpfp`",
+        self.txid,
+      );
+
+      ensure!(
+        !wallet
+          .pending_etchings()?
+          .iter()
+          .any(|(_, entry)| entry.commit.txid() == self.txid),
+        "transaction {} commits to a pending etching whose reveal replacing it would invalidate, use `--cpfp`",
+        self.txid,
+      );
+
+      ensure!(
+        self.fee_rate.fee(original.vsize()) > entry.fees.base,
+        "fee rate of {} sat/vB is not greater than the current fee rate of transaction {}",
+        self.fee_rate.n(),
+        self.txid,
+      );
+
+      Self::replacement(
+        &original,
+        entry.fees.base,
+        &cardinal_utxos,
+        change,
+        self.fee_rate,
+      )?
+    };
+
+    let parents = if unsigned_transaction
+      .input
+      .iter()
+      .any(|tx_in| Self::script_path_spend(&tx_in.witness).is_some())
+    {
+      let parents = unsigned_transaction
+        .input
+        .iter()
+        .map(|tx_in| tx_in.previous_output.txid)
+        .collect::<BTreeSet<Txid>>()
+        .into_iter()
+        .map(|txid| client.get_raw_transaction(&txid, None))
+        .collect::<Result<Vec<Transaction>, bitcoincore_rpc::Error>>()?;
+
+      let prevouts = unsigned_transaction
+        .input
+        .iter()
+        .map(|tx_in| {
+          parents
+            .iter()
+            .find(|parent| parent.txid() == tx_in.previous_output.txid)
+            .and_then(|parent| parent.output.get(tx_in.previous_output.vout.into_usize()))
+            .cloned()
+            .ok_or_else(|| anyhow!("input {} not found", tx_in.previous_output))
+        })
+        .collect::<Result<Vec<TxOut>>>()?;
+
+      let recovery_keys = client
+        .list_descriptors(Some(true))?
+        .descriptors
+        .into_iter()
+        .filter_map(|descriptor| {
+          let (wif, _checksum) = descriptor.desc.strip_prefix("rawtr(")?.split_once(')')?;
+          PrivateKey::from_wif(wif).ok()
+        })
+        .collect::<Vec<PrivateKey>>();
+
+      Self::sign_reveal(&mut unsigned_transaction, &prevouts, &recovery_keys).with_context(
+        || {
+          format!(
+            "unable to re-sign reveal transaction {}, use `--cpfp`",
+            self.txid
+          )
+        },
+      )?;
+
+      parents
+    } else {
+      Vec::new()
+    };
+
+    let psbt = wallet.psbt(
+      &unsigned_transaction,
+      &parents.iter().collect::<Vec<&Transaction>>(),
+    )?;
+
+    let (txid, psbt) = if self.dry_run {
+      (unsigned_transaction.txid(), psbt)
+    } else {
+      let psbt = client
+        .wallet_process_psbt(&psbt, Some(true), None, None)?
+        .psbt;
+
+      let signed_transaction = client
+        .finalize_psbt(&psbt, None)?
+        .hex
+        .ok_or_else(|| anyhow!("unable to sign transaction"))?;
+
+      (client.send_raw_transaction(&signed_transaction)?, psbt)
+    };
+
+    Ok(Some(Box::new(Output {
+      original: self.txid,
+      txid,
+      psbt,
+      fee: fee.to_sat(),
+    })))
+  }
+
+  /// Build a replacement for `original` which keeps its inputs and outputs in
+  /// place, so inscriptions stay on the same sats and the runestone assigns
+  /// runes to the same outputs. The higher fee is paid by cardinal inputs
+  /// added after the original inputs, with a change output added after the
+  /// original outputs. The witnesses of script-path inputs, which reveal
+  /// inscriptions, are kept so that their size is accounted for, and must be
+  /// re-signed with `sign_reveal`.
+  fn replacement(
+    original: &Transaction,
+    original_fee: Amount,
+    cardinal_utxos: &BTreeMap<OutPoint, TxOut>,
+    change: Address,
+    fee_rate: FeeRate,
+  ) -> Result<(Transaction, Amount)> {
+    let artifact = Runestone::decipher(original);
+
+    match &artifact {
+      Some(Artifact::Cenotaph(_)) => bail!("transaction contains cenotaph"),
+      Some(Artifact::Runestone(runestone)) => ensure!(
+        runestone
+          .edicts
+          .iter()
+          .all(|edict| edict.output.into_usize() != original.output.len()),
+        "runestone splits runes among all outputs, which would include the change output"
+      ),
+      None => {}
+    }
+
+    let mut transaction = original.clone();
+
+    for tx_in in &mut transaction.input {
+      if Self::script_path_spend(&tx_in.witness).is_none() {
+        tx_in.witness = Witness::new();
+      }
+    }
+
+    transaction.output.push(TxOut {
+      script_pubkey: change.script_pubkey(),
+      value: 0,
+    });
+
+    // BIP 125 requires replacements to pay for their own relay
+    let (transaction, fee) = Self::fund(transaction, original_fee, cardinal_utxos, |vsize| {
+      fee_rate
+        .fee(vsize)
+        .max(original_fee + Amount::from_sat(vsize.try_into().unwrap()))
+    })?;
+
+    assert_eq!(Runestone::decipher(&transaction), artifact);
+
+    Ok((transaction, fee))
+  }
+
+  /// Re-sign the script-path inputs of a reveal replacement. Reveal scripts are
+  /// signed with the one-time key that is the internal key of the commit
+  /// output, which is recovered from the backed-up recovery key, the tweaked
+  /// key of the commit output, by subtracting the taproot tweak.
+  fn sign_reveal(
+    transaction: &mut Transaction,
+    prevouts: &[TxOut],
+    recovery_keys: &[PrivateKey],
+  ) -> Result {
+    let secp256k1 = Secp256k1::new();
+
+    let mut sighash_cache = SighashCache::new(transaction);
+
+    for input in 0..prevouts.len() {
+      let Some((script, control_block)) =
+        Self::script_path_spend(&sighash_cache.transaction().input[input].witness)
+      else {
+        continue;
+      };
+
+      let leaf_hash = TapLeafHash::from_script(&script, control_block.leaf_version);
+
+      let merkle_root = control_block
+        .merkle_branch
+        .as_inner()
+        .iter()
+        .fold(TapNodeHash::from(leaf_hash), |node, sibling| {
+          TapNodeHash::from_node_hashes(node, *sibling)
+        });
+
+      let (output_key, _parity) = control_block
+        .internal_key
+        .tap_tweak(&secp256k1, Some(merkle_root));
+
+      let recovery_key = recovery_keys
+        .iter()
+        .find(|key| key.inner.x_only_public_key(&secp256k1).0 == output_key.to_inner())
+        .ok_or_else(|| anyhow!("recovery key for input {input} not found"))?;
+
+      let tweak =
+        TapTweakHash::from_key_and_tweak(control_block.internal_key, Some(merkle_root)).to_scalar();
+
+      let key_pair = UntweakedKeyPair::from_secret_key(
+        &secp256k1,
+        &recovery_key.inner.negate().add_tweak(&tweak)?.negate(),
+      );
+
+      assert_eq!(key_pair.x_only_public_key().0, control_block.internal_key);
+
+      let sighash = sighash_cache.taproot_script_spend_signature_hash(
+        input,
+        &Prevouts::All(prevouts),
+        leaf_hash,
+        TapSighashType::Default,
+      )?;
+
+      let sig = secp256k1.sign_schnorr(
+        &secp256k1::Message::from_slice(sighash.as_ref())?,
+        &key_pair,
+      );
+
+      let witness = sighash_cache
+        .witness_mut(input)
+        .expect("input should exist");
+
+      witness.clear();
+      witness.push(
+        Signature {
+          sig,
+          hash_ty: TapSighashType::Default,
+        }
+        .to_vec(),
+      );
+      witness.push(script);
+      witness.push(control_block.serialize());
+    }
+
+    Ok(())
+  }
+
+  fn script_path_spend(witness: &Witness) -> Option<(ScriptBuf, ControlBlock)> {
+    Some((
+      witness.tapscript()?.into(),
+      ControlBlock::decode(witness.last()?).ok()?,
+    ))
+  }
+
+  /// Build a child which spends `anchor` to an identical output, so any
+  /// inscriptions and runes it holds are kept, and pays for itself and its
+  /// unconfirmed ancestors at `fee_rate` with cardinal inputs.
+  fn child(
+    anchor: OutPoint,
+    anchor_output: TxOut,
+    ancestor_size: usize,
+    ancestor_fee: Amount,
+    cardinal_utxos: &BTreeMap<OutPoint, TxOut>,
+    change: Address,
+    fee_rate: FeeRate,
+  ) -> Result<(Transaction, Amount)> {
+    let transaction = Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input: vec![TxIn {
+        previous_output: anchor,
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::new(),
+      }],
+      output: vec![
+        anchor_output,
+        TxOut {
+          script_pubkey: change.script_pubkey(),
+          value: 0,
+        },
+      ],
+    };
+
+    Self::fund(transaction, Amount::ZERO, cardinal_utxos, |vsize| {
+      fee_rate.fee(vsize).max(
+        fee_rate
+          .fee(vsize + ancestor_size)
+          .checked_sub(ancestor_fee)
+          .unwrap_or_default(),
+      )
+    })
+  }
+
+  /// Add cardinal inputs to `transaction` until, together with `available`,
+  /// they pay `fee` and at least the dust value to the last output, which
+  /// receives the rest.
+  fn fund(
+    mut transaction: Transaction,
+    mut available: Amount,
+    cardinal_utxos: &BTreeMap<OutPoint, TxOut>,
+    fee: impl Fn(usize) -> Amount,
+  ) -> Result<(Transaction, Amount)> {
+    let mut candidates = cardinal_utxos
+      .iter()
+      .map(|(utxo, tx_out)| (Amount::from_sat(tx_out.value), *utxo))
+      .collect::<BTreeSet<(Amount, OutPoint)>>();
+
+    loop {
+      let fee = fee(Self::estimate_vsize(&transaction));
+
+      let change = transaction.output.last_mut().unwrap();
+
+      let required = fee + change.script_pubkey.dust_value();
+
+      if available >= required {
+        change.value = (available - fee).to_sat();
+        return Ok((transaction, fee));
+      }
+
+      let (value, utxo) = candidates
+        .iter()
+        .find(|(value, _utxo)| *value >= required - available)
+        .or_else(|| candidates.last())
+        .copied()
+        .ok_or_else(|| anyhow!("not enough cardinal utxos"))?;
+
+      candidates.remove(&(value, utxo));
+
+      transaction.input.push(TxIn {
+        previous_output: utxo,
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::new(),
+      });
+
+      available += value;
+    }
+  }
+
+  fn estimate_vsize(transaction: &Transaction) -> usize {
+    let mut transaction = transaction.clone();
+
+    for tx_in in &mut transaction.input {
+      if tx_in.witness.is_empty() {
+        tx_in.witness = Witness::from_slice(&[&[0; SCHNORR_SIGNATURE_SIZE]]);
+      }
+    }
+
+    transaction.vsize()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn original(outputs: Vec<TxOut>) -> Transaction {
+    Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input: vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+      output: outputs,
+    }
+  }
+
+  fn cardinals(utxos: &[(u64, u64)]) -> BTreeMap<OutPoint, TxOut> {
+    utxos
+      .iter()
+      .map(|(n, value)| (outpoint(*n), tx_out(*value, change(0))))
+      .collect()
+  }
+
+  #[test]
+  fn replacement_keeps_original_inputs_and_outputs_in_place() {
+    let original = original(vec![tx_out(10_000, recipient()), tx_out(5_000, change(0))]);
+
+    let (replacement, fee) = Bump::replacement(
+      &original,
+      Amount::from_sat(200),
+      &cardinals(&[(3, 20_000)]),
+      change(1),
+      FeeRate::try_from(10.0).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(replacement.input[..2], original.input[..]);
+    assert_eq!(replacement.input[2].previous_output, outpoint(3));
+    assert_eq!(replacement.output[..2], original.output[..]);
+    assert_eq!(
+      replacement.output[2].script_pubkey,
+      change(1).script_pubkey()
+    );
+    assert_eq!(replacement.output[2].value, 20_000 + 200 - fee.to_sat(),);
+    assert_eq!(
+      fee,
+      FeeRate::try_from(10.0)
+        .unwrap()
+        .fee(Bump::estimate_vsize(&replacement))
+    );
+  }
+
+  #[test]
+  fn replacement_pays_for_its_own_relay() {
+    let original = original(vec![tx_out(10_000, recipient())]);
+
+    let (replacement, fee) = Bump::replacement(
+      &original,
+      Amount::from_sat(1_000),
+      &cardinals(&[(3, 20_000)]),
+      change(1),
+      FeeRate::try_from(1.0).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      fee.to_sat(),
+      1_000 + u64::try_from(Bump::estimate_vsize(&replacement)).unwrap()
+    );
+  }
+
+  #[test]
+  fn replacement_uses_smallest_sufficient_cardinal() {
+    let original = original(vec![tx_out(10_000, recipient())]);
+
+    let (replacement, _fee) = Bump::replacement(
+      &original,
+      Amount::from_sat(200),
+      &cardinals(&[(3, 100_000), (4, 5_000), (5, 1_000)]),
+      change(1),
+      FeeRate::try_from(10.0).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(replacement.input.len(), 3);
+    assert_eq!(replacement.input[2].previous_output, outpoint(4));
+  }
+
+  #[test]
+  fn replacement_without_enough_cardinals_is_an_error() {
+    let original = original(vec![tx_out(10_000, recipient())]);
+
+    assert_eq!(
+      Bump::replacement(
+        &original,
+        Amount::from_sat(200),
+        &cardinals(&[(3, 500)]),
+        change(1),
+        FeeRate::try_from(10.0).unwrap(),
+      )
+      .unwrap_err()
+      .to_string(),
+      "not enough cardinal utxos",
+    );
+  }
+
+  #[test]
+  fn replacement_keeps_runestone() {
+    let runestone = Runestone {
+      edicts: vec![Edict {
+        id: RuneId { block: 1, tx: 1 },
+        amount: 100,
+        output: 1,
+      }],
+      ..default()
+    };
+
+    let original = original(vec![
+      TxOut {
+        script_pubkey: runestone.encipher(),
+        value: 0,
+      },
+      tx_out(10_000, recipient()),
+      tx_out(10_000, change(0)),
+    ]);
+
+    let (replacement, _fee) = Bump::replacement(
+      &original,
+      Amount::from_sat(200),
+      &cardinals(&[(3, 20_000)]),
+      change(1),
+      FeeRate::try_from(10.0).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      Runestone::decipher(&replacement),
+      Some(Artifact::Runestone(runestone)),
+    );
+  }
+
+  #[test]
+  fn replacement_of_runestone_splitting_among_all_outputs_is_an_error() {
+    let original = original(vec![
+      TxOut {
+        script_pubkey: Runestone {
+          edicts: vec![Edict {
+            id: RuneId { block: 1, tx: 1 },
+            amount: 100,
+            output: 2,
+          }],
+          ..default()
+        }
+        .encipher(),
+        value: 0,
+      },
+      tx_out(10_000, recipient()),
+    ]);
+
+    assert_eq!(
+      Bump::replacement(
+        &original,
+        Amount::from_sat(200),
+        &cardinals(&[(3, 20_000)]),
+        change(1),
+        FeeRate::try_from(10.0).unwrap(),
+      )
+      .unwrap_err()
+      .to_string(),
+      "runestone splits runes among all outputs, which would include the change output",
+    );
+  }
+
+  #[test]
+  fn sign_reveal_signs_reveal_script_with_key_recovered_from_recovery_key() {
+    let secp256k1 = Secp256k1::new();
+
+    let key_pair = UntweakedKeyPair::new(&secp256k1, &mut bitcoin::secp256k1::rand::thread_rng());
+    let (public_key, _parity) = key_pair.x_only_public_key();
+
+    let script = ScriptBuf::builder()
+      .push_slice(public_key.serialize())
+      .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG)
+      .into_script();
+
+    let taproot_spend_info = bitcoin::taproot::TaprootBuilder::new()
+      .add_leaf(0, script.clone())
+      .unwrap()
+      .finalize(&secp256k1, public_key)
+      .unwrap();
+
+    let control_block = taproot_spend_info
+      .control_block(&(script.clone(), bitcoin::taproot::LeafVersion::TapScript))
+      .unwrap();
+
+    let recovery_key = PrivateKey::new(
+      key_pair
+        .tap_tweak(&secp256k1, taproot_spend_info.merkle_root())
+        .to_inner()
+        .secret_key(),
+      Network::Bitcoin,
+    );
+
+    let prevouts = vec![
+      TxOut {
+        script_pubkey: ScriptBuf::new_v1_p2tr_tweaked(taproot_spend_info.output_key()),
+        value: 10_000,
+      },
+      tx_out(20_000, change(0)),
+    ];
+
+    let mut transaction = original(vec![tx_out(25_000, recipient())]);
+
+    transaction.input[0].witness = Witness::from_slice(&[
+      [0; SCHNORR_SIGNATURE_SIZE].as_slice(),
+      script.as_bytes(),
+      &control_block.serialize(),
+    ]);
+
+    Bump::sign_reveal(&mut transaction, &prevouts, &[recovery_key]).unwrap();
+
+    let witness = &transaction.input[0].witness;
+
+    assert_eq!(witness.len(), 3);
+    assert_eq!(witness.tapscript(), Some(script.as_script()));
+    assert_eq!(witness.last(), Some(control_block.serialize().as_slice()));
+    assert!(transaction.input[1].witness.is_empty());
+
+    let sighash = SighashCache::new(&transaction)
+      .taproot_script_spend_signature_hash(
+        0,
+        &Prevouts::All(&prevouts),
+        TapLeafHash::from_script(&script, control_block.leaf_version),
+        TapSighashType::Default,
+      )
+      .unwrap();
+
+    secp256k1
+      .verify_schnorr(
+        &Signature::from_slice(&witness[0]).unwrap().sig,
+        &secp256k1::Message::from_slice(sighash.as_ref()).unwrap(),
+        &public_key,
+      )
+      .unwrap();
+
+    assert_eq!(
+      Bump::sign_reveal(&mut transaction, &prevouts, &[])
+        .unwrap_err()
+        .to_string(),
+      "recovery key for input 0 not found",
+    );
+  }
+
+  #[test]
+  fn child_mirrors_anchor_and_pays_for_ancestors() {
+    let anchor_output = tx_out(10_000, change(0));
+
+    let (child, fee) = Bump::child(
+      outpoint(1),
+      anchor_output.clone(),
+      200,
+      Amount::from_sat(200),
+      &cardinals(&[(3, 20_000)]),
+      change(1),
+      FeeRate::try_from(10.0).unwrap(),
+    )
+    .unwrap();
+
+    let vsize = Bump::estimate_vsize(&child);
+
+    assert_eq!(child.input[0].previous_output, outpoint(1));
+    assert_eq!(child.input[1].previous_output, outpoint(3));
+    assert_eq!(child.output[0], anchor_output);
+    assert_eq!(child.output[1].value, 20_000 - fee.to_sat());
+    assert_eq!(
+      fee,
+      FeeRate::try_from(10.0).unwrap().fee(vsize + 200) - Amount::from_sat(200)
+    );
+  }
+}
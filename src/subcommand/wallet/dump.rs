@@ -1,6 +1,12 @@
 use super::*;
 
 pub(crate) fn run(wallet: Wallet) -> SubcommandResult {
+  if wallet.watch_only() {
+    return Ok(Some(Box::new(
+      wallet.bitcoin_client().list_descriptors(Some(false))?,
+    )));
+  }
+
   eprintln!(
     "==========================================
 = THIS STRING CONTAINS YOUR PRIVATE KEYS =
@@ -1,7 +1,9 @@
-use super::*;
+use {super::*, base64::Engine, bitcoin::psbt::Psbt};
 
 #[derive(Debug, Parser)]
 pub(crate) struct Mint {
+  #[clap(long, help = "Don't sign or broadcast transaction.")]
+  dry_run: bool,
   #[clap(long, help = "Use <FEE_RATE> sats/vbyte for mint transaction.")]
   fee_rate: FeeRate,
   #[clap(long, help = "Mint <RUNE>. May contain `.` or `•`as spacers.")]
@@ -20,6 +22,7 @@ pub struct Output {
   pub rune: SpacedRune,
   pub pile: Pile,
   pub mint: Txid,
+  pub psbt: Option<String>,
 }
 
 impl Mint {
@@ -29,6 +32,10 @@ impl Mint {
       "`ord wallet mint` requires index created with `--index-runes` flag",
     );
 
+    if !self.dry_run {
+      wallet.ensure_can_sign()?;
+    }
+
     let rune = self.rune.rune;
 
     let bitcoin_client = wallet.bitcoin_client();
@@ -92,6 +99,39 @@ impl Mint {
     let unsigned_transaction =
       fund_raw_transaction(bitcoin_client, self.fee_rate, &unfunded_transaction)?;
 
+    let pile = Pile {
+      amount,
+      divisibility: rune_entry.divisibility,
+      symbol: rune_entry.symbol,
+    };
+
+    if self.dry_run {
+      let unsigned_transaction: Transaction =
+        consensus::encode::deserialize(&unsigned_transaction)?;
+
+      assert_eq!(
+        Runestone::decipher(&unsigned_transaction),
+        Some(Artifact::Runestone(runestone)),
+      );
+
+      let psbt = bitcoin_client
+        .wallet_process_psbt(
+          &base64::engine::general_purpose::STANDARD
+            .encode(Psbt::from_unsigned_tx(unsigned_transaction.clone())?.serialize()),
+          Some(false),
+          None,
+          None,
+        )?
+        .psbt;
+
+      return Ok(Some(Box::new(Output {
+        rune: self.rune,
+        pile,
+        mint: unsigned_transaction.txid(),
+        psbt: Some(psbt),
+      })));
+    }
+
     let signed_transaction = bitcoin_client
       .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
       .hex;
@@ -107,12 +147,9 @@ impl Mint {
 
     Ok(Some(Box::new(Output {
       rune: self.rune,
-      pile: Pile {
-        amount,
-        divisibility: rune_entry.divisibility,
-        symbol: rune_entry.symbol,
-      },
+      pile,
       mint: transaction,
+      psbt: None,
     })))
   }
 }
@@ -12,6 +12,7 @@ pub(crate) struct Restore {
 enum Source {
   Descriptor,
   Mnemonic,
+  Xpub,
 }
 
 impl Restore {
@@ -47,6 +48,14 @@ impl Restore {
           mnemonic.to_seed(self.passphrase.unwrap_or_default()),
         )?;
       }
+      Source::Xpub => {
+        io::stdin().read_line(&mut buffer)?;
+        ensure!(
+          self.passphrase.is_none(),
+          "extended public key does not take a passphrase"
+        );
+        Wallet::initialize_from_xpub(name, settings, buffer.trim())?;
+      }
     }
 
     Ok(None)
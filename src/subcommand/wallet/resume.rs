@@ -22,6 +22,7 @@ impl Resume {
         if self.dry_run {
           etchings.push(batch::Output {
             reveal_broadcast: false,
+            reveal_psbt: Some(wallet.psbt(&entry.reveal, &[&entry.commit])?),
             ..entry.output.clone()
           });
           continue;
@@ -1,4 +1,9 @@
-use {super::*, crate::outgoing::Outgoing, base64::Engine, bitcoin::psbt::Psbt};
+use {
+  super::*,
+  crate::{outgoing::Outgoing, wallet::batch_transaction_builder::BatchTransactionBuilder},
+  base64::Engine,
+  bitcoin::psbt::Psbt,
+};
 
 #[derive(Debug, Parser)]
 pub(crate) struct Send {
@@ -11,8 +16,16 @@ pub(crate) struct Send {
     help = "Target <AMOUNT> postage with sent inscriptions. [default: 10000 sat]"
   )]
   pub(crate) postage: Option<Amount>,
-  address: Address<NetworkUnchecked>,
-  outgoing: Outgoing,
+  #[arg(
+    long,
+    help = "Send inscriptions and sats listed in YAML <BATCH> file in a single transaction.",
+    conflicts_with_all = ["address", "outgoing"]
+  )]
+  pub(crate) batch: Option<PathBuf>,
+  #[arg(required_unless_present = "batch")]
+  address: Option<Address<NetworkUnchecked>>,
+  #[arg(required_unless_present = "batch")]
+  outgoing: Option<Outgoing>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,14 +36,72 @@ pub struct Output {
   pub fee: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchOutput {
+  pub txid: Txid,
+  pub psbt: String,
+  pub sends: Vec<BatchEntry>,
+  pub fee: u64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct BatchFile {
+  sends: Vec<BatchEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BatchEntry {
+  pub destination: Address<NetworkUnchecked>,
+  pub outgoing: Outgoing,
+  pub postage: Option<u64>,
+}
+
+impl BatchFile {
+  fn load(path: &Path) -> Result<Self> {
+    Ok(serde_yaml::from_reader(fs::File::open(path)?)?)
+  }
+}
+
 impl Send {
   pub(crate) fn run(self, wallet: Wallet) -> SubcommandResult {
+    if !self.dry_run {
+      wallet.ensure_can_sign()?;
+    }
+
+    if let Some(batch) = &self.batch {
+      let batch = BatchFile::load(batch)?;
+
+      let unsigned_transaction = Self::create_unsigned_send_batch_transaction(
+        &wallet,
+        &batch.sends,
+        self.postage,
+        self.fee_rate,
+      )?;
+
+      let (txid, psbt, fee) = Self::send_transaction(&wallet, unsigned_transaction, self.dry_run)?;
+
+      return Ok(Some(Box::new(BatchOutput {
+        txid,
+        psbt,
+        sends: batch.sends,
+        fee,
+      })));
+    }
+
     let address = self
       .address
       .clone()
+      .expect("address is required without --batch")
       .require_network(wallet.chain().network())?;
 
-    let unsigned_transaction = match self.outgoing {
+    let outgoing = self
+      .outgoing
+      .clone()
+      .expect("outgoing is required without --batch");
+
+    let unsigned_transaction = match outgoing {
       Outgoing::Amount(amount) => {
         Self::create_unsigned_send_amount_transaction(&wallet, address, amount, self.fee_rate)?
       }
@@ -71,9 +142,26 @@ impl Send {
       )?,
     };
 
+    let (txid, psbt, fee) = Self::send_transaction(&wallet, unsigned_transaction, self.dry_run)?;
+
+    Ok(Some(Box::new(Output {
+      txid,
+      psbt,
+      outgoing,
+      fee,
+    })))
+  }
+
+  /// Sign and broadcast `unsigned_transaction`, unless `dry_run` is set, and
+  /// return its txid, PSBT, and fee.
+  fn send_transaction(
+    wallet: &Wallet,
+    unsigned_transaction: Transaction,
+    dry_run: bool,
+  ) -> Result<(Txid, String, u64)> {
     let unspent_outputs = wallet.utxos();
 
-    let (txid, psbt) = if self.dry_run {
+    let (txid, psbt) = if dry_run {
       let psbt = wallet
         .bitcoin_client()
         .wallet_process_psbt(
@@ -122,12 +210,76 @@ impl Send {
       fee = fee.checked_sub(txout.value).unwrap();
     }
 
-    Ok(Some(Box::new(Output {
-      txid,
-      psbt,
-      outgoing: self.outgoing,
-      fee,
-    })))
+    Ok((txid, psbt, fee))
+  }
+
+  fn create_unsigned_send_batch_transaction(
+    wallet: &Wallet,
+    sends: &[BatchEntry],
+    postage: Option<Amount>,
+    fee_rate: FeeRate,
+  ) -> Result<Transaction> {
+    ensure!(!sends.is_empty(), "batch file contains no sends");
+
+    let inscription_info = wallet.inscription_info();
+    let runic_outputs = wallet.get_runic_outputs()?;
+
+    let mut outgoing = Vec::new();
+
+    for send in sends {
+      let destination = send
+        .destination
+        .clone()
+        .require_network(wallet.chain().network())?;
+
+      let satpoint = match send.outgoing {
+        Outgoing::InscriptionId(id) => {
+          inscription_info
+            .get(&id)
+            .ok_or_else(|| anyhow!("inscription {id} not found"))?
+            .satpoint
+        }
+        Outgoing::SatPoint(satpoint) => {
+          for inscription_satpoint in wallet.inscriptions().keys() {
+            if satpoint == *inscription_satpoint {
+              bail!("inscriptions must be sent by inscription ID");
+            }
+          }
+
+          satpoint
+        }
+        Outgoing::Sat(sat) => wallet.find_sat_in_outputs(sat)?,
+        Outgoing::Amount(_) | Outgoing::Rune { .. } => bail!(
+          "batch sends may only contain inscriptions, sats, and satpoints, not `{}`",
+          send.outgoing
+        ),
+      };
+
+      ensure!(
+        !runic_outputs.contains(&satpoint.outpoint),
+        "runic outpoints may not be sent by satpoint"
+      );
+
+      let target = match send.postage.map(Amount::from_sat).or(postage) {
+        Some(postage) => Target::ExactPostage(postage),
+        None => Target::Postage,
+      };
+
+      outgoing.push((satpoint, destination, target));
+    }
+
+    Ok(
+      BatchTransactionBuilder::new(
+        outgoing,
+        wallet.inscriptions().clone(),
+        wallet.utxos().clone(),
+        wallet.locked_utxos().clone().into_keys().collect(),
+        runic_outputs,
+        wallet.get_change_address()?,
+        fee_rate,
+      )
+      .build_transaction()?,
+    )
   }
 
   fn create_unsigned_send_amount_transaction(
@@ -0,0 +1,752 @@
+use {super::*, bitcoin::secp256k1::constants::SCHNORR_SIGNATURE_SIZE};
+
+/// Bitcoin Core's default `-datacarriersize`. Standard OP_RETURN outputs may
+/// contain at most 82 bytes following the OP_RETURN opcode.
+const MAX_STANDARD_OP_RETURN_SIZE: usize = 83;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Split {
+  #[arg(long, help = "Don't sign or broadcast transactions")]
+  pub(crate) dry_run: bool,
+  #[arg(long, help = "Use fee rate of <FEE_RATE> sats/vB")]
+  fee_rate: FeeRate,
+  #[arg(
+    long,
+    help = "Send runes to outputs listed in YAML or CSV <SPLITS> file. CSV files must have `.csv` extension."
+  )]
+  splits: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Output {
+  pub transactions: Vec<Sent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sent {
+  pub txid: Txid,
+  pub psbt: String,
+  pub fee: u64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Splits {
+  outputs: Vec<Row>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Row {
+  address: Address<NetworkUnchecked>,
+  rune: SpacedRune,
+  amount: Decimal,
+  postage: Option<u64>,
+}
+
+/// Runes sent to a single address, which receives one output.
+#[derive(Debug, PartialEq)]
+struct Recipient {
+  address: Address,
+  postage: Option<Amount>,
+  runes: BTreeMap<RuneId, u128>,
+}
+
+impl Splits {
+  fn load(path: &Path) -> Result<Self> {
+    if path.extension().and_then(|extension| extension.to_str()) == Some("csv") {
+      Self::from_csv(&fs::read_to_string(path)?)
+    } else {
+      Ok(serde_yaml::from_reader(fs::File::open(path)?)?)
+    }
+  }
+
+  fn from_csv(csv: &str) -> Result<Self> {
+    let mut outputs = Vec::new();
+
+    for (i, line) in csv.lines().enumerate() {
+      let line = line.trim();
+
+      if line.is_empty() || (i == 0 && line.starts_with("address")) {
+        continue;
+      }
+
+      let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+
+      let (address, rune, amount, postage) = match fields.as_slice() {
+        [address, rune, amount] => (address, rune, amount, None),
+        [address, rune, amount, ""] => (address, rune, amount, None),
+        [address, rune, amount, postage] => (address, rune, amount, Some(postage)),
+        _ => bail!("line {}: expected `address,rune,amount[,postage]`", i + 1),
+      };
+
+      outputs.push(Row {
+        address: address
+          .parse()
+          .with_context(|| format!("line {}: invalid address", i + 1))?,
+        rune: rune
+          .parse()
+          .with_context(|| format!("line {}: invalid rune", i + 1))?,
+        amount: amount
+          .parse()
+          .with_context(|| format!("line {}: invalid amount", i + 1))?,
+        postage: postage
+          .map(|postage| postage.parse())
+          .transpose()
+          .with_context(|| format!("line {}: invalid postage", i + 1))?,
+      });
+    }
+
+    Ok(Self { outputs })
+  }
+}
+
+impl Split {
+  pub(crate) fn run(self, wallet: Wallet) -> SubcommandResult {
+    if !self.dry_run {
+      wallet.ensure_can_sign()?;
+    }
+
+    ensure!(
+      wallet.has_rune_index(),
+      "sending runes with `ord wallet split` requires index created with `--index-runes` flag",
+    );
+
+    let splits = Splits::load(&self.splits)?;
+
+    ensure!(
+      !splits.outputs.is_empty(),
+      "splits file contains no outputs"
+    );
+
+    let mut ids = BTreeMap::<Rune, RuneId>::new();
+    let mut entries = BTreeMap::<RuneId, RuneEntry>::new();
+    let mut recipients = Vec::<Recipient>::new();
+
+    for row in splits.outputs {
+      let address = row.address.require_network(wallet.chain().network())?;
+
+      let id = match ids.get(&row.rune.rune) {
+        Some(id) => *id,
+        None => {
+          let (id, entry, _parent) = wallet
+            .get_rune(row.rune.rune)?
+            .with_context(|| format!("rune `{}` has not been etched", row.rune.rune))?;
+          ids.insert(row.rune.rune, id);
+          entries.insert(id, entry);
+          id
+        }
+      };
+
+      let amount = row.amount.to_integer(entries[&id].divisibility)?;
+
+      ensure!(amount > 0, "cannot send zero `{}` to {address}", row.rune);
+
+      let postage = row.postage.map(Amount::from_sat);
+
+      if let Some(recipient) = recipients.iter_mut().find(|r| r.address == address) {
+        recipient.postage = recipient.postage.max(postage);
+        let total = recipient.runes.entry(id).or_default();
+        *total = total
+          .checked_add(amount)
+          .with_context(|| format!("`{}` sent to {address} overflows", row.rune))?;
+      } else {
+        recipients.push(Recipient {
+          address,
+          postage,
+          runes: [(id, amount)].into(),
+        });
+      }
+    }
+
+    for recipient in &recipients {
+      let postage = recipient.postage.unwrap_or(TARGET_POSTAGE);
+      let dust_value = recipient.address.script_pubkey().dust_value();
+      ensure!(
+        postage >= dust_value,
+        "postage of {postage} sent to {} is below dust limit of {dust_value}",
+        recipient.address,
+      );
+    }
+
+    let inscribed_outputs = wallet
+      .inscriptions()
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<HashSet<OutPoint>>();
+
+    let mut available = Vec::new();
+
+    for output in wallet.get_runic_outputs()? {
+      if inscribed_outputs.contains(&output) {
+        continue;
+      }
+
+      let mut balances = BTreeMap::<RuneId, u128>::new();
+
+      for (spaced_rune, pile) in wallet.get_runes_balances_for_output(&output)? {
+        if let Some(id) = ids.get(&spaced_rune.rune) {
+          *balances.entry(*id).or_default() += pile.amount;
+        }
+      }
+
+      if !balances.is_empty() {
+        available.push((output, balances));
+      }
+    }
+
+    let mut totals = BTreeMap::<RuneId, u128>::new();
+
+    for recipient in &recipients {
+      for (id, amount) in &recipient.runes {
+        *totals.entry(*id).or_default() += amount;
+      }
+    }
+
+    for (id, total) in totals {
+      let balance = available
+        .iter()
+        .filter_map(|(_, balances)| balances.get(&id))
+        .sum::<u128>();
+
+      let entry = &entries[&id];
+
+      ensure! {
+        balance >= total,
+        "insufficient `{}` balance, only {} in wallet",
+        entry.spaced_rune,
+        Pile {
+          amount: balance,
+          divisibility: entry.divisibility,
+          symbol: entry.symbol,
+        },
+      }
+    }
+
+    let batches = Self::pack(recipients)?;
+
+    wallet.lock_non_cardinal_outputs()?;
+
+    let mut funding = Vec::new();
+
+    let unsigned_transactions = Self::create_unsigned_split_transactions(
+      &wallet,
+      &batches,
+      available,
+      self.fee_rate,
+      &mut funding,
+    );
+
+    // cardinal inputs are only locked while creating transactions, so that
+    // later transactions do not spend them again
+    if !funding.is_empty() && !wallet.bitcoin_client().unlock_unspent(&funding)? {
+      bail!("failed to unlock UTXOs");
+    }
+
+    let unsigned_transactions = unsigned_transactions?;
+
+    let mut outputs = wallet.utxos().clone();
+
+    for unsigned_transaction in &unsigned_transactions {
+      outputs.insert(
+        OutPoint {
+          txid: unsigned_transaction.txid(),
+          vout: 1,
+        },
+        unsigned_transaction.output[1].clone(),
+      );
+    }
+
+    let mut transactions = Vec::new();
+
+    for (i, unsigned_transaction) in unsigned_transactions.iter().enumerate() {
+      let psbt = wallet.psbt(
+        unsigned_transaction,
+        &unsigned_transactions[..i]
+          .iter()
+          .collect::<Vec<&Transaction>>(),
+      )?;
+
+      let (txid, psbt) = if self.dry_run {
+        (unsigned_transaction.txid(), psbt)
+      } else {
+        let psbt = wallet
+          .bitcoin_client()
+          .wallet_process_psbt(&psbt, Some(true), None, None)?
+          .psbt;
+
+        let signed_tx = wallet
+          .bitcoin_client()
+          .finalize_psbt(&psbt, None)?
+          .hex
+          .ok_or_else(|| anyhow!("unable to sign transaction"))?;
+
+        (
+          wallet.bitcoin_client().send_raw_transaction(&signed_tx)?,
+          psbt,
+        )
+      };
+
+      let mut fee = 0;
+      for txin in unsigned_transaction.input.iter() {
+        let Some(txout) = outputs.get(&txin.previous_output) else {
+          panic!("input {} not found in utxos", txin.previous_output);
+        };
+        fee += txout.value;
+      }
+
+      for txout in unsigned_transaction.output.iter() {
+        fee = fee.checked_sub(txout.value).unwrap();
+      }
+
+      transactions.push(Sent { txid, psbt, fee });
+    }
+
+    Ok(Some(Box::new(Output { transactions })))
+  }
+
+  /// Group recipients, in order, into as few transactions as possible, such
+  /// that each transaction's runestone fits in a standard OP_RETURN output.
+  fn pack(recipients: Vec<Recipient>) -> Result<Vec<Vec<Recipient>>> {
+    let mut batches = Vec::<Vec<Recipient>>::new();
+
+    for mut recipient in recipients {
+      if let Some(batch) = batches.last_mut() {
+        batch.push(recipient);
+
+        if Self::runestone(batch).encipher().len() <= MAX_STANDARD_OP_RETURN_SIZE {
+          continue;
+        }
+
+        recipient = batch.pop().unwrap();
+      }
+
+      let batch = vec![recipient];
+
+      ensure!(
+        Self::runestone(&batch).encipher().len() <= MAX_STANDARD_OP_RETURN_SIZE,
+        "runestone for output to {} exceeds maximum standard OP_RETURN size of {MAX_STANDARD_OP_RETURN_SIZE} bytes",
+        batch[0].address,
+      );
+
+      batches.push(batch);
+    }
+
+    Ok(batches)
+  }
+
+  /// Create a runestone with one edict per rune sent to each recipient.
+  /// Output 0 is the runestone and output 1 receives unallocated runes, so
+  /// recipients start at output 2.
+  fn runestone(recipients: &[Recipient]) -> Runestone {
+    let mut edicts = recipients
+      .iter()
+      .enumerate()
+      .flat_map(|(i, recipient)| {
+        recipient.runes.iter().map(move |(id, amount)| Edict {
+          id: *id,
+          amount: *amount,
+          output: (i + 2).try_into().unwrap(),
+        })
+      })
+      .collect::<Vec<Edict>>();
+
+    edicts.sort_by_key(|edict| (edict.id, edict.output));
+
+    Runestone {
+      edicts,
+      ..default()
+    }
+  }
+
+  /// Create one transaction per batch. Each transaction after the first
+  /// spends output 1 of the one before it, which holds the runes left over
+  /// after its edicts, so a wallet holding a rune in a single output can fund
+  /// every transaction. Cardinal inputs added by funding are locked and
+  /// pushed to `funding`.
+  fn create_unsigned_split_transactions(
+    wallet: &Wallet,
+    batches: &[Vec<Recipient>],
+    mut available: Vec<(OutPoint, BTreeMap<RuneId, u128>)>,
+    fee_rate: FeeRate,
+    funding: &mut Vec<OutPoint>,
+  ) -> Result<Vec<Transaction>> {
+    let mut transactions = Vec::<Transaction>::new();
+    let mut leftover = None;
+
+    for batch in batches {
+      let chained = transactions.last().zip(leftover.take()).map(
+        |(previous, runes): (&Transaction, BTreeMap<RuneId, u128>)| {
+          (
+            OutPoint {
+              txid: previous.txid(),
+              vout: 1,
+            },
+            previous.output[1].clone(),
+            runes,
+          )
+        },
+      );
+
+      let (transaction, runes) = Self::create_unsigned_split_transaction(
+        wallet,
+        batch,
+        &mut available,
+        chained,
+        fee_rate,
+        funding,
+      )?;
+
+      transactions.push(transaction);
+      leftover = (!runes.is_empty()).then_some(runes);
+    }
+
+    Ok(transactions)
+  }
+
+  /// Create a transaction sending runes to `recipients`, returning it along
+  /// with the runes left over in output 1.
+  fn create_unsigned_split_transaction(
+    wallet: &Wallet,
+    recipients: &[Recipient],
+    available: &mut Vec<(OutPoint, BTreeMap<RuneId, u128>)>,
+    chained: Option<(OutPoint, TxOut, BTreeMap<RuneId, u128>)>,
+    fee_rate: FeeRate,
+    funding: &mut Vec<OutPoint>,
+  ) -> Result<(Transaction, BTreeMap<RuneId, u128>)> {
+    let mut needed = BTreeMap::<RuneId, u128>::new();
+
+    for recipient in recipients {
+      for (id, amount) in &recipient.runes {
+        *needed.entry(*id).or_default() += amount;
+      }
+    }
+
+    let mut inputs = Vec::new();
+    let mut input_runes = chained
+      .as_ref()
+      .map(|(_, _, runes)| runes.clone())
+      .unwrap_or_default();
+
+    available.retain(|(output, balances)| {
+      let useful = balances.iter().any(|(id, balance)| {
+        *balance > 0
+          && needed
+            .get(id)
+            .is_some_and(|needed| input_runes.get(id).copied().unwrap_or_default() < *needed)
+      });
+
+      if useful {
+        inputs.push(*output);
+        for (id, balance) in balances {
+          *input_runes.entry(*id).or_default() += balance;
+        }
+      }
+
+      !useful
+    });
+
+    for (id, amount) in &needed {
+      let balance = input_runes.entry(*id).or_default();
+      *balance = balance
+        .checked_sub(*amount)
+        .ok_or_else(|| anyhow!("insufficient balance of rune {id}"))?;
+    }
+
+    input_runes.retain(|_, balance| *balance > 0);
+
+    let runestone = Self::runestone(recipients);
+
+    let mut output = vec![
+      TxOut {
+        script_pubkey: runestone.encipher(),
+        value: 0,
+      },
+      TxOut {
+        script_pubkey: wallet.get_change_address()?.script_pubkey(),
+        value: TARGET_POSTAGE.to_sat(),
+      },
+    ];
+
+    for recipient in recipients {
+      output.push(TxOut {
+        script_pubkey: recipient.address.script_pubkey(),
+        value: recipient.postage.unwrap_or(TARGET_POSTAGE).to_sat(),
+      });
+    }
+
+    let unfunded_transaction = Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input: inputs
+        .iter()
+        .map(|previous_output| TxIn {
+          previous_output: *previous_output,
+          script_sig: ScriptBuf::new(),
+          sequence: Sequence::MAX,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output,
+    };
+
+    let mut unsigned_transaction: Transaction = consensus::encode::deserialize(
+      &fund_raw_transaction(wallet.bitcoin_client(), fee_rate, &unfunded_transaction)?,
+    )?;
+
+    // lock cardinal inputs added by funding, so that later transactions in
+    // the split do not spend them again
+    let added = unsigned_transaction
+      .input
+      .iter()
+      .map(|txin| txin.previous_output)
+      .filter(|output| !inputs.contains(output))
+      .collect::<Vec<OutPoint>>();
+
+    if !wallet.bitcoin_client().lock_unspent(&added)? {
+      bail!("failed to lock UTXOs");
+    }
+
+    funding.extend(added);
+
+    // the previous transaction is not yet known to bitcoin core, so its
+    // output is added after funding, paying for its own input
+    if let Some((outpoint, tx_out, _)) = chained {
+      let vsize = |transaction: &Transaction| {
+        let mut transaction = transaction.clone();
+        for txin in &mut transaction.input {
+          txin.witness = Witness::from_slice(&[&[0; SCHNORR_SIGNATURE_SIZE]]);
+        }
+        transaction.vsize()
+      };
+
+      let before = vsize(&unsigned_transaction);
+
+      unsigned_transaction.input.push(TxIn {
+        previous_output: outpoint,
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+      });
+
+      let fee = fee_rate.fee(vsize(&unsigned_transaction)) - fee_rate.fee(before);
+
+      let value = tx_out.value.checked_sub(fee.to_sat()).ok_or_else(|| {
+        anyhow!("fee rate too high to spend runes left over by previous transaction")
+      })?;
+
+      // funding appends a change output after the recipients, if needed
+      let change = if unsigned_transaction.output.len() > recipients.len() + 2 {
+        unsigned_transaction.output.len() - 1
+      } else {
+        1
+      };
+
+      unsigned_transaction.output[change].value += value;
+    }
+
+    assert_eq!(
+      Runestone::decipher(&unsigned_transaction),
+      Some(Artifact::Runestone(runestone)),
+    );
+
+    Ok((unsigned_transaction, input_runes))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn recipient(n: u8, runes: &[(RuneId, u128)]) -> Recipient {
+    Recipient {
+      address: Address::p2wsh(&ScriptBuf::from_bytes(vec![n]), Network::Bitcoin),
+      postage: None,
+      runes: runes.iter().copied().collect(),
+    }
+  }
+
+  #[test]
+  fn csv_rows_are_parsed() {
+    assert_eq!(
+      Splits::from_csv(
+        "address,rune,amount,postage
+bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4,A•A,1.5,
+bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4, B , 2 , 1000
+
+bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4,C,3
+"
+      )
+      .unwrap(),
+      Splits {
+        outputs: vec![
+          Row {
+            address: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+              .parse()
+              .unwrap(),
+            rune: "A•A".parse().unwrap(),
+            amount: "1.5".parse().unwrap(),
+            postage: None,
+          },
+          Row {
+            address: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+              .parse()
+              .unwrap(),
+            rune: "B".parse().unwrap(),
+            amount: "2".parse().unwrap(),
+            postage: Some(1000),
+          },
+          Row {
+            address: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+              .parse()
+              .unwrap(),
+            rune: "C".parse().unwrap(),
+            amount: "3".parse().unwrap(),
+            postage: None,
+          },
+        ],
+      }
+    );
+  }
+
+  #[test]
+  fn csv_errors_include_line_number() {
+    assert_eq!(
+      Splits::from_csv("address,rune,amount\nfoo,A,1")
+        .unwrap_err()
+        .to_string(),
+      "line 2: invalid address",
+    );
+
+    assert_eq!(
+      Splits::from_csv("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4,A")
+        .unwrap_err()
+        .to_string(),
+      "line 1: expected `address,rune,amount[,postage]`",
+    );
+  }
+
+  #[test]
+  fn yaml_rows_are_parsed() {
+    assert_eq!(
+      serde_yaml::from_str::<Splits>(
+        "outputs:
+- address: bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4
+  rune: A•A
+  amount: 1.5
+  postage: 1000
+"
+      )
+      .unwrap(),
+      Splits {
+        outputs: vec![Row {
+          address: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+            .parse()
+            .unwrap(),
+          rune: "A•A".parse().unwrap(),
+          amount: "1.5".parse().unwrap(),
+          postage: Some(1000),
+        }],
+      }
+    );
+  }
+
+  #[test]
+  fn runestone_edicts_are_sorted_by_id_and_output() {
+    let a = RuneId { block: 1, tx: 1 };
+    let b = RuneId { block: 2, tx: 1 };
+
+    assert_eq!(
+      Split::runestone(&[recipient(0, &[(b, 1), (a, 2)]), recipient(1, &[(a, 3)])]),
+      Runestone {
+        edicts: vec![
+          Edict {
+            id: a,
+            amount: 2,
+            output: 2,
+          },
+          Edict {
+            id: a,
+            amount: 3,
+            output: 3,
+          },
+          Edict {
+            id: b,
+            amount: 1,
+            output: 2,
+          },
+        ],
+        ..default()
+      }
+    );
+  }
+
+  #[test]
+  fn small_splits_fit_in_one_transaction() {
+    let id = RuneId { block: 1, tx: 1 };
+
+    let batches = Split::pack((0..3).map(|n| recipient(n, &[(id, 100)])).collect()).unwrap();
+
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].len(), 3);
+  }
+
+  #[test]
+  fn large_splits_are_packed_into_multiple_transactions() {
+    let id = RuneId {
+      block: 840_000,
+      tx: 1,
+    };
+
+    let batches =
+      Split::pack((0..100).map(|n| recipient(n, &[(id, 1_000_000)])).collect()).unwrap();
+
+    assert!(batches.len() > 1);
+
+    assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), 100);
+
+    let mut n = 0;
+    for batch in &batches {
+      assert!(Split::runestone(batch).encipher().len() <= MAX_STANDARD_OP_RETURN_SIZE);
+
+      for recipient in batch {
+        assert_eq!(
+          recipient.address,
+          Address::p2wsh(&ScriptBuf::from_bytes(vec![n]), Network::Bitcoin)
+        );
+        n += 1;
+      }
+    }
+
+    let overfull = (0..=batches[0].len())
+      .map(|n| recipient(n.try_into().unwrap(), &[(id, 1_000_000)]))
+      .collect::<Vec<Recipient>>();
+
+    assert!(Split::runestone(&overfull).encipher().len() > MAX_STANDARD_OP_RETURN_SIZE);
+  }
+
+  #[test]
+  fn recipient_whose_edicts_do_not_fit_is_an_error() {
+    let runes = (1..=20)
+      .map(|block| {
+        (
+          RuneId {
+            block: block * 1_000_000,
+            tx: 1_000,
+          },
+          u128::MAX,
+        )
+      })
+      .collect::<Vec<(RuneId, u128)>>();
+
+    assert_eq!(
+      Split::pack(vec![recipient(0, &runes)])
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "runestone for output to {} exceeds maximum standard OP_RETURN size of 83 bytes",
+        recipient(0, &[]).address,
+      ),
+    );
+  }
+}
@@ -2,6 +2,7 @@ use {super::*, boilerplate::Boilerplate};
 
 pub(crate) use {
   crate::subcommand::server::ServerConfig,
+  address::AddressHtml,
   block::BlockHtml,
   children::ChildrenHtml,
   clock::ClockSvg,
@@ -22,13 +23,19 @@ pub(crate) use {
   range::RangeHtml,
   rare::RareTxt,
   sat::SatHtml,
+  search::SearchHtml,
 };
 
 pub use {
-  blocks::BlocksHtml, rune::RuneHtml, runes::RunesHtml, status::StatusHtml,
+  blocks::BlocksHtml,
+  rune::RuneHtml,
+  runes::RunesHtml,
+  status::StatusHtml,
   transaction::TransactionHtml,
+  usage::{KeyUsage, UsageHtml},
 };
 
+pub mod address;
 pub mod block;
 pub mod blocks;
 mod children;
@@ -49,8 +56,10 @@ mod rare;
 pub mod rune;
 pub mod runes;
 pub mod sat;
+mod search;
 pub mod status;
 pub mod transaction;
+pub mod usage;
 
 #[derive(Boilerplate)]
 pub(crate) struct PageHtml<T: PageContent> {
@@ -1,9 +1,11 @@
 use super::*;
 
-#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BlocksHtml {
   pub last: u32,
+  #[schemars(with = "Vec<api::schema::BlockHash>")]
   pub blocks: Vec<BlockHash>,
+  #[schemars(with = "BTreeMap<String, Vec<InscriptionId>>")]
   pub featured_blocks: BTreeMap<BlockHash, Vec<InscriptionId>>,
 }
 
@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RuneHtml {
   pub entry: RuneEntry,
   pub id: RuneId,
@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RunesHtml {
   pub entries: Vec<(RuneId, RuneEntry)>,
   pub more: bool,
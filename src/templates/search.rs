@@ -0,0 +1,69 @@
+use super::*;
+
+#[derive(Boilerplate)]
+pub(crate) struct SearchHtml {
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) next: Option<String>,
+  pub(crate) prev: Option<String>,
+  pub(crate) query: String,
+}
+
+impl PageContent for SearchHtml {
+  fn title(&self) -> String {
+    format!("Search: {}", self.query)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn without_results() {
+    assert_regex_match!(
+      SearchHtml {
+        inscriptions: Vec::new(),
+        next: None,
+        prev: None,
+        query: "foo".into(),
+      },
+      "
+        <h1>Search Results</h1>
+        <form action=/search method=get>
+          <input name=q value=\"foo\">
+        </form>
+        <p>No inscriptions found.</p>
+        <div class=center>
+        prev
+        next
+        </div>
+      "
+      .unindent()
+    );
+  }
+
+  #[test]
+  fn with_results_and_pagination() {
+    assert_regex_match!(
+      SearchHtml {
+        inscriptions: vec![inscription_id(1), inscription_id(2)],
+        next: Some("/search?q=foo&charm=cursed&page=2".into()),
+        prev: Some("/search?q=foo&charm=cursed&page=0".into()),
+        query: "foo".into(),
+      },
+      "
+        <h1>Search Results</h1>
+        .*
+        <div class=thumbnails>
+          <a href=/inscription/1{64}i1><iframe .* src=/preview/1{64}i1></iframe></a>
+          <a href=/inscription/2{64}i2><iframe .* src=/preview/2{64}i2></iframe></a>
+        </div>
+        <div class=center>
+        <a class=prev href=\"/search\\?q=foo&amp;charm=cursed&amp;page=0\">prev</a>
+        <a class=next href=\"/search\\?q=foo&amp;charm=cursed&amp;page=2\">next</a>
+        </div>
+      "
+      .unindent()
+    );
+  }
+}
@@ -1,11 +1,13 @@
 use super::*;
 
-#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionHtml {
   pub chain: Chain,
   pub etching: Option<SpacedRune>,
   pub inscription_count: u32,
+  #[schemars(with = "api::schema::Transaction")]
   pub transaction: Transaction,
+  #[schemars(with = "api::schema::Txid")]
   pub txid: Txid,
 }
 
@@ -0,0 +1,82 @@
+use super::*;
+
+#[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UsageHtml {
+  pub anonymous_clients: usize,
+  pub anonymous_rate_limit: Option<u32>,
+  pub anonymous_requests: u64,
+  pub anonymous_throttled: u64,
+  pub keys: Vec<KeyUsage>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KeyUsage {
+  pub admin: bool,
+  pub name: String,
+  pub quota: Option<u64>,
+  pub rate_limit: Option<u32>,
+  pub requests: u64,
+  pub requests_today: u64,
+  pub throttled: u64,
+}
+
+impl PageContent for UsageHtml {
+  fn title(&self) -> String {
+    "Usage".into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn usage() {
+    assert_regex_match!(
+      UsageHtml {
+        anonymous_clients: 3,
+        anonymous_rate_limit: Some(60),
+        anonymous_requests: 100,
+        anonymous_throttled: 2,
+        keys: vec![KeyUsage {
+          admin: false,
+          name: "explorer".into(),
+          quota: None,
+          rate_limit: Some(600),
+          requests: 10,
+          requests_today: 5,
+          throttled: 1,
+        }],
+      },
+      "
+        <h1>Usage</h1>
+        <h2>Anonymous</h2>
+        <dl>
+          <dt>rate limit</dt>
+          <dd>60/min per IP address</dd>
+          <dt>requests</dt>
+          <dd>100</dd>
+          <dt>throttled</dt>
+          <dd>2</dd>
+          <dt>active clients</dt>
+          <dd>3</dd>
+        </dl>
+        <h2>API Keys</h2>
+        <h3>explorer</h3>
+        <dl>
+          <dt>rate limit</dt>
+          <dd>600/min</dd>
+          <dt>daily quota</dt>
+          <dd>none</dd>
+          <dt>requests</dt>
+          <dd>10</dd>
+          <dt>requests today</dt>
+          <dd>5</dd>
+          <dt>throttled</dt>
+          <dd>1</dd>
+        </dl>
+      "
+      .unindent()
+    );
+  }
+}
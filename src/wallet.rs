@@ -6,6 +6,7 @@ use {
   bitcoin::{
     bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, Fingerprint},
     psbt::Psbt,
+    taproot::{ControlBlock, LeafVersion, TapLeafHash},
   },
   bitcoincore_rpc::bitcoincore_rpc_json::{Descriptor, ImportDescriptors, Timestamp},
   entry::{EtchingEntry, EtchingEntryValue},
@@ -13,7 +14,7 @@ use {
   index::entry::Entry,
   indicatif::{ProgressBar, ProgressStyle},
   log::log_enabled,
-  miniscript::descriptor::{DescriptorSecretKey, DescriptorXKey, Wildcard},
+  miniscript::descriptor::{DescriptorPublicKey, DescriptorSecretKey, DescriptorXKey, Wildcard},
   redb::{Database, DatabaseError, ReadableTable, RepairSession, StorageError, TableDefinition},
   reqwest::header,
   std::sync::Once,
@@ -21,12 +22,15 @@ use {
 };
 
 pub mod batch;
+pub mod batch_transaction_builder;
 pub mod entry;
 pub mod transaction_builder;
 pub mod wallet_constructor;
 
 const SCHEMA_VERSION: u64 = 1;
 
+const MIGRATIONS: &[Migration] = &[];
+
 define_table! { RUNE_TO_ETCHING, u128, EtchingEntryValue }
 define_table! { STATISTICS, u64, u64 }
 
@@ -60,6 +64,7 @@ pub(crate) struct Wallet {
   inscriptions: BTreeMap<SatPoint, Vec<InscriptionId>>,
   locked_utxos: BTreeMap<OutPoint, TxOut>,
   settings: Settings,
+  watch_only: bool,
 }
 
 impl Wallet {
@@ -113,6 +118,19 @@ impl Wallet {
     &self.bitcoin_client
   }
 
+  pub(crate) fn watch_only(&self) -> bool {
+    self.watch_only
+  }
+
+  pub(crate) fn ensure_can_sign(&self) -> Result {
+    ensure!(
+      !self.watch_only,
+      "wallet is watch-only and cannot sign transactions, use `--dry-run` to create a PSBT and sign it elsewhere"
+    );
+
+    Ok(())
+  }
+
   pub(crate) fn utxos(&self) -> &BTreeMap<OutPoint, TxOut> {
     &self.utxos
   }
@@ -377,6 +395,68 @@ impl Wallet {
     })
   }
 
+  /// Create a PSBT for `transaction` for signing by an external signer.
+  ///
+  /// Inputs that are already signed, such as reveal inputs signed with the
+  /// ephemeral inscription key, keep their witness as a final script witness,
+  /// along with their taproot script-path spend information. Outputs of
+  /// `parents` are used for inputs spending transactions not yet known to
+  /// Bitcoin Core, which fills in UTXOs and BIP32 derivations for wallet inputs.
+  pub(crate) fn psbt(&self, transaction: &Transaction, parents: &[&Transaction]) -> Result<String> {
+    let mut unsigned_transaction = transaction.clone();
+
+    for txin in &mut unsigned_transaction.input {
+      txin.witness = Witness::new();
+    }
+
+    let mut psbt = Psbt::from_unsigned_tx(unsigned_transaction)?;
+
+    for (input, txin) in psbt.inputs.iter_mut().zip(&transaction.input) {
+      input.witness_utxo = parents
+        .iter()
+        .find(|parent| parent.txid() == txin.previous_output.txid)
+        .and_then(|parent| parent.output.get(txin.previous_output.vout.into_usize()))
+        .cloned();
+
+      if txin.witness.is_empty() {
+        continue;
+      }
+
+      if let (Some(script), Some(control_block)) = (
+        txin.witness.tapscript(),
+        txin
+          .witness
+          .last()
+          .and_then(|control_block| ControlBlock::decode(control_block).ok()),
+      ) {
+        input.tap_internal_key = Some(control_block.internal_key);
+
+        if control_block.merkle_branch.is_empty() {
+          input.tap_merkle_root =
+            Some(TapLeafHash::from_script(script, LeafVersion::TapScript).into());
+        }
+
+        input
+          .tap_scripts
+          .insert(control_block, (script.into(), LeafVersion::TapScript));
+      }
+
+      input.final_script_witness = Some(txin.witness.clone());
+    }
+
+    Ok(
+      self
+        .bitcoin_client()
+        .wallet_process_psbt(
+          &base64::engine::general_purpose::STANDARD.encode(psbt.serialize()),
+          Some(false),
+          None,
+          None,
+        )?
+        .psbt,
+    )
+  }
+
   fn check_descriptors(wallet_name: &str, descriptors: Vec<Descriptor>) -> Result<Vec<Descriptor>> {
     let tr = descriptors
       .iter()
@@ -404,7 +484,32 @@ impl Wallet {
 
     let descriptors = Self::check_descriptors(&name, descriptors)?;
 
-    client.create_wallet(&name, None, Some(true), None, None)?;
+    let secp = Secp256k1::new();
+
+    let private = descriptors
+      .iter()
+      .filter(|descriptor| descriptor.desc.starts_with("tr("))
+      .map(|descriptor| {
+        Ok(
+          !miniscript::Descriptor::parse_descriptor(&secp, &descriptor.desc)?
+            .1
+            .is_empty(),
+        )
+      })
+      .collect::<Result<BTreeSet<bool>>>()?;
+
+    ensure!(
+      private.len() == 1,
+      "descriptors must either all contain private keys or all be public"
+    );
+
+    client.create_wallet(
+      &name,
+      Some(!private.contains(&true)),
+      Some(true),
+      None,
+      None,
+    )?;
 
     let descriptors = descriptors
       .into_iter()
@@ -431,6 +536,46 @@ impl Wallet {
     Ok(())
   }
 
+  pub(crate) fn initialize_from_xpub(name: String, settings: &Settings, xpub: &str) -> Result {
+    let client = Self::check_version(settings.bitcoin_rpc_client(Some(name.clone()))?)?;
+
+    let network = settings.chain().network();
+
+    let mut descriptors = Vec::new();
+
+    for change in [false, true] {
+      let public_key = format!("{xpub}/{}/*", u8::from(change))
+        .parse::<DescriptorPublicKey>()
+        .context("invalid extended public key")?;
+
+      let DescriptorPublicKey::XPub(DescriptorXKey { xkey, .. }) = &public_key else {
+        bail!("expected extended public key");
+      };
+
+      ensure!(
+        (xkey.network == Network::Bitcoin) == (network == Network::Bitcoin),
+        "extended public key is not for {}",
+        settings.chain(),
+      );
+
+      descriptors.push(ImportDescriptors {
+        descriptor: miniscript::descriptor::Descriptor::new_tr(public_key, None)?.to_string(),
+        timestamp: Timestamp::Time(0),
+        active: Some(true),
+        range: None,
+        next_index: None,
+        internal: Some(change),
+        label: None,
+      });
+    }
+
+    client.create_wallet(&name, Some(true), Some(true), None, None)?;
+
+    client.import_descriptors(descriptors)?;
+
+    Ok(())
+  }
+
   pub(crate) fn initialize(name: String, settings: &Settings, seed: [u8; 64]) -> Result {
     Self::check_version(settings.bitcoin_rpc_client(None)?)?.create_wallet(
       &name,
@@ -590,6 +735,17 @@ impl Wallet {
             .unwrap_or(0);
 
           match schema_version.cmp(&SCHEMA_VERSION) {
+            cmp::Ordering::Less if Migration::available(MIGRATIONS, schema_version, SCHEMA_VERSION) => {
+              Migration::run(
+                MIGRATIONS,
+                &database,
+                &path,
+                STATISTICS,
+                Statistic::Schema.key(),
+                schema_version,
+                SCHEMA_VERSION,
+              )?;
+            }
             cmp::Ordering::Less =>
               bail!(
                 "wallet database at `{}` appears to have been built with an older, incompatible version of ord, consider deleting and rebuilding the index: index schema {schema_version}, ord schema {SCHEMA_VERSION}",
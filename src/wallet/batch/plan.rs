@@ -46,6 +46,10 @@ impl Plan {
     utxos: &BTreeMap<OutPoint, TxOut>,
     wallet: &Wallet,
   ) -> SubcommandResult {
+    if !self.dry_run {
+      wallet.ensure_can_sign()?;
+    }
+
     let Transactions {
       commit_tx,
       commit_vout,
@@ -75,14 +79,23 @@ impl Plan {
         )?
         .psbt;
 
-      let reveal_psbt = Psbt::from_unsigned_tx(Self::remove_witnesses(reveal_tx.clone()))?;
+      let reveal_psbt = wallet.psbt(&reveal_tx, &[&commit_tx])?;
+
+      if !self.no_backup {
+        ensure!(
+          !wallet.watch_only(),
+          "watch-only wallets cannot back up the recovery key, which is needed to recover the commit output if the reveal is not mined, use `--no-backup` to continue without it"
+        );
+
+        Self::backup_recovery_key(wallet, recovery_key_pair)?;
+      }
 
       return Ok(Some(Box::new(self.output(
         commit_tx.txid(),
         Some(commit_psbt),
         reveal_tx.txid(),
         false,
-        Some(base64::engine::general_purpose::STANDARD.encode(reveal_psbt.serialize())),
+        Some(reveal_psbt),
         total_fees,
         self.inscriptions.clone(),
         rune,
@@ -0,0 +1,787 @@
+//! `BatchTransactionBuilder` constructs a single transaction that sends many
+//! sats, each to its own recipient, where `TransactionBuilder` sends one.
+//!
+//! Outgoing satpoints are spent in the order they first appear, and each
+//! outgoing sat is aligned to the first position of its own output. Sats in
+//! front of the first outgoing sat, and excess value between outgoing sats,
+//! are returned to the change address. Postage for the last outgoing sat, and
+//! the fee, are paid with additional cardinal inputs, with any remaining value
+//! returned to the change address.
+//!
+//! `Target::Postage` sends at most 20,000 sats with each outgoing sat,
+//! reducing it to 10,000 sats if there is excess value, and
+//! `Target::ExactPostage(Amount)` and `Target::Value(Amount)` send the
+//! requested amount, plus less than the dust value if stripping the excess
+//! would create a dust output.
+
+use {
+  super::*,
+  transaction_builder::{Error, Target},
+};
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq)]
+enum Layout {
+  Complete {
+    inputs: Vec<OutPoint>,
+    outputs: Vec<(Address, Amount)>,
+  },
+  Fund(Amount),
+  Pad(Amount),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BatchTransactionBuilder {
+  amounts: BTreeMap<OutPoint, TxOut>,
+  change: Address,
+  fee_rate: FeeRate,
+  inscriptions: BTreeMap<SatPoint, Vec<InscriptionId>>,
+  locked_utxos: BTreeSet<OutPoint>,
+  outgoing: Vec<(SatPoint, Address, Target)>,
+  runic_utxos: BTreeSet<OutPoint>,
+}
+
+impl BatchTransactionBuilder {
+  pub fn new(
+    outgoing: Vec<(SatPoint, Address, Target)>,
+    inscriptions: BTreeMap<SatPoint, Vec<InscriptionId>>,
+    amounts: BTreeMap<OutPoint, TxOut>,
+    locked_utxos: BTreeSet<OutPoint>,
+    runic_utxos: BTreeSet<OutPoint>,
+    change: Address,
+    fee_rate: FeeRate,
+  ) -> Self {
+    Self {
+      amounts,
+      change,
+      fee_rate,
+      inscriptions,
+      locked_utxos,
+      outgoing,
+      runic_utxos,
+    }
+  }
+
+  pub fn build_transaction(mut self) -> Result<Transaction> {
+    assert!(
+      !self.outgoing.is_empty(),
+      "invariant: outgoing is not empty"
+    );
+
+    for (_satpoint, recipient, target) in &self.outgoing {
+      if *recipient == self.change {
+        return Err(Error::DuplicateAddress(recipient.clone()));
+      }
+
+      if let Target::Value(output_value) | Target::ExactPostage(output_value) = *target {
+        let dust_value = recipient.script_pubkey().dust_value();
+
+        if output_value < dust_value {
+          return Err(Error::Dust {
+            output_value,
+            dust_value,
+          });
+        }
+      }
+    }
+
+    let mut outgoing_inputs = Vec::new();
+
+    for (satpoint, _recipient, _target) in &self.outgoing {
+      let amount = self
+        .amounts
+        .get(&satpoint.outpoint)
+        .ok_or(Error::NotInWallet(*satpoint))?
+        .value;
+
+      if satpoint.offset >= amount {
+        return Err(Error::OutOfRange(*satpoint, amount - 1));
+      }
+
+      if !outgoing_inputs.contains(&satpoint.outpoint) {
+        outgoing_inputs.push(satpoint.outpoint);
+      }
+    }
+
+    self
+      .outgoing
+      .sort_by_key(|(satpoint, _recipient, _target)| {
+        (
+          outgoing_inputs
+            .iter()
+            .position(|outpoint| *outpoint == satpoint.outpoint),
+          satpoint.offset,
+        )
+      });
+
+    for pair in self.outgoing.windows(2) {
+      if pair[0].0 == pair[1].0 {
+        return Err(Error::DuplicateOutgoing(pair[0].0));
+      }
+    }
+
+    for (inscribed_satpoint, inscription_ids) in &self.inscriptions {
+      if !outgoing_inputs.contains(&inscribed_satpoint.outpoint) {
+        continue;
+      }
+
+      if self
+        .outgoing
+        .iter()
+        .any(|(satpoint, _recipient, _target)| satpoint == inscribed_satpoint)
+      {
+        continue;
+      }
+
+      return Err(Error::UtxoContainsAdditionalInscriptions {
+        outgoing_satpoint: self
+          .outgoing
+          .iter()
+          .map(|(satpoint, _recipient, _target)| *satpoint)
+          .find(|satpoint| satpoint.outpoint == inscribed_satpoint.outpoint)
+          .unwrap(),
+        inscribed_satpoint: *inscribed_satpoint,
+        inscription_ids: inscription_ids.clone(),
+      });
+    }
+
+    let mut padding = Vec::new();
+    let mut funding = Vec::new();
+
+    loop {
+      match self.layout(&padding, &outgoing_inputs, &funding)? {
+        Layout::Complete { inputs, outputs } => return Ok(self.build(inputs, outputs)),
+        Layout::Fund(deficit) => {
+          let utxo = self.select_cardinal_utxo(deficit, [&padding, &outgoing_inputs, &funding])?;
+          tprintln!("added funding input {utxo} to cover {deficit} deficit");
+          funding.push(utxo);
+        }
+        Layout::Pad(deficit) => {
+          let utxo = self.select_cardinal_utxo(deficit, [&padding, &outgoing_inputs, &funding])?;
+          tprintln!("added padding input {utxo} to cover {deficit} deficit");
+          padding.push(utxo);
+        }
+      }
+    }
+  }
+
+  /// Lay out outputs for the given inputs, or return the amount of additional
+  /// value needed to pad the first output or to fund postage and fees.
+  fn layout(
+    &self,
+    padding: &[OutPoint],
+    outgoing_inputs: &[OutPoint],
+    funding: &[OutPoint],
+  ) -> Result<Layout> {
+    let value = |outpoint: &OutPoint| self.amounts[outpoint].value;
+
+    let mut positions = Vec::new();
+    let mut outgoing_end = padding.iter().map(value).sum::<u64>();
+
+    for input in outgoing_inputs {
+      for (satpoint, _recipient, _target) in &self.outgoing {
+        if satpoint.outpoint == *input {
+          positions.push(outgoing_end + satpoint.offset);
+        }
+      }
+
+      outgoing_end += value(input);
+    }
+
+    let total = outgoing_end + funding.iter().map(value).sum::<u64>();
+
+    let change_dust = self.change.script_pubkey().dust_value();
+
+    let mut outputs = Vec::new();
+
+    if positions[0] > 0 {
+      let alignment = Amount::from_sat(positions[0]);
+
+      if alignment < change_dust {
+        return Ok(Layout::Pad(change_dust - alignment));
+      }
+
+      outputs.push((self.change.clone(), alignment));
+    }
+
+    for (i, (satpoint, recipient, target)) in self.outgoing.iter().enumerate() {
+      let dust_value = recipient.script_pubkey().dust_value();
+
+      let Some(next) = positions.get(i + 1) else {
+        let space = Amount::from_sat(outgoing_end - positions[i]);
+
+        let postage = match *target {
+          Target::Postage if space >= dust_value && space <= TransactionBuilder::MAX_POSTAGE => {
+            space
+          }
+          Target::Postage => TARGET_POSTAGE,
+          Target::Value(value) | Target::ExactPostage(value) => value,
+        };
+
+        outputs.push((recipient.clone(), postage));
+
+        break;
+      };
+
+      let space = Amount::from_sat(next - positions[i]);
+
+      let (postage, required) = match *target {
+        Target::Postage if space > TransactionBuilder::MAX_POSTAGE => (TARGET_POSTAGE, dust_value),
+        Target::Postage => (space, dust_value),
+        Target::Value(value) | Target::ExactPostage(value) if space >= value + change_dust => {
+          (value, value)
+        }
+        Target::Value(value) | Target::ExactPostage(value) => (space, value),
+      };
+
+      if space < required {
+        return Err(Error::OutgoingTooClose {
+          outgoing_satpoint: *satpoint,
+          next_satpoint: self.outgoing[i + 1].0,
+          space,
+          required,
+        });
+      }
+
+      outputs.push((recipient.clone(), postage));
+
+      if space > postage {
+        outputs.push((self.change.clone(), space - postage));
+      }
+    }
+
+    let inputs = padding
+      .iter()
+      .chain(outgoing_inputs)
+      .chain(funding)
+      .copied()
+      .collect::<Vec<OutPoint>>();
+
+    let addresses = outputs
+      .iter()
+      .map(|(address, _amount)| address.clone())
+      .collect::<Vec<Address>>();
+
+    let fee = self.fee_rate.fee(TransactionBuilder::estimate_vbytes_with(
+      inputs.len(),
+      addresses.clone(),
+    ));
+
+    let fee_with_change = self.fee_rate.fee(TransactionBuilder::estimate_vbytes_with(
+      inputs.len(),
+      addresses.into_iter().chain([self.change.clone()]).collect(),
+    ));
+
+    let total = Amount::from_sat(total);
+
+    let spent = outputs
+      .iter()
+      .map(|(_address, amount)| *amount)
+      .sum::<Amount>();
+
+    if total >= spent + fee_with_change + change_dust {
+      outputs.push((self.change.clone(), total - spent - fee_with_change));
+    } else if total < spent + fee {
+      return Ok(Layout::Fund(spent + fee_with_change + change_dust - total));
+    } else {
+      tprintln!(
+        "remaining {} is too small for change output, adding to fee",
+        total - spent - fee
+      );
+    }
+
+    Ok(Layout::Complete { inputs, outputs })
+  }
+
+  fn build(self, inputs: Vec<OutPoint>, outputs: Vec<(Address, Amount)>) -> Transaction {
+    let transaction = Transaction {
+      version: 2,
+      lock_time: LockTime::ZERO,
+      input: inputs
+        .iter()
+        .map(|outpoint| TxIn {
+          previous_output: *outpoint,
+          script_sig: ScriptBuf::new(),
+          sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output: outputs
+        .iter()
+        .map(|(address, amount)| TxOut {
+          value: amount.to_sat(),
+          script_pubkey: address.script_pubkey(),
+        })
+        .collect(),
+    };
+
+    let change_dust = self.change.script_pubkey().dust_value();
+
+    for (satpoint, recipient, target) in &self.outgoing {
+      let mut sat_offset = 0;
+      for input in &inputs {
+        if *input == satpoint.outpoint {
+          sat_offset += satpoint.offset;
+          break;
+        }
+        sat_offset += self.amounts[input].value;
+      }
+
+      let mut output_start = 0;
+      let mut found = false;
+      for tx_out in &transaction.output {
+        if output_start == sat_offset {
+          assert_eq!(
+            tx_out.script_pubkey,
+            recipient.script_pubkey(),
+            "invariant: outgoing sat is sent to recipient"
+          );
+
+          let value = Amount::from_sat(tx_out.value);
+
+          match *target {
+            Target::Postage => assert!(
+              value <= TransactionBuilder::MAX_POSTAGE,
+              "invariant: excess postage is stripped"
+            ),
+            Target::Value(target) | Target::ExactPostage(target) => assert!(
+              value >= target && value < target + change_dust,
+              "invariant: output equals target value"
+            ),
+          }
+
+          found = true;
+          break;
+        }
+        output_start += tx_out.value;
+      }
+      assert!(
+        found,
+        "invariant: outgoing sat is at first position in recipient output"
+      );
+    }
+
+    for tx_out in &transaction.output {
+      assert!(
+        tx_out.script_pubkey == self.change.script_pubkey()
+          || self.outgoing.iter().any(
+            |(_satpoint, recipient, _target)| tx_out.script_pubkey == recipient.script_pubkey()
+          ),
+        "invariant: all outputs are either change or recipient: unrecognized output {}",
+        tx_out.script_pubkey
+      );
+
+      assert!(
+        Amount::from_sat(tx_out.value) >= tx_out.script_pubkey.dust_value(),
+        "invariant: all outputs are above dust limit",
+      );
+    }
+
+    let mut actual_fee = Amount::ZERO;
+    for input in &transaction.input {
+      actual_fee += Amount::from_sat(self.amounts[&input.previous_output].value);
+    }
+    for output in &transaction.output {
+      actual_fee -= Amount::from_sat(output.value);
+    }
+
+    let mut modified_tx = transaction.clone();
+    for input in &mut modified_tx.input {
+      input.witness = Witness::from_slice(&[&[0; 64]]);
+    }
+    let expected_fee = self.fee_rate.fee(modified_tx.vsize());
+
+    assert!(
+      actual_fee >= expected_fee
+        && actual_fee
+          <= expected_fee
+            + change_dust
+            + self
+              .fee_rate
+              .fee(TransactionBuilder::ADDITIONAL_OUTPUT_VBYTES),
+      "invariant: fee estimation is correct",
+    );
+
+    transaction
+  }
+
+  /// Select the smallest cardinal UTXO worth at least `target_value`, or the
+  /// largest if none are.
+  fn select_cardinal_utxo(
+    &self,
+    target_value: Amount,
+    inputs: [&[OutPoint]; 3],
+  ) -> Result<OutPoint> {
+    let inscribed_utxos = self
+      .inscriptions
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<BTreeSet<OutPoint>>();
+
+    let candidates = self
+      .amounts
+      .iter()
+      .filter(|(utxo, _txout)| {
+        !self.runic_utxos.contains(utxo)
+          && !inscribed_utxos.contains(utxo)
+          && !self.locked_utxos.contains(utxo)
+          && !inputs.iter().any(|inputs| inputs.contains(utxo))
+      })
+      .map(|(utxo, txout)| (Amount::from_sat(txout.value), *utxo))
+      .collect::<Vec<(Amount, OutPoint)>>();
+
+    candidates
+      .iter()
+      .filter(|(value, _utxo)| *value >= target_value)
+      .min()
+      .or_else(|| candidates.iter().max())
+      .map(|(_value, utxo)| *utxo)
+      .ok_or(Error::NotEnoughCardinalUtxos)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::Error, super::*};
+
+  fn builder(
+    outgoing: Vec<(SatPoint, Address, Target)>,
+    utxos: &[(OutPoint, u64)],
+    inscriptions: &[SatPoint],
+  ) -> BatchTransactionBuilder {
+    BatchTransactionBuilder::new(
+      outgoing,
+      inscriptions
+        .iter()
+        .enumerate()
+        .map(|(i, satpoint)| (*satpoint, vec![inscription_id(i.try_into().unwrap())]))
+        .collect(),
+      utxos
+        .iter()
+        .map(|(outpoint, value)| (*outpoint, tx_out(*value, address())))
+        .collect(),
+      BTreeSet::new(),
+      BTreeSet::new(),
+      change(0),
+      FeeRate::try_from(1.0).unwrap(),
+    )
+  }
+
+  #[test]
+  fn each_outgoing_sat_is_sent_to_its_own_output() {
+    let transaction = builder(
+      vec![
+        (satpoint(1, 0), recipient(), Target::Postage),
+        (satpoint(2, 0), change(2), Target::Postage),
+        (satpoint(3, 0), change(3), Target::Postage),
+      ],
+      &[
+        (outpoint(1), 10_000),
+        (outpoint(2), 10_000),
+        (outpoint(3), 10_000),
+        (outpoint(4), 100_000),
+      ],
+      &[satpoint(1, 0), satpoint(2, 0), satpoint(3, 0)],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(
+      transaction.input,
+      [
+        tx_in(outpoint(1)),
+        tx_in(outpoint(2)),
+        tx_in(outpoint(3)),
+        tx_in(outpoint(4)),
+      ]
+    );
+
+    assert_eq!(
+      transaction.output[..3],
+      [
+        tx_out(10_000, recipient()),
+        tx_out(10_000, change(2)),
+        tx_out(10_000, change(3)),
+      ]
+    );
+
+    assert_eq!(transaction.output.len(), 4);
+    assert_eq!(
+      transaction.output[3].script_pubkey,
+      change(0).script_pubkey()
+    );
+    assert!(transaction.output[3].value < 100_000);
+  }
+
+  #[test]
+  fn inputs_are_spent_in_order_of_first_appearance() {
+    let transaction = builder(
+      vec![
+        (satpoint(2, 0), recipient(), Target::Postage),
+        (satpoint(1, 0), change(2), Target::Postage),
+      ],
+      &[
+        (outpoint(1), 10_000),
+        (outpoint(2), 10_000),
+        (outpoint(3), 100_000),
+      ],
+      &[],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(
+      transaction.input,
+      [tx_in(outpoint(2)), tx_in(outpoint(1)), tx_in(outpoint(3))]
+    );
+
+    assert_eq!(
+      transaction.output[..2],
+      [tx_out(10_000, recipient()), tx_out(10_000, change(2))]
+    );
+  }
+
+  #[test]
+  fn excess_value_between_outgoing_sats_is_stripped() {
+    let transaction = builder(
+      vec![
+        (satpoint(1, 50_000), change(2), Target::Postage),
+        (satpoint(1, 0), recipient(), Target::Postage),
+      ],
+      &[(outpoint(1), 100_000)],
+      &[],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(transaction.input, [tx_in(outpoint(1))]);
+
+    assert_eq!(
+      transaction.output[..3],
+      [
+        tx_out(10_000, recipient()),
+        tx_out(40_000, change(0)),
+        tx_out(10_000, change(2)),
+      ]
+    );
+
+    assert_eq!(transaction.output.len(), 4);
+    assert_eq!(
+      transaction.output[3].script_pubkey,
+      change(0).script_pubkey()
+    );
+  }
+
+  #[test]
+  fn value_in_front_of_first_outgoing_sat_is_returned_as_change() {
+    let transaction = builder(
+      vec![(satpoint(1, 5_000), recipient(), Target::Postage)],
+      &[(outpoint(1), 10_000), (outpoint(2), 100_000)],
+      &[],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(
+      transaction.output[..2],
+      [tx_out(5_000, change(0)), tx_out(5_000, recipient())]
+    );
+  }
+
+  #[test]
+  fn alignment_output_under_dust_limit_is_padded() {
+    let transaction = builder(
+      vec![(satpoint(1, 1), recipient(), Target::Postage)],
+      &[
+        (outpoint(1), 10_000),
+        (outpoint(2), 1_000),
+        (outpoint(3), 100_000),
+      ],
+      &[],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(
+      transaction.input,
+      [tx_in(outpoint(2)), tx_in(outpoint(1)), tx_in(outpoint(3))]
+    );
+
+    assert_eq!(
+      transaction.output[..2],
+      [tx_out(1_001, change(0)), tx_out(9_999, recipient())]
+    );
+  }
+
+  #[test]
+  fn postage_for_last_outgoing_sat_is_added() {
+    let transaction = builder(
+      vec![(satpoint(1, 0), recipient(), Target::Postage)],
+      &[(outpoint(1), 100), (outpoint(2), 100_000)],
+      &[],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(transaction.input, [tx_in(outpoint(1)), tx_in(outpoint(2))]);
+    assert_eq!(transaction.output[0], tx_out(10_000, recipient()));
+    assert_eq!(
+      transaction.output[1].script_pubkey,
+      change(0).script_pubkey()
+    );
+  }
+
+  #[test]
+  fn exact_postage_is_sent() {
+    let transaction = builder(
+      vec![
+        (
+          satpoint(1, 0),
+          recipient(),
+          Target::ExactPostage(Amount::from_sat(1_000)),
+        ),
+        (
+          satpoint(1, 5_000),
+          change(2),
+          Target::ExactPostage(Amount::from_sat(2_000)),
+        ),
+      ],
+      &[(outpoint(1), 10_000), (outpoint(2), 100_000)],
+      &[],
+    )
+    .build_transaction()
+    .unwrap();
+
+    assert_eq!(
+      transaction.output[..3],
+      [
+        tx_out(1_000, recipient()),
+        tx_out(4_000, change(0)),
+        tx_out(2_000, change(2)),
+      ]
+    );
+  }
+
+  #[test]
+  fn outgoing_sats_that_are_too_close_are_an_error() {
+    assert_eq!(
+      builder(
+        vec![
+          (satpoint(1, 0), recipient(), Target::Postage),
+          (satpoint(1, 100), change(2), Target::Postage),
+        ],
+        &[(outpoint(1), 10_000), (outpoint(2), 100_000)],
+        &[],
+      )
+      .build_transaction(),
+      Err(Error::OutgoingTooClose {
+        outgoing_satpoint: satpoint(1, 0),
+        next_satpoint: satpoint(1, 100),
+        space: Amount::from_sat(100),
+        required: Amount::from_sat(294),
+      })
+    );
+
+    assert_eq!(
+      builder(
+        vec![
+          (
+            satpoint(1, 0),
+            recipient(),
+            Target::ExactPostage(Amount::from_sat(6_000))
+          ),
+          (satpoint(1, 5_000), change(2), Target::Postage),
+        ],
+        &[(outpoint(1), 10_000), (outpoint(2), 100_000)],
+        &[],
+      )
+      .build_transaction(),
+      Err(Error::OutgoingTooClose {
+        outgoing_satpoint: satpoint(1, 0),
+        next_satpoint: satpoint(1, 5_000),
+        space: Amount::from_sat(5_000),
+        required: Amount::from_sat(6_000),
+      })
+    );
+  }
+
+  #[test]
+  fn duplicate_outgoing_is_an_error() {
+    assert_eq!(
+      builder(
+        vec![
+          (satpoint(1, 0), recipient(), Target::Postage),
+          (satpoint(1, 0), change(2), Target::Postage),
+        ],
+        &[(outpoint(1), 10_000), (outpoint(2), 100_000)],
+        &[],
+      )
+      .build_transaction(),
+      Err(Error::DuplicateOutgoing(satpoint(1, 0)))
+    );
+  }
+
+  #[test]
+  fn spending_unsent_inscription_is_an_error() {
+    assert_eq!(
+      builder(
+        vec![(satpoint(1, 0), recipient(), Target::Postage)],
+        &[(outpoint(1), 100_000), (outpoint(2), 100_000)],
+        &[satpoint(1, 0), satpoint(1, 50_000)],
+      )
+      .build_transaction(),
+      Err(Error::UtxoContainsAdditionalInscriptions {
+        outgoing_satpoint: satpoint(1, 0),
+        inscribed_satpoint: satpoint(1, 50_000),
+        inscription_ids: vec![inscription_id(1)],
+      })
+    );
+  }
+
+  #[test]
+  fn recipient_may_not_be_change_address() {
+    assert_eq!(
+      builder(
+        vec![(satpoint(1, 0), change(0), Target::Postage)],
+        &[(outpoint(1), 10_000), (outpoint(2), 100_000)],
+        &[],
+      )
+      .build_transaction(),
+      Err(Error::DuplicateAddress(change(0)))
+    );
+  }
+
+  #[test]
+  fn insufficient_cardinal_utxos_is_an_error() {
+    assert_eq!(
+      builder(
+        vec![(satpoint(1, 0), recipient(), Target::Postage)],
+        &[(outpoint(1), 10_000), (outpoint(2), 100)],
+        &[satpoint(1, 0)],
+      )
+      .build_transaction(),
+      Err(Error::NotEnoughCardinalUtxos)
+    );
+  }
+
+  #[test]
+  fn inscribed_and_runic_utxos_are_not_used_for_funding() {
+    let mut builder = builder(
+      vec![(satpoint(1, 0), recipient(), Target::Postage)],
+      &[
+        (outpoint(1), 10_000),
+        (outpoint(2), 100_000),
+        (outpoint(3), 100_000),
+      ],
+      &[satpoint(1, 0), satpoint(2, 0)],
+    );
+
+    builder.runic_utxos.insert(outpoint(3));
+
+    assert_eq!(
+      builder.build_transaction(),
+      Err(Error::NotEnoughCardinalUtxos)
+    );
+  }
+}
@@ -39,6 +39,7 @@ use {
 #[derive(Debug, PartialEq)]
 pub enum Error {
   DuplicateAddress(Address),
+  DuplicateOutgoing(SatPoint),
   Dust {
     output_value: Amount,
     dust_value: Amount,
@@ -46,6 +47,12 @@ pub enum Error {
   NotEnoughCardinalUtxos,
   NotInWallet(SatPoint),
   OutOfRange(SatPoint, u64),
+  OutgoingTooClose {
+    outgoing_satpoint: SatPoint,
+    next_satpoint: SatPoint,
+    space: Amount,
+    required: Amount,
+  },
   UtxoContainsAdditionalInscriptions {
     outgoing_satpoint: SatPoint,
     inscribed_satpoint: SatPoint,
@@ -70,6 +77,12 @@ impl Display for Error {
       } => write!(f, "output value is below dust value: {output_value} < {dust_value}"),
       Error::NotInWallet(outgoing_satpoint) => write!(f, "outgoing satpoint {outgoing_satpoint} not in wallet"),
       Error::OutOfRange(outgoing_satpoint, maximum) => write!(f, "outgoing satpoint {outgoing_satpoint} offset higher than maximum {maximum}"),
+      Error::OutgoingTooClose {
+        outgoing_satpoint,
+        next_satpoint,
+        space,
+        required,
+      } => write!(f, "cannot send {outgoing_satpoint} in separate output from {next_satpoint}: only {space} between them, need at least {required}"),
       Error::NotEnoughCardinalUtxos => write!(
         f,
         "wallet does not contain enough cardinal UTXOs, please add additional funds to wallet."
@@ -85,6 +98,7 @@ impl Display for Error {
       ),
       Error::ValueOverflow => write!(f, "arithmetic overflow calculating value"),
       Error::DuplicateAddress(address) => write!(f, "duplicate input address: {address}"),
+      Error::DuplicateOutgoing(satpoint) => write!(f, "duplicate outgoing satpoint: {satpoint}"),
     }
   }
 }
@@ -112,7 +126,7 @@ type Result<T> = std::result::Result<T, Error>;
 
 impl TransactionBuilder {
   const ADDITIONAL_INPUT_VBYTES: usize = 58;
-  const ADDITIONAL_OUTPUT_VBYTES: usize = 43;
+  pub(crate) const ADDITIONAL_OUTPUT_VBYTES: usize = 43;
   const SCHNORR_SIGNATURE_SIZE: usize = 64;
   pub(crate) const MAX_POSTAGE: Amount = Amount::from_sat(2 * 10_000);
 
@@ -431,7 +445,7 @@ impl TransactionBuilder {
     )
   }
 
-  fn estimate_vbytes_with(inputs: usize, outputs: Vec<Address>) -> usize {
+  pub(crate) fn estimate_vbytes_with(inputs: usize, outputs: Vec<Address>) -> usize {
     Transaction {
       version: 2,
       lock_time: LockTime::ZERO,
@@ -11,7 +11,7 @@ fn run_is_an_alias_for_update() {
 
   CommandBuilder::new(format!("--index {} index run", index_path.display()))
     .core(&core)
-    .run_and_extract_stdout();
+    .run_and_deserialize_output::<ord::subcommand::index::update::Output>();
 
   assert!(index_path.is_file())
 }
@@ -27,7 +27,7 @@ fn custom_index_path() {
 
   CommandBuilder::new(format!("--index {} index update", index_path.display()))
     .core(&core)
-    .run_and_extract_stdout();
+    .run_and_deserialize_output::<ord::subcommand::index::update::Output>();
 
   assert!(index_path.is_file())
 }
@@ -43,13 +43,18 @@ fn re_opening_database_does_not_trigger_schema_check() {
 
   CommandBuilder::new(format!("--index {} index update", index_path.display()))
     .core(&core)
-    .run_and_extract_stdout();
+    .run_and_deserialize_output::<ord::subcommand::index::update::Output>();
 
   assert!(index_path.is_file());
 
-  CommandBuilder::new(format!("--index {} index update", index_path.display()))
-    .core(&core)
-    .run_and_extract_stdout();
+  assert_eq!(
+    CommandBuilder::new(format!("--index {} index update", index_path.display()))
+      .core(&core)
+      .run_and_deserialize_output::<ord::subcommand::index::update::Output>(),
+    ord::subcommand::index::update::Output {
+      migrations: Vec::new(),
+    },
+  );
 }
 
 #[test]
@@ -90,3 +95,277 @@ fn export_inscription_number_to_id_tsv() {
     &ord::Object::InscriptionId(inscription),
   );
 }
+
+#[test]
+fn rollback_reverts_blocks() {
+  let core = mockcore::spawn();
+  core.mine_blocks(5);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let index_path = tempdir.path().join("foo.redb");
+
+  CommandBuilder::new(format!("--index {} index update", index_path.display()))
+    .core(&core)
+    .run_and_deserialize_output::<ord::subcommand::index::update::Output>();
+
+  assert_eq!(
+    CommandBuilder::new(format!(
+      "--index {} index rollback --height 3",
+      index_path.display()
+    ))
+    .core(&core)
+    .run_and_deserialize_output::<ord::subcommand::index::rollback::Output>(),
+    ord::subcommand::index::rollback::Output {
+      height: 3,
+      reverted: 2,
+    }
+  );
+
+  CommandBuilder::new(format!(
+    "--index {} index rollback --height 3",
+    index_path.display()
+  ))
+  .core(&core)
+  .expected_exit_code(1)
+  .expected_stderr("error: cannot roll back to height 3: index is at height 3\n")
+  .run_and_extract_stdout();
+}
+
+#[test]
+fn verify_succeeds_on_consistent_index() {
+  let core = mockcore::spawn();
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-sats"], &[]);
+
+  create_wallet(&core, &ord);
+
+  inscribe(&core, &ord);
+
+  core.mine_blocks(1);
+
+  assert_eq!(
+    CommandBuilder::new("--index-sats index verify")
+      .core(&core)
+      .run_and_deserialize_output::<ord::subcommand::index::verify::Output>(),
+    ord::subcommand::index::verify::Output {
+      warnings: Vec::new(),
+    },
+  );
+}
+
+#[test]
+fn snapshot_round_trip() {
+  let core = mockcore::spawn();
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-sats"], &[]);
+
+  create_wallet(&core, &ord);
+
+  inscribe(&core, &ord);
+
+  core.mine_blocks(1);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let exported = tempdir.path().join("exported.redb");
+  let imported = tempdir.path().join("imported.redb");
+  let snapshot = tempdir.path().join("snapshot");
+
+  let manifest = CommandBuilder::new(format!(
+    "--index-sats --index {} index export --snapshot {}",
+    exported.display(),
+    snapshot.display(),
+  ))
+  .core(&core)
+  .run_and_deserialize_output::<ord::index::snapshot::Manifest>();
+
+  assert_eq!(manifest.height, 3);
+
+  assert_eq!(
+    CommandBuilder::new(format!(
+      "--index {} index import {}",
+      imported.display(),
+      snapshot.display(),
+    ))
+    .core(&core)
+    .run_and_deserialize_output::<ord::index::snapshot::Manifest>(),
+    manifest,
+  );
+
+  let tsv = |index: &Path| {
+    CommandBuilder::new(format!(
+      "--index {} index export --tsv inscriptions.tsv",
+      index.display(),
+    ))
+    .core(&core)
+    .run_and_extract_file("inscriptions.tsv")
+  };
+
+  assert_eq!(tsv(&imported), tsv(&exported));
+
+  assert_eq!(
+    CommandBuilder::new(format!("--index {} index verify", imported.display()))
+      .core(&core)
+      .run_and_deserialize_output::<ord::subcommand::index::verify::Output>(),
+    ord::subcommand::index::verify::Output {
+      warnings: Vec::new(),
+    },
+  );
+}
+
+#[test]
+fn snapshot_import_errors() {
+  let core = mockcore::spawn();
+  core.mine_blocks(2);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let exported = tempdir.path().join("exported.redb");
+  let imported = tempdir.path().join("imported.redb");
+  let snapshot = tempdir.path().join("snapshot");
+
+  let manifest = CommandBuilder::new(format!(
+    "--index {} index export --snapshot {}",
+    exported.display(),
+    snapshot.display(),
+  ))
+  .core(&core)
+  .run_and_deserialize_output::<ord::index::snapshot::Manifest>();
+
+  let import = |index: &Path| {
+    CommandBuilder::new(format!(
+      "--index {} index import {}",
+      index.display(),
+      snapshot.display(),
+    ))
+    .core(&core)
+    .expected_exit_code(1)
+  };
+
+  import(&exported)
+    .expected_stderr(format!(
+      "error: index already exists at `{}`\n",
+      exported.display()
+    ))
+    .run_and_extract_stdout();
+
+  let mut bytes = fs::read(&snapshot).unwrap();
+  *bytes.last_mut().unwrap() ^= 1;
+  fs::write(&snapshot, &bytes).unwrap();
+
+  import(&imported)
+    .expected_stderr("error: snapshot checksum mismatch\n")
+    .run_and_extract_stdout();
+
+  assert!(!imported.exists());
+
+  *bytes.last_mut().unwrap() ^= 1;
+  fs::write(&snapshot, &bytes).unwrap();
+
+  core.invalidate_tip();
+  core.mine_blocks(2);
+
+  import(&imported)
+    .expected_stderr(format!(
+      "error: snapshot block {} at height 2 is not in the active chain\n",
+      manifest.block_hash
+    ))
+    .run_and_extract_stdout();
+
+  assert!(!imported.exists());
+}
+
+#[test]
+fn export_inscription_records() {
+  let core = mockcore::spawn();
+  let ord = TestServer::spawn(&core);
+
+  create_wallet(&core, &ord);
+
+  let (first, _) = inscribe(&core, &ord);
+
+  core.mine_blocks(1);
+
+  let batch = CommandBuilder::new(format!(
+    "wallet inscribe --fee-rate 1 --file foo.txt --parent {first} --metaprotocol bar"
+  ))
+  .write("foo.txt", "FOOBAR")
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Batch>();
+
+  core.mine_blocks(1);
+
+  let second = batch.inscriptions[0].id;
+
+  let ndjson = CommandBuilder::new("index export --inscriptions inscriptions.ndjson")
+    .core(&core)
+    .run_and_extract_file("inscriptions.ndjson");
+
+  let records = ndjson
+    .lines()
+    .map(serde_json::from_str::<ord::subcommand::index::export::InscriptionRecord>)
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+
+  assert_eq!(records.len(), 2);
+
+  assert_eq!(records[0].id, first);
+  assert_eq!(records[0].number, 0);
+  assert_eq!(records[0].sequence_number, 0);
+  assert_eq!(records[0].height, 2);
+  assert_eq!(
+    records[0].content_type.as_deref(),
+    Some("text/plain;charset=utf-8")
+  );
+  assert_eq!(records[0].content_length, Some(3));
+  assert_eq!(records[0].parents, Vec::new());
+  assert_eq!(records[0].metaprotocol, None);
+
+  assert_eq!(records[1].id, second);
+  assert_eq!(records[1].height, 4);
+  assert_eq!(records[1].content_length, Some(6));
+  assert_eq!(records[1].parents, vec![first]);
+  assert_eq!(records[1].metaprotocol.as_deref(), Some("bar"));
+  assert_eq!(records[1].satpoint, batch.inscriptions[0].location);
+  assert!(records[1].fee > 0);
+
+  let csv = CommandBuilder::new(
+    "index export --inscriptions inscriptions.csv --format csv --from-height 3 --to-height 4",
+  )
+  .core(&core)
+  .run_and_extract_file("inscriptions.csv");
+
+  let lines = csv.lines().collect::<Vec<&str>>();
+
+  assert_eq!(
+    lines[0],
+    "charms,content_length,content_type,delegate,fee,height,id,metaprotocol,number,parents,sat,\
+    satpoint,sequence_number,timestamp"
+  );
+
+  assert_eq!(lines.len(), 2);
+
+  assert_eq!(
+    lines[1],
+    format!(
+      "{},6,text/plain;charset=utf-8,,{},4,{second},bar,{},{first},,{},1,{}",
+      records[1]
+        .charms
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(";"),
+      records[1].fee,
+      records[1].number,
+      records[1].satpoint,
+      records[1].timestamp,
+    )
+  );
+
+  assert_eq!(
+    CommandBuilder::new("index export --inscriptions inscriptions.ndjson --to-height 1")
+      .core(&core)
+      .run_and_extract_file("inscriptions.ndjson"),
+    "",
+  );
+}
@@ -76,9 +76,12 @@ const RUNE: u128 = 99246114928149462;
 
 type Balance = ord::subcommand::wallet::balance::Output;
 type Batch = ord::wallet::batch::Output;
+type Broadcast = ord::subcommand::wallet::broadcast::Output;
+type Bump = ord::subcommand::wallet::bump::Output;
 type Create = ord::subcommand::wallet::create::Output;
 type Inscriptions = Vec<ord::subcommand::wallet::inscriptions::Output>;
 type Send = ord::subcommand::wallet::send::Output;
+type Split = ord::subcommand::wallet::split::Output;
 type Supply = ord::subcommand::supply::Output;
 
 fn create_wallet(core: &mockcore::Handle, ord: &TestServer) {
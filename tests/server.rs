@@ -574,6 +574,66 @@ fn run_no_sync() {
   child.kill().unwrap();
 }
 
+#[test]
+fn event_log() {
+  let core = mockcore::spawn();
+
+  let port = TcpListener::bind("127.0.0.1:0")
+    .unwrap()
+    .local_addr()
+    .unwrap()
+    .port();
+
+  let tempdir = Arc::new(TempDir::new().unwrap());
+
+  let log = tempdir.path().join("events.ndjson");
+
+  let builder = CommandBuilder::new(format!(
+    "server --address 127.0.0.1 --http-port {port} --event-log {}",
+    log.display()
+  ))
+  .core(&core)
+  .temp_dir(tempdir.clone());
+
+  let mut child = builder.command().spawn().unwrap();
+
+  core.mine_blocks(1);
+
+  let txid = core.broadcast_tx(TransactionTemplate {
+    inputs: &[(
+      1,
+      0,
+      0,
+      envelope(&[b"ord", &[1], b"text/plain;charset=utf-8", &[], b"foo"]),
+    )],
+    ..default()
+  });
+
+  core.mine_blocks(1);
+
+  for attempt in 0.. {
+    if let Some(event) = fs::read_to_string(&log).ok().and_then(|events| {
+      events
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|event| event["type"] == "inscription_created")
+    }) {
+      assert_eq!(event["inscription_id"], format!("{txid}i0"));
+      assert_eq!(event["block_height"], 2);
+      assert_eq!(event["block_hash"], core.state().hashes[2].to_string());
+      break;
+    }
+
+    if attempt == 100 {
+      panic!("no event written to event log");
+    }
+
+    thread::sleep(Duration::from_millis(50));
+  }
+
+  child.kill().unwrap();
+}
+
 #[test]
 fn authentication() {
   let core = mockcore::spawn();
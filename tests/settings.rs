@@ -6,11 +6,13 @@ fn default() {
     .integration_test(false)
     .stdout_regex(
       r#"\{
+  "api_keys": null,
   "bitcoin_data_dir": ".*(Bitcoin|bitcoin)",
   "bitcoin_rpc_password": null,
   "bitcoin_rpc_url": "127.0.0.1:8332",
   "bitcoin_rpc_username": null,
   "chain": "mainnet",
+  "chain_tip_distance": 21,
   "commit_interval": 5000,
   "config": null,
   "config_dir": null,
@@ -20,16 +22,23 @@ fn default() {
   "height_limit": null,
   "hidden": \[\],
   "index": ".*index\.redb",
+  "index_addresses": false,
   "index_cache_size": \d+,
   "index_runes": false,
   "index_sats": false,
+  "index_search": false,
   "index_spent_sats": false,
   "index_transactions": false,
   "integration_test": false,
+  "max_savepoints": 2,
   "no_index_inscriptions": false,
+  "savepoint_interval": 10,
   "server_password": null,
+  "server_rate_limit": null,
+  "server_trusted_proxies": null,
   "server_url": null,
-  "server_username": null
+  "server_username": null,
+  "undo_log_depth": 100
 \}
 "#,
     )
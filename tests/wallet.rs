@@ -3,6 +3,8 @@ use super::*;
 mod authentication;
 mod balance;
 mod batch_command;
+mod broadcast;
+mod bump;
 mod cardinals;
 mod create;
 mod dump;
@@ -18,4 +20,5 @@ mod resume;
 mod sats;
 mod selection;
 mod send;
+mod split;
 mod transactions;
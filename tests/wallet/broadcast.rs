@@ -0,0 +1,240 @@
+use {
+  super::*,
+  base64::Engine,
+  bitcoin::{absolute::LockTime, psbt::Psbt, ScriptBuf, Transaction, TxIn, TxOut},
+};
+
+#[test]
+fn send_dry_run_psbt_can_be_broadcast() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(1);
+
+  let (inscription, _) = inscribe(&core, &ord);
+
+  let send = CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 --dry-run bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 {inscription}",
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Send>();
+
+  assert!(core.mempool().is_empty());
+
+  let broadcast = CommandBuilder::new(format!("wallet broadcast {}", send.psbt))
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Broadcast>();
+
+  assert_eq!(broadcast.txid, send.txid);
+  assert_eq!(core.mempool().len(), 1);
+  assert_eq!(core.mempool()[0].txid(), send.txid);
+}
+
+#[test]
+fn inscribe_dry_run_psbts_can_be_broadcast() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(1);
+
+  let inscribe = CommandBuilder::new("wallet inscribe --dry-run --file foo.txt --fee-rate 1")
+    .write("foo.txt", "FOO")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Batch>();
+
+  let reveal_psbt = Psbt::deserialize(
+    &base64::engine::general_purpose::STANDARD
+      .decode(inscribe.reveal_psbt.as_ref().unwrap())
+      .unwrap(),
+  )
+  .unwrap();
+
+  let input = &reveal_psbt.inputs[0];
+
+  assert_eq!(
+    reveal_psbt.unsigned_tx.input[0].previous_output.txid,
+    inscribe.commit
+  );
+  assert!(input.witness_utxo.is_some());
+  assert!(input.final_script_witness.is_some());
+  assert!(input.tap_internal_key.is_some());
+  assert!(input.tap_merkle_root.is_some());
+  assert_eq!(input.tap_scripts.len(), 1);
+
+  CommandBuilder::new(format!(
+    "wallet broadcast {}",
+    inscribe.commit_psbt.unwrap()
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Broadcast>();
+
+  let broadcast = CommandBuilder::new(format!(
+    "wallet broadcast {}",
+    inscribe.reveal_psbt.unwrap()
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Broadcast>();
+
+  assert_eq!(broadcast.txid, inscribe.reveal);
+
+  core.mine_blocks(1);
+
+  ord.assert_response_regex(
+    format!("/inscription/{}", inscribe.inscriptions[0].id),
+    ".*<h1>Inscription 0</h1>.*",
+  );
+}
+
+#[test]
+fn broadcasting_etching_reveal_before_commitment_matures_is_an_error() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--regtest", "--index-runes"], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(1);
+
+  let batch = CommandBuilder::new(
+    "--regtest --index-runes wallet batch --fee-rate 0 --batch batch.yaml --dry-run",
+  )
+  .write(
+    "batch.yaml",
+    serde_yaml::to_string(&batch::File {
+      etching: Some(batch::Etching {
+        divisibility: 0,
+        rune: SpacedRune {
+          rune: Rune(RUNE),
+          spacers: 0,
+        },
+        supply: "1000".parse().unwrap(),
+        premine: "1000".parse().unwrap(),
+        symbol: '¢',
+        terms: None,
+        turbo: false,
+      }),
+      inscriptions: vec![batch::Entry {
+        file: Some("inscription.jpeg".into()),
+        ..default()
+      }],
+      ..default()
+    })
+    .unwrap(),
+  )
+  .write("inscription.jpeg", "inscription")
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Batch>();
+
+  CommandBuilder::new(format!(
+    "--regtest wallet broadcast {}",
+    batch.commit_psbt.unwrap()
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Broadcast>();
+
+  core.mine_blocks(1);
+
+  let reveal_psbt = batch.reveal_psbt.unwrap();
+
+  CommandBuilder::new(format!("--regtest wallet broadcast {reveal_psbt}"))
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .expected_stderr(format!(
+      "error: rune commitment {} has 1 confirmations, but reveal may only be broadcast once it has 5\n",
+      batch.commit
+    ))
+    .run_and_extract_stdout();
+
+  assert!(core.mempool().is_empty());
+
+  core.mine_blocks(4);
+
+  let broadcast = CommandBuilder::new(format!("--regtest wallet broadcast {reveal_psbt}"))
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Broadcast>();
+
+  assert_eq!(broadcast.txid, batch.reveal);
+}
+
+#[test]
+fn broadcasting_psbt_that_sends_inscription_to_fees_is_an_error() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(1);
+
+  let (inscription, reveal) = inscribe(&core, &ord);
+
+  let coinbase = core.mine_blocks(1)[0].txdata[0].txid();
+
+  let psbt = Psbt::from_unsigned_tx(Transaction {
+    version: 2,
+    lock_time: LockTime::ZERO,
+    input: [OutPoint::new(coinbase, 0), OutPoint::new(reveal, 0)]
+      .into_iter()
+      .map(|previous_output| TxIn {
+        previous_output,
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::new(),
+      })
+      .collect(),
+    output: vec![TxOut {
+      script_pubkey: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        .parse::<Address<NetworkUnchecked>>()
+        .unwrap()
+        .assume_checked()
+        .script_pubkey(),
+      value: 100_000_000,
+    }],
+  })
+  .unwrap();
+
+  CommandBuilder::new(format!(
+    "wallet broadcast {}",
+    base64::engine::general_purpose::STANDARD.encode(psbt.serialize())
+  ))
+  .core(&core)
+  .ord(&ord)
+  .expected_exit_code(1)
+  .expected_stderr(format!(
+    "error: inscription {inscription} would be lost to fees\n"
+  ))
+  .run_and_extract_stdout();
+
+  assert!(core.mempool().is_empty());
+}
+
+#[test]
+fn broadcasting_invalid_psbt_is_an_error() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  CommandBuilder::new("wallet broadcast foo")
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .stderr_regex("error: failed to decode PSBT.*")
+    .run_and_extract_stdout();
+}
@@ -0,0 +1,214 @@
+use super::*;
+
+#[test]
+fn send_can_be_replaced_with_higher_fee() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(2);
+
+  let (inscription, _) = inscribe(&core, &ord);
+
+  let send = CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 {inscription}",
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Send>();
+
+  let original = core.mempool()[0].clone();
+
+  let bump = CommandBuilder::new(format!("wallet bump --fee-rate 10 {}", send.txid))
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Bump>();
+
+  assert_eq!(bump.original, send.txid);
+
+  let mempool = core.mempool();
+  assert_eq!(mempool.len(), 1);
+
+  let replacement = &mempool[0];
+  assert_eq!(replacement.txid(), bump.txid);
+  assert_eq!(
+    replacement.input[..original.input.len()]
+      .iter()
+      .map(|tx_in| tx_in.previous_output)
+      .collect::<Vec<OutPoint>>(),
+    original
+      .input
+      .iter()
+      .map(|tx_in| tx_in.previous_output)
+      .collect::<Vec<OutPoint>>(),
+  );
+  assert_eq!(
+    replacement.output[..original.output.len()],
+    original.output[..]
+  );
+  assert!(bump.fee >= 10 * u64::try_from(replacement.vsize()).unwrap());
+
+  core.mine_blocks(1);
+
+  ord.assert_response_regex(
+    format!("/inscription/{inscription}"),
+    format!(
+      ".*<dt>address</dt>\\s*<dd class=monospace>bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4</dd>.*<dt>location</dt>\\s*<dd class=monospace>{}:0:0</dd>.*",
+      bump.txid
+    ),
+  );
+}
+
+#[test]
+fn stuck_reveal_can_be_replaced() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(2);
+
+  let inscribe = CommandBuilder::new("wallet inscribe --file foo.txt --fee-rate 1")
+    .write("foo.txt", "FOO")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Batch>();
+
+  let original = core
+    .mempool()
+    .into_iter()
+    .find(|transaction| transaction.txid() == inscribe.reveal)
+    .unwrap();
+
+  let bump = CommandBuilder::new(format!("wallet bump --fee-rate 10 {}", inscribe.reveal))
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Bump>();
+
+  let mempool = core.mempool();
+  assert_eq!(mempool.len(), 2);
+  assert!(mempool
+    .iter()
+    .any(|transaction| transaction.txid() == inscribe.commit));
+
+  let replacement = mempool
+    .iter()
+    .find(|transaction| transaction.txid() == bump.txid)
+    .unwrap();
+  assert_eq!(
+    replacement.input[0].previous_output,
+    original.input[0].previous_output
+  );
+  assert_eq!(
+    replacement.input[0].witness.tapscript(),
+    original.input[0].witness.tapscript()
+  );
+  assert_ne!(replacement.input[0].witness, original.input[0].witness);
+  assert_eq!(replacement.output[0], original.output[0]);
+  assert!(bump.fee >= 10 * u64::try_from(replacement.vsize()).unwrap());
+
+  core.mine_blocks(1);
+
+  ord.assert_response_regex(
+    format!("/inscription/{}i0", bump.txid),
+    format!(
+      ".*<dt>location</dt>\\s*<dd class=monospace>{}:0:0</dd>.*",
+      bump.txid
+    ),
+  );
+}
+
+#[test]
+fn replacing_reveal_without_recovery_key_is_an_error() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(2);
+
+  let inscribe = CommandBuilder::new("wallet inscribe --file foo.txt --fee-rate 1 --no-backup")
+    .write("foo.txt", "FOO")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Batch>();
+
+  CommandBuilder::new(format!("wallet bump --fee-rate 10 {}", inscribe.reveal))
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .expected_stderr(format!(
+      "error: unable to re-sign reveal transaction {}, use `--cpfp`\nbecause: recovery key for input 0 not found\n",
+      inscribe.reveal
+    ))
+    .run_and_extract_stdout();
+}
+
+#[test]
+fn stuck_reveal_can_be_bumped_with_child() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(2);
+
+  let inscribe = CommandBuilder::new("wallet inscribe --file foo.txt --fee-rate 1")
+    .write("foo.txt", "FOO")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<Batch>();
+
+  let bump = CommandBuilder::new(format!(
+    "wallet bump --cpfp --fee-rate 10 {}",
+    inscribe.reveal
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Bump>();
+
+  let mempool = core.mempool();
+  assert_eq!(mempool.len(), 3);
+
+  let child = mempool
+    .iter()
+    .find(|transaction| transaction.txid() == bump.txid)
+    .unwrap();
+  assert_eq!(
+    child.input[0].previous_output,
+    OutPoint::new(inscribe.reveal, 0)
+  );
+
+  core.mine_blocks(1);
+
+  ord.assert_response_regex(
+    format!("/inscription/{}", inscribe.inscriptions[0].id),
+    format!(
+      ".*<dt>location</dt>\\s*<dd class=monospace>{}:0:0</dd>.*",
+      bump.txid
+    ),
+  );
+}
+
+#[test]
+fn bumping_confirmed_transaction_is_an_error() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  let txid = core.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!("wallet bump --fee-rate 10 {txid}"))
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .stderr_regex(format!("error: transaction {txid} not found in mempool.*"))
+    .run_and_extract_stdout();
+}
@@ -489,3 +489,67 @@ fn minting_rune_with_postage_dust() {
   .expected_stderr("error: postage below dust limit of 330sat\n")
   .run_and_extract_stdout();
 }
+
+#[test]
+fn minting_rune_with_dry_run_returns_psbt() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  core.mine_blocks(1);
+
+  create_wallet(&core, &ord);
+
+  batch(
+    &core,
+    &ord,
+    batch::File {
+      etching: Some(batch::Etching {
+        divisibility: 0,
+        rune: SpacedRune {
+          rune: Rune(RUNE),
+          spacers: 0,
+        },
+        premine: "0".parse().unwrap(),
+        supply: "21".parse().unwrap(),
+        symbol: '¢',
+        turbo: false,
+        terms: Some(batch::Terms {
+          cap: 1,
+          offset: Some(batch::Range {
+            end: Some(10),
+            start: None,
+          }),
+          amount: "21".parse().unwrap(),
+          height: None,
+        }),
+      }),
+      inscriptions: vec![batch::Entry {
+        file: Some("inscription.jpeg".into()),
+        ..default()
+      }],
+      ..default()
+    },
+  );
+
+  let output = CommandBuilder::new(format!(
+    "--chain regtest --index-runes wallet mint --fee-rate 1 --rune {} --dry-run",
+    Rune(RUNE)
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<mint::Output>();
+
+  assert!(core.mempool().is_empty());
+
+  let broadcast = CommandBuilder::new(format!(
+    "--chain regtest --index-runes wallet broadcast {}",
+    output.psbt.unwrap()
+  ))
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Broadcast>();
+
+  assert_eq!(broadcast.txid, output.mint);
+  assert_eq!(core.mempool()[0].txid(), output.mint);
+}
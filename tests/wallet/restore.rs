@@ -1,4 +1,11 @@
-use {super::*, ord::subcommand::wallet::create};
+use {
+  super::*,
+  bitcoin::{
+    bip32::{DerivationPath, ExtendedPrivKey, ExtendedPubKey},
+    secp256k1::Secp256k1,
+  },
+  ord::subcommand::wallet::create,
+};
 
 #[test]
 fn restore_generates_same_descriptors() {
@@ -194,3 +201,161 @@ fn passphrase_conflicts_with_descriptor() {
   .expected_stderr("error: descriptor does not take a passphrase\n")
   .run_and_extract_stdout();
 }
+
+fn xpub(network: Network) -> String {
+  let secp = Secp256k1::new();
+
+  let master = ExtendedPrivKey::new_master(network, &[0; 64]).unwrap();
+
+  let path = "m/86'/0'/0'".parse::<DerivationPath>().unwrap();
+
+  let xpub = ExtendedPubKey::from_priv(&secp, &master.derive_priv(&secp, &path).unwrap());
+
+  format!("[{}/86'/0'/0']{xpub}", master.fingerprint(&secp))
+}
+
+#[test]
+fn restore_from_xpub_creates_watch_only_wallet() {
+  let core = mockcore::spawn();
+  let ord = TestServer::spawn(&core);
+
+  let xpub = xpub(Network::Bitcoin);
+
+  CommandBuilder::new("wallet restore --from xpub")
+    .stdin(xpub.clone().into())
+    .core(&core)
+    .run_and_extract_stdout();
+
+  let descriptors = core.descriptors();
+
+  assert_eq!(descriptors.len(), 2);
+  assert!(descriptors[0].starts_with(&format!("tr({xpub}/0/*)#")));
+  assert!(descriptors[1].starts_with(&format!("tr({xpub}/1/*)#")));
+  assert!(core.state().watch_only_wallets.contains("ord"));
+
+  core.mine_blocks(1);
+
+  assert_eq!(
+    CommandBuilder::new("wallet balance")
+      .core(&core)
+      .ord(&ord)
+      .run_and_deserialize_output::<Balance>()
+      .cardinal,
+    50 * COIN_VALUE
+  );
+
+  CommandBuilder::new("wallet dump")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<ListDescriptorsResult>();
+
+  CommandBuilder::new("wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1000sat")
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .expected_stderr("error: wallet is watch-only and cannot sign transactions, use `--dry-run` to create a PSBT and sign it elsewhere\n")
+    .run_and_extract_stdout();
+
+  CommandBuilder::new(
+    "wallet send --fee-rate 1 --dry-run bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1000sat",
+  )
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Send>();
+
+  assert!(core.mempool().is_empty());
+}
+
+#[test]
+fn inscribing_from_watch_only_wallet_requires_no_backup() {
+  let core = mockcore::spawn();
+  let ord = TestServer::spawn(&core);
+
+  CommandBuilder::new("wallet restore --from xpub")
+    .stdin(xpub(Network::Bitcoin).into())
+    .core(&core)
+    .run_and_extract_stdout();
+
+  core.mine_blocks(1);
+
+  CommandBuilder::new("wallet inscribe --fee-rate 1 --dry-run --file foo.txt")
+    .write("foo.txt", "FOO")
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .expected_stderr("error: watch-only wallets cannot back up the recovery key, which is needed to recover the commit output if the reveal is not mined, use `--no-backup` to continue without it\n")
+    .run_and_extract_stdout();
+
+  let output =
+    CommandBuilder::new("wallet inscribe --fee-rate 1 --dry-run --no-backup --file foo.txt")
+      .write("foo.txt", "FOO")
+      .core(&core)
+      .ord(&ord)
+      .run_and_deserialize_output::<Batch>();
+
+  assert!(output.commit_psbt.is_some());
+  assert!(output.reveal_psbt.is_some());
+  assert!(core.mempool().is_empty());
+}
+
+#[test]
+fn restore_from_xpub_for_wrong_network_fails() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  CommandBuilder::new("--regtest wallet restore --from xpub")
+    .stdin(xpub(Network::Bitcoin).into())
+    .core(&core)
+    .expected_exit_code(1)
+    .expected_stderr("error: extended public key is not for regtest\n")
+    .run_and_extract_stdout();
+
+  assert!(core.wallets().is_empty());
+}
+
+#[test]
+fn restore_from_public_descriptors_creates_watch_only_wallet() {
+  let core = mockcore::spawn();
+
+  let xpub = xpub(Network::Bitcoin);
+
+  let descriptors = [false, true]
+    .into_iter()
+    .map(|internal| {
+      let desc = format!("tr({xpub}/{}/*)", u8::from(internal))
+        .parse::<miniscript::Descriptor<miniscript::DescriptorPublicKey>>()
+        .unwrap()
+        .to_string();
+
+      format!(
+        r#"{{"desc":"{desc}","timestamp":1706047839,"active":true,"internal":{internal},"range":[0,1000],"next":1}}"#
+      )
+    })
+    .collect::<Vec<String>>()
+    .join(",");
+
+  CommandBuilder::new("wallet restore --from descriptor")
+    .stdin(format!(r#"{{"wallet_name":"foo","descriptors":[{descriptors}]}}"#).into())
+    .core(&core)
+    .run_and_extract_stdout();
+
+  assert_eq!(core.descriptors().len(), 2);
+  assert!(core.state().watch_only_wallets.contains("ord"));
+}
+
+#[test]
+fn restore_from_public_and_private_descriptors_fails() {
+  let core = mockcore::spawn();
+
+  let public = format!("tr({}/1/*)", xpub(Network::Bitcoin))
+    .parse::<miniscript::Descriptor<miniscript::DescriptorPublicKey>>()
+    .unwrap();
+
+  CommandBuilder::new("wallet restore --from descriptor")
+    .stdin(format!(r#"{{"wallet_name":"foo","descriptors":[{{"desc":"tr([c0b9536d/86'/1'/0']tprv8fXhtVjj3vb7kgxKuiWXzcUsur44gbLbbtwxL4HKmpzkBNoMrYqbQhMe7MWhrZjLFc9RBpTRYZZkrS8HH1Q3SmD5DkfpjKqtd97q1JWfqzr/0/*)#dweuu0ww","timestamp":1706047839,"active":true,"internal":false,"range":[0,1000],"next":1}},{{"desc":"{public}","timestamp":1706047839,"active":true,"internal":true,"range":[0,1013],"next":14}}]}}"#).into())
+    .core(&core)
+    .expected_exit_code(1)
+    .expected_stderr("error: descriptors must either all contain private keys or all be public\n")
+    .run_and_extract_stdout();
+
+  assert!(core.wallets().is_empty());
+}
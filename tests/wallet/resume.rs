@@ -83,6 +83,24 @@ fn wallet_resume() {
 
   core.mine_blocks(6);
 
+  let output = CommandBuilder::new("--regtest --index-runes wallet resume --dry-run")
+    .temp_dir(tempdir.clone())
+    .core(&core)
+    .ord(&ord)
+    .spawn()
+    .child
+    .wait_with_output()
+    .unwrap();
+
+  assert!(output.status.success());
+
+  let output =
+    serde_json::from_slice::<ord::subcommand::wallet::resume::ResumeOutput>(&output.stdout)
+      .unwrap();
+
+  assert!(!output.etchings.first().unwrap().reveal_broadcast);
+  assert!(output.etchings.first().unwrap().reveal_psbt.is_some());
+
   let output = CommandBuilder::new("--regtest --index-runes wallet resume")
     .temp_dir(tempdir)
     .core(&core)
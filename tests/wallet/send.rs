@@ -1110,3 +1110,150 @@ fn error_messages_use_spaced_runes() {
     .expected_stderr("error: rune `FOO` has not been etched\n")
     .run_and_extract_stdout();
 }
+
+#[test]
+fn batch_send_sends_many_inscriptions_in_one_transaction() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  let (a, _) = inscribe(&core, &ord);
+  let (b, _) = inscribe(&core, &ord);
+  let (c, _) = inscribe(&core, &ord);
+
+  let output = CommandBuilder::new("wallet send --fee-rate 1 --batch batch.yaml")
+    .write(
+      "batch.yaml",
+      format!(
+        "sends:
+- destination: bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4
+  outgoing: {a}
+- destination: bc1qcqgs2pps4u4yedfyl5pysdjjncs8et5utseepv
+  outgoing: {b}
+  postage: 1000
+- destination: bc1pxwww0ct9ue7e8tdnlmug5m2tamfn7q06sahstg39ys4c9f3340qqxrdu9k
+  outgoing: {c}
+"
+      ),
+    )
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<ord::subcommand::wallet::send::BatchOutput>();
+
+  assert_eq!(core.mempool().len(), 1);
+  assert_eq!(core.mempool()[0].txid(), output.txid);
+  assert_eq!(output.sends.len(), 3);
+
+  core.mine_blocks(1);
+
+  let tx = core.tx_by_id(output.txid);
+
+  for (inscription, vout, address, postage) in [
+    (a, 0, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", 10_000),
+    (b, 1, "bc1qcqgs2pps4u4yedfyl5pysdjjncs8et5utseepv", 1000),
+    (
+      c,
+      3,
+      "bc1pxwww0ct9ue7e8tdnlmug5m2tamfn7q06sahstg39ys4c9f3340qqxrdu9k",
+      10_000,
+    ),
+  ] {
+    let response = ord.json_request(format!("/inscription/{inscription}"));
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let info = serde_json::from_str::<api::Inscription>(&response.text().unwrap()).unwrap();
+
+    assert_eq!(
+      info.satpoint,
+      SatPoint {
+        outpoint: OutPoint {
+          txid: output.txid,
+          vout
+        },
+        offset: 0,
+      }
+    );
+
+    assert_eq!(
+      tx.output[usize::try_from(vout).unwrap()].script_pubkey,
+      address
+        .parse::<Address<NetworkUnchecked>>()
+        .unwrap()
+        .assume_checked()
+        .script_pubkey(),
+    );
+
+    assert_eq!(tx.output[usize::try_from(vout).unwrap()].value, postage);
+  }
+
+  assert_eq!(tx.output[2].value, 9000);
+
+  assert!(output.fee > 0);
+}
+
+#[test]
+fn batch_send_with_dry_run_does_not_broadcast() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  let (inscription, _) = inscribe(&core, &ord);
+
+  CommandBuilder::new("wallet send --fee-rate 1 --dry-run --batch batch.yaml")
+    .write(
+      "batch.yaml",
+      format!(
+        "sends:
+- destination: bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4
+  outgoing: {inscription}
+"
+      ),
+    )
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<ord::subcommand::wallet::send::BatchOutput>();
+
+  assert!(core.mempool().is_empty());
+}
+
+#[test]
+fn batch_send_may_not_contain_amounts() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn_with_server_args(&core, &[], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(1);
+
+  CommandBuilder::new("wallet send --fee-rate 1 --batch batch.yaml")
+    .write(
+      "batch.yaml",
+      "sends:
+- destination: bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4
+  outgoing: 1 btc
+",
+    )
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: batch sends may only contain inscriptions, sats, and satpoints, not `1 btc`\n",
+    )
+    .run_and_extract_stdout();
+}
+
+#[test]
+fn batch_send_conflicts_with_address() {
+  CommandBuilder::new(
+    "wallet send --fee-rate 1 --batch batch.yaml bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1btc",
+  )
+  .expected_exit_code(2)
+  .stderr_regex(".*cannot be used with.*")
+  .run_and_extract_stdout();
+}
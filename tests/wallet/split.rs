@@ -0,0 +1,416 @@
+use {super::*, base64::Engine, bitcoin::psbt::Psbt, ord::subcommand::wallet::receive};
+
+fn address(n: u8) -> Address {
+  Address::p2wsh(&bitcoin::ScriptBuf::from_bytes(vec![n]), Network::Regtest)
+}
+
+#[test]
+fn split_sends_runes_to_many_outputs() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  etch(&core, &ord, Rune(RUNE));
+
+  let output = CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.yaml",
+  )
+  .write(
+    "splits.yaml",
+    format!(
+      "outputs:
+- address: {}
+  rune: {}
+  amount: 100
+- address: {}
+  rune: {}
+  amount: 200
+  postage: 5000
+- address: {}
+  rune: {}
+  amount: 50
+",
+      address(0),
+      Rune(RUNE),
+      address(1),
+      Rune(RUNE),
+      address(0),
+      Rune(RUNE),
+    ),
+  )
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Split>();
+
+  assert_eq!(output.transactions.len(), 1);
+
+  let txid = output.transactions[0].txid;
+
+  core.mine_blocks(1);
+
+  let tx = core.tx_by_id(txid);
+
+  assert_eq!(tx.output[2].script_pubkey, address(0).script_pubkey());
+  assert_eq!(tx.output[2].value, 10_000);
+  assert_eq!(tx.output[3].script_pubkey, address(1).script_pubkey());
+  assert_eq!(tx.output[3].value, 5000);
+
+  let balances = CommandBuilder::new("--regtest --index-runes balances")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<ord::subcommand::balances::Output>();
+
+  let pile = |amount| Pile {
+    amount,
+    divisibility: 0,
+    symbol: Some('¢'),
+  };
+
+  assert_eq!(
+    balances,
+    ord::subcommand::balances::Output {
+      runes: [(
+        SpacedRune::new(Rune(RUNE), 0),
+        [
+          (OutPoint { txid, vout: 1 }, pile(650)),
+          (OutPoint { txid, vout: 2 }, pile(150)),
+          (OutPoint { txid, vout: 3 }, pile(200)),
+        ]
+        .into()
+      )]
+      .into(),
+    }
+  );
+}
+
+#[test]
+fn split_reads_csv_files() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  let etched = etch(&core, &ord, Rune(RUNE));
+
+  let output = CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.csv",
+  )
+  .write(
+    "splits.csv",
+    format!(
+      "address,rune,amount,postage\n{},{},400,\n{},{},600,1000\n",
+      address(0),
+      Rune(RUNE),
+      address(1),
+      Rune(RUNE),
+    ),
+  )
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Split>();
+
+  assert_eq!(output.transactions.len(), 1);
+
+  core.mine_blocks(1);
+
+  let tx = core.tx_by_id(output.transactions[0].txid);
+
+  assert_eq!(tx.output[2].value, 10_000);
+  assert_eq!(tx.output[3].value, 1000);
+
+  assert_eq!(
+    Runestone::decipher(&tx),
+    Some(Artifact::Runestone(Runestone {
+      edicts: vec![
+        Edict {
+          id: etched.id,
+          amount: 400,
+          output: 2,
+        },
+        Edict {
+          id: etched.id,
+          amount: 600,
+          output: 3,
+        },
+      ],
+      ..default()
+    })),
+  );
+}
+
+#[test]
+fn split_with_too_many_outputs_for_one_runestone_uses_multiple_transactions() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  etch(&core, &ord, Rune(RUNE));
+
+  let receive = CommandBuilder::new("--regtest --index-runes wallet receive --number 2")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<receive::Output>();
+
+  CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.yaml",
+  )
+  .write(
+    "splits.yaml",
+    format!(
+      "outputs:
+- address: {}
+  rune: {}
+  amount: 500
+- address: {}
+  rune: {}
+  amount: 500
+",
+      receive.addresses[0].clone().assume_checked(),
+      Rune(RUNE),
+      receive.addresses[1].clone().assume_checked(),
+      Rune(RUNE),
+    ),
+  )
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Split>();
+
+  core.mine_blocks(1);
+
+  let mut splits = "outputs:\n".to_string();
+
+  for n in 0..30 {
+    splits.push_str(&format!(
+      "- address: {}\n  rune: {}\n  amount: 10\n",
+      address(n),
+      Rune(RUNE)
+    ));
+  }
+
+  let output = CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.yaml",
+  )
+  .write("splits.yaml", splits)
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Split>();
+
+  assert_eq!(output.transactions.len(), 2);
+
+  core.mine_blocks(1);
+
+  let mut recipients = 0;
+
+  for transaction in &output.transactions {
+    let tx = core.tx_by_id(transaction.txid);
+
+    assert!(tx.output[0].script_pubkey.len() <= 83);
+
+    let Some(Artifact::Runestone(runestone)) = Runestone::decipher(&tx) else {
+      panic!("transaction does not contain runestone");
+    };
+
+    for edict in runestone.edicts {
+      assert_eq!(edict.amount, 10);
+      assert_eq!(
+        tx.output[usize::try_from(edict.output).unwrap()].script_pubkey,
+        address(recipients).script_pubkey(),
+      );
+      recipients += 1;
+    }
+  }
+
+  assert_eq!(recipients, 30);
+}
+
+#[test]
+fn split_from_single_rune_output_chains_transactions() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  etch(&core, &ord, Rune(RUNE));
+
+  let mut splits = "outputs:\n".to_string();
+
+  for n in 0..40 {
+    splits.push_str(&format!(
+      "- address: {}\n  rune: {}\n  amount: 10\n",
+      address(n),
+      Rune(RUNE)
+    ));
+  }
+
+  let output = CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.yaml",
+  )
+  .write("splits.yaml", splits)
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Split>();
+
+  assert!(output.transactions.len() > 1);
+
+  for (previous, transaction) in output.transactions.iter().zip(&output.transactions[1..]) {
+    assert!(core
+      .mempool()
+      .iter()
+      .find(|tx| tx.txid() == transaction.txid)
+      .unwrap()
+      .input
+      .iter()
+      .any(|txin| txin.previous_output == OutPoint::new(previous.txid, 1)));
+  }
+
+  core.mine_blocks(1);
+
+  let balances = CommandBuilder::new("--regtest --index-runes balances")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<ord::subcommand::balances::Output>();
+
+  let outputs = &balances.runes[&SpacedRune::new(Rune(RUNE), 0)];
+
+  assert_eq!(outputs.len(), 41);
+
+  assert_eq!(
+    outputs[&OutPoint::new(output.transactions.last().unwrap().txid, 1)].amount,
+    600,
+  );
+
+  assert_eq!(outputs.values().map(|pile| pile.amount).sum::<u128>(), 1000);
+}
+
+#[test]
+fn split_dry_run_does_not_lock_utxos() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  etch(&core, &ord, Rune(RUNE));
+
+  let mut splits = "outputs:\n".to_string();
+
+  for n in 0..40 {
+    splits.push_str(&format!(
+      "- address: {}\n  rune: {}\n  amount: 10\n",
+      address(n),
+      Rune(RUNE)
+    ));
+  }
+
+  let output = CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --dry-run --fee-rate 1 --splits splits.yaml",
+  )
+  .write("splits.yaml", splits)
+  .core(&core)
+  .ord(&ord)
+  .run_and_deserialize_output::<Split>();
+
+  assert!(output.transactions.len() > 1);
+  assert!(core.mempool().is_empty());
+
+  let balances = CommandBuilder::new("--regtest --index-runes balances")
+    .core(&core)
+    .ord(&ord)
+    .run_and_deserialize_output::<ord::subcommand::balances::Output>();
+
+  let runic = &balances.runes[&SpacedRune::new(Rune(RUNE), 0)];
+
+  let locked = core.get_locked();
+
+  for transaction in &output.transactions {
+    let psbt = Psbt::deserialize(
+      &base64::engine::general_purpose::STANDARD
+        .decode(&transaction.psbt)
+        .unwrap(),
+    )
+    .unwrap();
+
+    for txin in &psbt.unsigned_tx.input {
+      assert!(runic.contains_key(&txin.previous_output) || !locked.contains(&txin.previous_output));
+    }
+  }
+}
+
+#[test]
+fn split_with_insufficient_balance_is_an_error() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  etch(&core, &ord, Rune(RUNE));
+
+  CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.csv",
+  )
+  .write(
+    "splits.csv",
+    format!(
+      "{},{},600\n{},{},600\n",
+      address(0),
+      Rune(RUNE),
+      address(1),
+      Rune(RUNE),
+    ),
+  )
+  .core(&core)
+  .ord(&ord)
+  .expected_exit_code(1)
+  .expected_stderr("error: insufficient `AAAAAAAAAAAAA` balance, only 1000\u{A0}¢ in wallet\n")
+  .run_and_extract_stdout();
+}
+
+#[test]
+fn split_of_rune_that_has_not_been_etched_is_an_error() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-runes", "--regtest"], &[]);
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(1);
+
+  CommandBuilder::new(
+    "--chain regtest --index-runes wallet split --fee-rate 1 --splits splits.csv",
+  )
+  .write("splits.csv", format!("{},FOO,1\n", address(0)))
+  .core(&core)
+  .ord(&ord)
+  .expected_exit_code(1)
+  .expected_stderr("error: rune `FOO` has not been etched\n")
+  .run_and_extract_stdout();
+}
+
+#[test]
+fn split_requires_rune_index() {
+  let core = mockcore::spawn();
+
+  let ord = TestServer::spawn(&core);
+
+  create_wallet(&core, &ord);
+
+  CommandBuilder::new("wallet split --fee-rate 1 --splits splits.csv")
+    .write(
+      "splits.csv",
+      "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4,FOO,1\n",
+    )
+    .core(&core)
+    .ord(&ord)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: sending runes with `ord wallet split` requires index created with `--index-runes` flag\n",
+    )
+    .run_and_extract_stdout();
+}